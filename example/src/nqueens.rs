@@ -24,9 +24,32 @@ use pcp::concept::*;
 use interval::ops::Range;
 use interval::interval_set::*;
 use gcollections::ops::*;
+use pcp::propagation::Scheduler;
+use pcp::propagation::schedulers::*;
 
 pub fn nqueens(n: usize) {
-  let mut space = FDSpace::empty();
+  let (space, status) = solve_nqueens::<RelaxedFifo>(n);
+
+  // Print result.
+  match status {
+    Satisfiable => {
+      print!("{}-queens problem is satisfiable. The first solution is:\n[", n);
+      for dom in space.vstore.iter() {
+        // At this stage, dom.lower() == dom.upper().
+        print!("{}, ", dom.lower());
+      }
+      println!("]");
+    }
+    Unsatisfiable => println!("{}-queens problem is unsatisfiable.", n),
+    EndOfSearch => println!("Search terminated or was interrupted."),
+    Unknown(_) => unreachable!(
+      "After the search step, the problem instance should be either satisfiable or unsatisfiable.")
+  }
+}
+
+/// Solves the `n`-queens problem with a constraints store scheduling the propagators with `S`.
+pub fn solve_nqueens<S: Scheduler + 'static>(n: usize) -> (FDSpaceWith<S>, Status<FDSpaceWith<S>>) {
+  let mut space = FDSpaceWith::<S>::empty();
 
   let mut queens = vec![];
   // 2 queens can't share the same line.
@@ -51,24 +74,25 @@ pub fn nqueens(n: usize) {
   // space.cstore.alloc(Box::new(Distinct::new(queens)));
 
   // Search step.
-  let mut search = one_solution_engine();
+  let mut search = one_solution_engine_with::<S>();
   search.start(&space);
   let (frozen_space, status) = search.enter(space);
-  let space = frozen_space.unfreeze();
+  (frozen_space.unfreeze(), status)
+}
 
-  // Print result.
-  match status {
-    Satisfiable => {
-      print!("{}-queens problem is satisfiable. The first solution is:\n[", n);
-      for dom in space.vstore.iter() {
-        // At this stage, dom.lower() == dom.upper().
-        print!("{}, ", dom.lower());
-      }
-      println!("]");
-    }
-    Unsatisfiable => println!("{}-queens problem is unsatisfiable.", n),
-    EndOfSearch => println!("Search terminated or was interrupted."),
-    Unknown(_) => unreachable!(
-      "After the search step, the problem instance should be either satisfiable or unsatisfiable.")
+#[cfg(test)]
+#[cfg(feature = "nightly")]
+mod tests {
+  use super::*;
+  use test::Bencher;
+
+  #[bench]
+  fn bench_nqueens_relaxed_fifo(b: &mut Bencher) {
+    b.iter(|| solve_nqueens::<RelaxedFifo>(30));
+  }
+
+  #[bench]
+  fn bench_nqueens_cost_queues(b: &mut Bencher) {
+    b.iter(|| solve_nqueens::<CostQueues>(30));
   }
-}
\ No newline at end of file
+}
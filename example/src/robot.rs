@@ -55,12 +55,14 @@ use pcp::term::*;
 use pcp::propagators::cumulative::Cumulative;
 use pcp::model::*;
 use pcp::concept::*;
+use pcp::propagation::Scheduler;
+use pcp::propagation::schedulers::*;
 use std::fmt::{Formatter, Display, Error};
 
 pub type Bound = i32;
 pub type Domain = IntervalSet<Bound>;
 
-pub struct RobotScheduling {
+pub struct RobotScheduling<S: Scheduler = RelaxedFifo> {
   pub num_robot: usize,
   pub max_time: usize,
   pub start: Vec<Var<VStore>>,
@@ -69,8 +71,8 @@ pub struct RobotScheduling {
   pub pipeting_duration: Vec<Var<VStore>>,
   pub pipeting_resource: Vec<Var<VStore>>,
   pub model: Model,
-  pub space: FDSpace,
-  pub status: Status<FDSpace>,
+  pub space: FDSpaceWith<S>,
+  pub status: Status<FDSpaceWith<S>>,
 }
 
 pub enum RobotType {
@@ -89,6 +91,14 @@ static E: usize = 4; // End, go to park
 impl RobotScheduling
 {
   pub fn new(num_robot: usize, max_time: usize) -> Self {
+    RobotScheduling::with_scheduler(num_robot, max_time)
+  }
+}
+
+impl<S: Scheduler + 'static> RobotScheduling<S>
+{
+  /// The propagators of the constraints store are scheduled with `S`.
+  pub fn with_scheduler(num_robot: usize, max_time: usize) -> Self {
     let mut robot = RobotScheduling {
       num_robot: num_robot,
       max_time: max_time,
@@ -98,7 +108,7 @@ impl RobotScheduling
       pipeting_duration: vec![],
       pipeting_resource: vec![],
       model: Model::new(),
-      space: FDSpace::empty(),
+      space: FDSpaceWith::<S>::empty(),
       status: Status::Unsatisfiable,
     };
     robot.initialize();
@@ -151,26 +161,18 @@ impl RobotScheduling
       self.pipeting_resource.push(Box::new(Constant::new(1)));
     }
 
-    let mut cumulative_pipeting = Cumulative::new(
+    let cumulative_pipeting = Cumulative::new(
       self.pipeting_start.iter().map(|v| v.bclone()).collect(),
       self.pipeting_duration.iter().map(|v| v.bclone()).collect(),
       self.pipeting_resource.iter().map(|v| v.bclone()).collect(),
       Box::new(Constant::new(1))
     );
-    cumulative_pipeting.join(&mut self.space.vstore, &mut self.space.cstore);
-    let inter_tasks = cumulative_pipeting.intermediate_vars();
-    for (nti, ti) in inter_tasks.into_iter().enumerate() {
-      for (ntj, var) in ti.into_iter().enumerate() {
-        self.model.register_var(
-          var,
-          format!("t{}_{}", nti, ntj));
-      }
-    }
+    self.space.cstore.alloc(Box::new(cumulative_pipeting));
   }
 
   pub fn solve(mut self) -> Self {
     let search =
-      OneSolution::<_, VectorStack<_>, FDSpace>::new(
+      OneSolution::<_, VectorStack<_>, FDSpaceWith<S>>::new(
       // Debugger::new(self.model.clone(),
       Propagation::new(
       Brancher::new(InputOrder, MinVal, Enumerate)));
@@ -191,7 +193,7 @@ impl RobotScheduling
   }
 }
 
-impl Display for RobotScheduling
+impl<S: Scheduler + 'static> Display for RobotScheduling<S>
 {
   fn fmt(&self, fmt: &mut Formatter) -> Result<(), Error> {
     use pcp::search::search_tree_visitor::Status::*;
@@ -233,4 +235,9 @@ mod tests {
   fn bench_schedule_2(b: &mut Bencher) {
     b.iter(|| RobotScheduling::new(2, 500).solve());
   }
+
+  #[bench]
+  fn bench_schedule_2_cost_queues(b: &mut Bencher) {
+    b.iter(|| RobotScheduling::<CostQueues>::with_scheduler(2, 500).solve());
+  }
 }
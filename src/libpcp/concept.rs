@@ -32,6 +32,11 @@ pub trait IntBound: Integer + Clone + Debug + Signed // Due to the lack of Subtr
 
 impl<R> IntBound for R where R: Integer + Clone + Debug + Signed {}
 
+/// `ceil(a / b)`
+pub fn div_ceil<Bound: IntBound>(a: Bound, b: &Bound) -> Bound {
+    -(-a).div_floor(b)
+}
+
 pub trait IntDomain:
     Bounded
     + Cardinality
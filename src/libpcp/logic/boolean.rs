@@ -132,6 +132,10 @@ where
     fn propagate(&mut self, vstore: &mut VStore) -> bool {
         self.update(vstore, Dom::singleton(Bound::one()))
     }
+
+    fn cost(&self) -> PropagatorCost {
+        PropagatorCost::Unary
+    }
 }
 
 impl<VStore> PropagatorDependencies<FDEvent> for Boolean<VStore> {
@@ -89,6 +89,10 @@ where
     fn propagate(&mut self, vstore: &mut VStore) -> bool {
         self.b.update(vstore, Dom::singleton(Bound::zero()))
     }
+
+    fn cost(&self) -> PropagatorCost {
+        PropagatorCost::Unary
+    }
 }
 
 impl<VStore> PropagatorDependencies<FDEvent> for BooleanNeg<VStore> {
@@ -102,6 +102,18 @@ impl<VStore> Propagator<VStore> for Conjunction<VStore> {
         }
         true
     }
+
+    fn is_stateful(&self) -> bool {
+        self.fs.iter().any(|f| f.is_stateful())
+    }
+
+    fn cost(&self) -> PropagatorCost {
+        self.fs
+            .iter()
+            .map(|f| f.cost())
+            .max()
+            .unwrap_or(PropagatorCost::Unary)
+    }
 }
 
 impl<VStore> PropagatorDependencies<FDEvent> for Conjunction<VStore> {
@@ -20,15 +20,116 @@ use model::*;
 use propagation::events::*;
 use propagation::*;
 use std::fmt::{Debug, Formatter, Result};
+use term::identity::*;
 use trilean::SKleene;
+use variable::ops::*;
+
+/// The constructive propagation of the disjuncts, it is a function pointer so the propagation of the other disjunctions does not require a cloneable store.
+type ConstructivePropagation<VStore> = fn(&[&Formula<VStore>], usize, &mut VStore) -> bool;
 
 pub struct Disjunction<VStore> {
     fs: Vec<Formula<VStore>>,
+    /// The maximal cost of a constructive propagation and its implementation, see `Disjunction::constructive`.
+    constructive: Option<(usize, ConstructivePropagation<VStore>)>,
 }
 
 impl<VStore> Disjunction<VStore> {
     pub fn new(fs: Vec<Formula<VStore>>) -> Self {
-        Disjunction { fs }
+        Disjunction {
+            fs,
+            constructive: None,
+        }
+    }
+}
+
+impl<VStore, Domain, Bound> Disjunction<VStore>
+where
+    VStore: VStoreConcept<Item = Domain> + DrainDelta<FDEvent>,
+    Domain: IntDomain<Item = Bound>,
+    Bound: IntBound,
+{
+    /// Enables the constructive disjunction: when several disjuncts are not disentailed, each of them is propagated to its fixpoint on a copy of the variables it depends on, and every variable is restricted to the union of the hulls of its domains in the copies.
+    /// A disjunct failing on its copy is disentailed.
+    /// The cost of a propagation is the number of domains copied or checked for a change, that is the number of variables of the disjuncts for each propagation of a disjunct.
+    /// The constructive propagation is skipped when propagating every undecided disjunct once costs more than `max_cost`, and the disjuncts stop before their fixpoint once the remaining cost is exhausted.
+    pub fn constructive(mut self, max_cost: usize) -> Self {
+        self.constructive = Some((max_cost, constructive_propagation));
+        self
+    }
+}
+
+/// A store where the variables of `deps` have their domains in `vstore`, the others are replaced by their hull since the disjuncts do not depend on them.
+fn copy_dependencies<VStore, Domain, Bound>(vstore: &VStore, deps: &[usize]) -> VStore
+where
+    VStore: VStoreConcept<Item = Domain>,
+    Domain: IntDomain<Item = Bound>,
+    Bound: IntBound,
+{
+    let mut copy = VStore::empty();
+    let mut deps = deps.iter().peekable();
+    for i in 0..vstore.size() {
+        let dom = if deps.peek() == Some(&&i) {
+            deps.next();
+            vstore[i].clone()
+        } else {
+            Domain::new(vstore[i].lower(), vstore[i].upper())
+        };
+        copy.alloc(dom);
+    }
+    copy
+}
+
+fn constructive_propagation<VStore, Domain, Bound>(
+    fs: &[&Formula<VStore>],
+    max_cost: usize,
+    vstore: &mut VStore,
+) -> bool
+where
+    VStore: VStoreConcept<Item = Domain> + DrainDelta<FDEvent>,
+    Domain: IntDomain<Item = Bound>,
+    Bound: IntBound,
+{
+    let mut deps: Vec<_> = fs
+        .iter()
+        .flat_map(|f| f.dependencies().into_iter().map(|(v, _)| v))
+        .collect();
+    deps.sort();
+    deps.dedup();
+    let round_cost = deps.len();
+    if fs.len() * round_cost > max_cost {
+        return true;
+    }
+    let mut remaining_cost = max_cost - fs.len() * round_cost;
+    let mut copies = vec![];
+    let mut supported = vec![];
+    for (i, f) in fs.iter().enumerate() {
+        let mut copy = copy_dependencies(vstore, &deps);
+        let mut f = f.bclone();
+        let mut consistent = f.propagate(&mut copy);
+        while consistent && copy.has_changed() && remaining_cost >= round_cost && round_cost > 0 {
+            remaining_cost -= round_cost;
+            copy.reset_changed();
+            consistent = f.propagate(&mut copy);
+        }
+        if consistent {
+            copies.push(copy);
+            supported.push(i);
+        }
+    }
+    match supported.len() {
+        0 => false,
+        1 => fs[supported[0]].bclone().propagate(vstore),
+        _ => {
+            for &i in &deps {
+                let lb = copies.iter().map(|c| c[i].lower()).min().unwrap();
+                let ub = copies.iter().map(|c| c[i].upper()).max().unwrap();
+                let dom = vstore[i].intersection(&Domain::new(lb, ub));
+                if !vstore.update(&Identity::new(i), dom) {
+                    return false;
+                }
+            }
+            true
+        }
     }
 }
 
@@ -36,6 +137,7 @@ impl<VStore> Debug for Disjunction<VStore> {
     fn fmt(&self, fmt: &mut Formatter) -> Result {
         fmt.debug_struct("Disjunction")
             .field("fs", &self.fs)
+            .field("constructive", &self.constructive.map(|(cost, _)| cost))
             .finish()
     }
 }
@@ -47,6 +149,7 @@ where
     fn clone(&self) -> Self {
         Disjunction {
             fs: self.fs.iter().map(|f| f.bclone()).collect(),
+            constructive: self.constructive,
         }
     }
 }
@@ -109,10 +212,29 @@ impl<VStore> Propagator<VStore> for Disjunction<VStore> {
             self.fs[unknown_formula].propagate(vstore)
         } else if num_disentailed == self.fs.len() {
             false
+        } else if let Some((max_cost, constructive)) = self.constructive {
+            let undecided: Vec<_> = self
+                .fs
+                .iter()
+                .filter(|f| f.is_subsumed(vstore) == Unknown)
+                .collect();
+            constructive(&undecided, max_cost, vstore)
         } else {
             true
         }
     }
+
+    fn is_stateful(&self) -> bool {
+        self.fs.iter().any(|f| f.is_stateful())
+    }
+
+    fn cost(&self) -> PropagatorCost {
+        self.fs
+            .iter()
+            .map(|f| f.cost())
+            .max()
+            .unwrap_or(PropagatorCost::Unary)
+    }
 }
 
 impl<VStore> PropagatorDependencies<FDEvent> for Disjunction<VStore> {
@@ -128,3 +250,149 @@ impl<VStore> PropagatorDependencies<FDEvent> for Disjunction<VStore> {
         deps
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use gcollections::ops::*;
+    use interval::interval_set::*;
+    use propagators::cmp::*;
+    use search::test::*;
+    use term::*;
+    use trilean::SKleene::*;
+
+    fn constant(c: i32) -> Var<VStore> {
+        Box::new(Constant::new(c))
+    }
+
+    /// `x` and `y` in `[0..10]` with the disjunction `(x <= 2 /\ y >= 5) \/ (x = 4 /\ y >= 7)`.
+    fn disjunction_test_one(
+        test_num: u32,
+        max_cost: Option<usize>,
+        expected_status: SKleene,
+        expected: Vec<(i32, i32)>,
+    ) {
+        let mut vstore = VStore::empty();
+        let x = Box::new(vstore.alloc((0, 10).to_interval_set())) as Var<VStore>;
+        let y = Box::new(vstore.alloc((0, 10).to_interval_set())) as Var<VStore>;
+        let left = Conjunction::new(vec![
+            Box::new(x_leq_y(x.bclone(), constant(2))),
+            Box::new(x_geq_y(y.bclone(), constant(5))),
+        ]);
+        let right = Conjunction::new(vec![
+            Box::new(XEqY::new(x.bclone(), constant(4))),
+            Box::new(x_geq_y(y.bclone(), constant(7))),
+        ]);
+        let mut disjunction = Disjunction::new(vec![Box::new(left), Box::new(right)]);
+        if let Some(max_cost) = max_cost {
+            disjunction = disjunction.constructive(max_cost);
+        }
+        assert!(
+            disjunction.propagate(&mut vstore),
+            "Test {}: propagation failed.",
+            test_num
+        );
+        assert_eq!(
+            disjunction.is_subsumed(&vstore),
+            expected_status,
+            "Test {}: wrong status.",
+            test_num
+        );
+        let doms: Vec<_> = vstore.iter().cloned().collect();
+        let expected: Vec<_> = expected.into_iter().map(|d| d.to_interval_set()).collect();
+        assert_eq!(doms, expected, "Test {}: wrong domains.", test_num);
+    }
+
+    #[test]
+    fn constructive_disjunction_test() {
+        disjunction_test_one(1, None, Unknown, vec![(0, 10), (0, 10)]);
+        // `x` is in the hull of `[0..2]` and `{4}`, and `y >= 5` in both disjuncts.
+        disjunction_test_one(2, Some(4), Unknown, vec![(0, 4), (5, 10)]);
+        // Propagating once the 2 disjuncts on copies of their 2 variables is over the cost bound.
+        disjunction_test_one(3, Some(3), Unknown, vec![(0, 10), (0, 10)]);
+    }
+
+    /// `x` and `y` in `[0..10]` with `(x >= y /\ y >= 5) \/ (x >= y /\ y >= 7)`, both disjuncts need a second propagation to prune `x`.
+    fn fixpoint_test_one(test_num: u32, max_cost: usize, expected: Vec<(i32, i32)>) {
+        let mut vstore = VStore::empty();
+        let x = Box::new(vstore.alloc((0, 10).to_interval_set())) as Var<VStore>;
+        let y = Box::new(vstore.alloc((0, 10).to_interval_set())) as Var<VStore>;
+        let disjunct = |lb: i32| {
+            Box::new(Conjunction::new(vec![
+                Box::new(x_geq_y(x.bclone(), y.bclone())),
+                Box::new(x_geq_y(y.bclone(), constant(lb))),
+            ])) as Formula<VStore>
+        };
+        let mut disjunction =
+            Disjunction::new(vec![disjunct(5), disjunct(7)]).constructive(max_cost);
+        assert!(
+            disjunction.propagate(&mut vstore),
+            "Test {}: propagation failed.",
+            test_num
+        );
+        let doms: Vec<_> = vstore.iter().cloned().collect();
+        let expected: Vec<_> = expected.into_iter().map(|d| d.to_interval_set()).collect();
+        assert_eq!(doms, expected, "Test {}: wrong domains.", test_num);
+    }
+
+    #[test]
+    fn constructive_disjunction_fixpoint_test() {
+        // One propagation of each disjunct only prunes `y`.
+        fixpoint_test_one(1, 4, vec![(0, 10), (5, 10)]);
+        // Only the first disjunct reaches the pruning of `x` within the cost.
+        fixpoint_test_one(2, 6, vec![(0, 10), (5, 10)]);
+        fixpoint_test_one(3, 12, vec![(5, 10), (5, 10)]);
+    }
+
+    #[test]
+    fn constructive_disjunction_failure_test() {
+        // `x = 3 \/ x = 5` with `x` in `[0..10]` is pruned to `[3..5]`, and fails with `x` in `[6..10]`.
+        let mut vstore = VStore::empty();
+        let x = Box::new(vstore.alloc((0, 10).to_interval_set())) as Var<VStore>;
+        let y = Box::new(vstore.alloc((6, 10).to_interval_set())) as Var<VStore>;
+        let x_is_3_or_5 = |x: &Var<VStore>| {
+            Disjunction::new(vec![
+                Box::new(XEqY::new(x.bclone(), constant(3))),
+                Box::new(XEqY::new(x.bclone(), constant(5))),
+            ])
+            .constructive(10)
+        };
+        assert!(x_is_3_or_5(&x).propagate(&mut vstore));
+        assert_eq!(x.read(&vstore), (3, 5).to_interval_set());
+        assert!(!x_is_3_or_5(&y).propagate(&mut vstore));
+    }
+
+    /// Number of schedules of three tasks of durations 3, 2 and 2 ending before 8 without overlap.
+    fn count_schedules(max_cost: Option<usize>) -> usize {
+        let durations = [3, 2, 2];
+        let mut space = FDSpace::empty();
+        let starts: Vec<_> = durations
+            .iter()
+            .map(|d| Box::new(space.vstore.alloc((0, 8 - d).to_interval_set())) as Var<VStore>)
+            .collect();
+        for i in 0..starts.len() {
+            for j in i + 1..starts.len() {
+                let before = |i: usize, j: usize| {
+                    Box::new(x_leq_y(
+                        Box::new(Addition::new(starts[i].bclone(), durations[i])),
+                        starts[j].bclone(),
+                    )) as Formula<VStore>
+                };
+                let mut disjunction = Disjunction::new(vec![before(i, j), before(j, i)]);
+                if let Some(max_cost) = max_cost {
+                    disjunction = disjunction.constructive(max_cost);
+                }
+                space.cstore.alloc(Box::new(disjunction));
+            }
+        }
+        count_assignments(space, starts.len())
+    }
+
+    #[test]
+    fn constructive_disjunction_solutions_test() {
+        // 3! orders and 4 positions for the unit of slack in each of them.
+        assert_eq!(count_schedules(None), 24);
+        assert_eq!(count_schedules(Some(100)), 24);
+        assert_eq!(count_schedules(Some(0)), 24);
+    }
+}
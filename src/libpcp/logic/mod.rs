@@ -17,12 +17,14 @@ pub mod boolean_neg;
 pub mod conjunction;
 pub mod disjunction;
 pub mod ops;
+pub mod reified;
 
 pub use logic::boolean::*;
 pub use logic::boolean_neg::*;
 pub use logic::conjunction::*;
 pub use logic::disjunction::*;
 pub use logic::ops::*;
+pub use logic::reified::*;
 
 use concept::*;
 use gcollections::*;
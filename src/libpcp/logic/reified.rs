@@ -0,0 +1,406 @@
+// Copyright 2018 Pierre Talbot (IRCAM)
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+
+//     http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Reification of any propagator `c` with a Boolean variable `b`: `Reified` is the full reification `b <=> c` and `Implies` the half-reification `b => c`.
+//! Contrarily to `equivalence`, the propagator and its negation are stored once and `b` is the only auxiliary variable.
+
+use concept::*;
+use gcollections::*;
+use kernel::*;
+use logic::{Boolean, Conjunction, NotFormula};
+use model::*;
+use propagation::events::*;
+use propagation::*;
+use std::fmt::{Debug, Formatter, Result};
+use term::ops::*;
+use trilean::SKleene;
+use trilean::SKleene::*;
+
+/// Assigns `b` to `1` if `value` is `true` and to `0` otherwise.
+fn fix<VStore, Domain, Bound>(b: &mut Boolean<VStore>, vstore: &mut VStore, value: bool) -> bool
+where
+    VStore: VStoreConcept<Item = Domain>,
+    Domain: IntDomain<Item = Bound>,
+    Bound: IntBound,
+{
+    let value = if value { Bound::one() } else { Bound::zero() };
+    b.update(vstore, Domain::singleton(value))
+}
+
+/// `b <=> c`
+pub struct Reified<VStore, P> {
+    b: Boolean<VStore>,
+    c: P,
+    not_c: Formula<VStore>,
+    /// The negation `b <=> not c`.
+    negated: bool,
+}
+
+impl<VStore, P> Reified<VStore, P>
+where
+    P: NotFormula<VStore>,
+{
+    pub fn new(b: Boolean<VStore>, c: P) -> Self {
+        let not_c = c.not();
+        Reified {
+            b,
+            c,
+            not_c,
+            negated: false,
+        }
+    }
+}
+
+impl<VStore, P> Reified<VStore, P>
+where
+    P: PropagatorConcept<VStore, FDEvent>,
+{
+    /// The formula equivalent to `b`.
+    fn formula(&self) -> &dyn PropagatorConcept<VStore, FDEvent> {
+        if self.negated {
+            &*self.not_c
+        } else {
+            &self.c
+        }
+    }
+}
+
+impl<VStore, P> Debug for Reified<VStore, P>
+where
+    P: Debug,
+{
+    fn fmt(&self, fmt: &mut Formatter) -> Result {
+        fmt.debug_struct("Reified")
+            .field("b", &self.b)
+            .field("c", &self.c)
+            .field("negated", &self.negated)
+            .finish()
+    }
+}
+
+impl<VStore, P> Clone for Reified<VStore, P>
+where
+    VStore: Collection,
+    P: Clone,
+{
+    fn clone(&self) -> Self {
+        Reified {
+            b: self.b.clone(),
+            c: self.c.clone(),
+            not_c: self.not_c.bclone(),
+            negated: self.negated,
+        }
+    }
+}
+
+impl<VStore, P> DisplayStateful<Model> for Reified<VStore, P>
+where
+    P: PropagatorConcept<VStore, FDEvent>,
+{
+    fn display(&self, model: &Model) {
+        self.b.display(model);
+        print!(" <=> (");
+        self.formula().display(model);
+        print!(")");
+    }
+}
+
+impl<VStore, Domain, Bound, P> NotFormula<VStore> for Reified<VStore, P>
+where
+    VStore: VStoreConcept<Item = Domain> + 'static,
+    Domain: IntDomain<Item = Bound> + 'static,
+    Bound: IntBound + 'static,
+    P: PropagatorConcept<VStore, FDEvent> + Clone + 'static,
+{
+    /// `not (b <=> c)` is `b <=> not c`.
+    fn not(&self) -> Formula<VStore> {
+        let mut not = self.clone();
+        not.negated = !self.negated;
+        Box::new(not)
+    }
+}
+
+impl<VStore, Domain, Bound, P> Subsumption<VStore> for Reified<VStore, P>
+where
+    VStore: Collection<Item = Domain>,
+    Domain: IntDomain<Item = Bound>,
+    Bound: IntBound,
+    P: PropagatorConcept<VStore, FDEvent>,
+{
+    fn is_subsumed(&self, vstore: &VStore) -> SKleene {
+        match self.b.is_subsumed(vstore) {
+            True => self.formula().is_subsumed(vstore),
+            False => !self.formula().is_subsumed(vstore),
+            Unknown => Unknown,
+        }
+    }
+}
+
+impl<VStore, Domain, Bound, P> Propagator<VStore> for Reified<VStore, P>
+where
+    VStore: VStoreConcept<Item = Domain>,
+    Domain: IntDomain<Item = Bound>,
+    Bound: IntBound,
+    P: PropagatorConcept<VStore, FDEvent>,
+{
+    /// Once `b` is assigned, the formula or its negation is propagated, otherwise `b` is assigned as soon as the formula is entailed or disentailed.
+    fn propagate(&mut self, vstore: &mut VStore) -> bool {
+        let (c, not_c): (&mut dyn PropagatorConcept<VStore, FDEvent>, _) = if self.negated {
+            (
+                &mut *self.not_c,
+                &mut self.c as &mut dyn PropagatorConcept<VStore, FDEvent>,
+            )
+        } else {
+            (&mut self.c, &mut *self.not_c)
+        };
+        match self.b.is_subsumed(vstore) {
+            True => c.propagate(vstore),
+            False => not_c.propagate(vstore),
+            Unknown => match c.is_subsumed(vstore) {
+                True => fix(&mut self.b, vstore, true),
+                False => fix(&mut self.b, vstore, false),
+                Unknown => true,
+            },
+        }
+    }
+
+    fn is_stateful(&self) -> bool {
+        self.c.is_stateful() || self.not_c.is_stateful()
+    }
+
+    fn cost(&self) -> PropagatorCost {
+        self.c.cost().max(self.not_c.cost())
+    }
+}
+
+impl<VStore, P> PropagatorDependencies<FDEvent> for Reified<VStore, P>
+where
+    P: PropagatorConcept<VStore, FDEvent>,
+{
+    fn dependencies(&self) -> Vec<(usize, FDEvent)> {
+        let mut deps = ViewDependencies::dependencies(&self.b, FDEvent::Assignment);
+        deps.append(&mut self.c.dependencies());
+        deps.append(&mut self.not_c.dependencies());
+        deps.sort();
+        deps.dedup();
+        deps
+    }
+}
+
+/// `b => c`
+pub struct Implies<VStore, P> {
+    b: Boolean<VStore>,
+    c: P,
+}
+
+impl<VStore, P> Implies<VStore, P> {
+    pub fn new(b: Boolean<VStore>, c: P) -> Self {
+        Implies { b, c }
+    }
+}
+
+impl<VStore, P> Debug for Implies<VStore, P>
+where
+    P: Debug,
+{
+    fn fmt(&self, fmt: &mut Formatter) -> Result {
+        fmt.debug_struct("Implies")
+            .field("b", &self.b)
+            .field("c", &self.c)
+            .finish()
+    }
+}
+
+impl<VStore, P> Clone for Implies<VStore, P>
+where
+    VStore: Collection,
+    P: Clone,
+{
+    fn clone(&self) -> Self {
+        Implies {
+            b: self.b.clone(),
+            c: self.c.clone(),
+        }
+    }
+}
+
+impl<VStore, P> DisplayStateful<Model> for Implies<VStore, P>
+where
+    P: DisplayStateful<Model>,
+{
+    fn display(&self, model: &Model) {
+        self.b.display(model);
+        print!(" => (");
+        self.c.display(model);
+        print!(")");
+    }
+}
+
+impl<VStore, Domain, Bound, P> NotFormula<VStore> for Implies<VStore, P>
+where
+    VStore: VStoreConcept<Item = Domain> + 'static,
+    Domain: IntDomain<Item = Bound> + 'static,
+    Bound: IntBound + 'static,
+    P: NotFormula<VStore>,
+{
+    /// `not (b => c)` is `b /\ not c`.
+    fn not(&self) -> Formula<VStore> {
+        Box::new(Conjunction::new(vec![
+            Box::new(self.b.clone()),
+            self.c.not(),
+        ]))
+    }
+}
+
+impl<VStore, Domain, Bound, P> Subsumption<VStore> for Implies<VStore, P>
+where
+    VStore: Collection<Item = Domain>,
+    Domain: IntDomain<Item = Bound>,
+    Bound: IntBound,
+    P: Subsumption<VStore>,
+{
+    fn is_subsumed(&self, vstore: &VStore) -> SKleene {
+        match (self.b.is_subsumed(vstore), self.c.is_subsumed(vstore)) {
+            (False, _) | (_, True) => True,
+            (True, False) => False,
+            _ => Unknown,
+        }
+    }
+}
+
+impl<VStore, Domain, Bound, P> Propagator<VStore> for Implies<VStore, P>
+where
+    VStore: VStoreConcept<Item = Domain>,
+    Domain: IntDomain<Item = Bound>,
+    Bound: IntBound,
+    P: Propagator<VStore> + Subsumption<VStore>,
+{
+    /// The formula is propagated once `b` is true, and `b` is false as soon as the formula is disentailed.
+    fn propagate(&mut self, vstore: &mut VStore) -> bool {
+        match self.b.is_subsumed(vstore) {
+            True => self.c.propagate(vstore),
+            False => true,
+            Unknown => {
+                if self.c.is_subsumed(vstore) == False {
+                    fix(&mut self.b, vstore, false)
+                } else {
+                    true
+                }
+            }
+        }
+    }
+
+    fn is_stateful(&self) -> bool {
+        self.c.is_stateful()
+    }
+
+    fn cost(&self) -> PropagatorCost {
+        self.c.cost()
+    }
+}
+
+impl<VStore, P> PropagatorDependencies<FDEvent> for Implies<VStore, P>
+where
+    P: PropagatorDependencies<FDEvent>,
+{
+    fn dependencies(&self) -> Vec<(usize, FDEvent)> {
+        let mut deps = ViewDependencies::dependencies(&self.b, FDEvent::Assignment);
+        deps.append(&mut self.c.dependencies());
+        deps.sort();
+        deps.dedup();
+        deps
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use gcollections::ops::*;
+    use interval::interval_set::*;
+    use logic::equivalence;
+    use propagators::cmp::*;
+    use search::test::*;
+
+    /// The space `x, y in [0..3]` with a Boolean `b`, `x < y` is the reified constraint.
+    fn reified_space<F>(reify: F) -> FDSpace
+    where
+        F: FnOnce(Boolean<VStore>, XLessY<VStore>) -> Vec<Formula<VStore>>,
+    {
+        let mut space = FDSpace::empty();
+        let x = Box::new(space.vstore.alloc((0, 3).to_interval_set())) as Var<VStore>;
+        let y = Box::new(space.vstore.alloc((0, 3).to_interval_set())) as Var<VStore>;
+        let b = Boolean::new(&mut space.vstore);
+        for f in reify(b, XLessY::new(x, y)) {
+            space.cstore.alloc(f);
+        }
+        space
+    }
+
+    fn count(space: FDSpace) -> usize {
+        count_assignments(space, 3)
+    }
+
+    #[test]
+    fn reified_test() {
+        // 6 pairs with `x < y` and 10 with `x >= y`, `b` is fixed by each pair.
+        let reified = |b: Boolean<VStore>, c| vec![Box::new(Reified::new(b, c)) as Formula<VStore>];
+        assert_eq!(count(reified_space(reified)), 16);
+        let entailed = |b: Boolean<VStore>, c| {
+            vec![
+                Box::new(Reified::new(b.clone(), c)) as Formula<VStore>,
+                Box::new(b),
+            ]
+        };
+        assert_eq!(count(reified_space(entailed)), 6);
+        let disentailed = |b: Boolean<VStore>, c| {
+            vec![
+                Box::new(Reified::new(b.clone(), c)) as Formula<VStore>,
+                b.not(),
+            ]
+        };
+        assert_eq!(count(reified_space(disentailed)), 10);
+        let negated = |b: Boolean<VStore>, c| vec![Reified::new(b.clone(), c).not(), Box::new(b)];
+        assert_eq!(count(reified_space(negated)), 10);
+        // Same count as the decomposition with `equivalence`.
+        let equiv = |b: Boolean<VStore>, c| vec![equivalence(Box::new(b), Box::new(c))];
+        assert_eq!(count(reified_space(equiv)), 16);
+    }
+
+    #[test]
+    fn implies_test() {
+        // `b = 0` for the 16 pairs and `b = 1` for the 6 pairs with `x < y`.
+        let implies = |b: Boolean<VStore>, c| vec![Box::new(Implies::new(b, c)) as Formula<VStore>];
+        assert_eq!(count(reified_space(implies)), 22);
+        let negated = |b: Boolean<VStore>, c| vec![Implies::new(b, c).not()];
+        assert_eq!(count(reified_space(negated)), 10);
+    }
+
+    #[test]
+    fn reified_propagation_test() {
+        let mut vstore = VStore::empty();
+        let x = Box::new(vstore.alloc((0, 3).to_interval_set())) as Var<VStore>;
+        let y = Box::new(vstore.alloc((5, 6).to_interval_set())) as Var<VStore>;
+        let b = Boolean::new(&mut vstore);
+        let mut reified = Reified::new(b.clone(), XLessY::new(x.bclone(), y.bclone()));
+        let mut implies = Implies::new(b.clone(), XLessY::new(y, x));
+        assert_eq!(reified.is_subsumed(&vstore), Unknown);
+        assert_eq!(implies.is_subsumed(&vstore), Unknown);
+        // `x < y` is entailed, so `b = 1`, and `y < x` is disentailed, so `b = 0`.
+        assert!(reified.propagate(&mut vstore));
+        assert_eq!(b.is_subsumed(&vstore), True);
+        assert_eq!(reified.is_subsumed(&vstore), True);
+        assert_eq!(implies.is_subsumed(&vstore), False);
+        assert!(!implies.propagate(&mut vstore));
+    }
+}
@@ -98,6 +98,17 @@ impl Model {
             i += 1;
         }
         args[i].display(self);
-        print!(") (decomposed)");
+        print!(")");
+    }
+
+    pub fn display_array<VStore>(&self, args: &[Var<VStore>]) {
+        print!("[");
+        for (i, arg) in args.iter().enumerate() {
+            if i > 0 {
+                print!(", ");
+            }
+            arg.display(self);
+        }
+        print!("]");
     }
 }
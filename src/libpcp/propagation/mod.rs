@@ -19,6 +19,7 @@
 pub mod concept;
 pub mod events;
 pub mod ops;
+pub mod profile;
 pub mod reactor;
 pub mod reactors;
 pub mod scheduler;
@@ -27,8 +28,10 @@ pub mod store;
 
 pub use propagation::concept::*;
 pub use propagation::ops::*;
+pub use propagation::profile::*;
 pub use propagation::reactor::Reactor;
 pub use propagation::scheduler::Scheduler;
 
-pub type CStoreFD<VStore> =
-    store::Store<VStore, events::FDEvent, reactors::IndexedDeps, schedulers::RelaxedFifo>;
+pub type CStoreFD<VStore> = CStoreFDWith<VStore, schedulers::RelaxedFifo>;
+pub type CStoreFDWith<VStore, Scheduler> =
+    store::Store<VStore, events::FDEvent, reactors::IndexedDeps, Scheduler>;
@@ -12,18 +12,127 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use gcollections::kind::*;
+use propagation::events::FDEvent;
 use trilean::SKleene;
 
 pub trait Subsumption<Store> {
     fn is_subsumed(&self, store: &Store) -> SKleene;
 }
 
+/// The cost class of a propagator, a rough estimate of the time complexity of `propagate` in the number of its variables.
+/// Schedulers can use it to run cheap propagators before expensive ones.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum PropagatorCost {
+    Unary,
+    Binary,
+    Ternary,
+    Linear,
+    Quadratic,
+    Cubic,
+    Exponential,
+}
+
+impl PropagatorCost {
+    /// The number of cost classes.
+    pub fn size() -> usize {
+        PropagatorCost::Exponential as usize + 1
+    }
+}
+
+/// The result of a propagation, richer than the `bool` returned by `propagate` (see `Propagator::propagate_status`).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PropagatorStatus {
+    /// A variable has an empty domain.
+    Failed,
+    /// The propagator is at its fixpoint (propagating again does not modify the variables) and it is not subsumed.
+    Fixpoint,
+    /// The propagator is subsumed and can be removed.
+    Subsumed,
+    /// The propagator might not be at its fixpoint, and its subsumption is checked with `is_subsumed`.
+    NotFixpoint,
+    /// The propagator might not be at its fixpoint, it was not subsumed before its propagation and `is_subsumed` is not called.
+    /// If the propagation modified the variables, the propagator is rescheduled and checks its subsumption again on its next call.
+    NotSubsumed,
+}
+
+/// The modification of the variable `var` since the last propagation of an incremental propagator (see `Propagator::propagate_delta`).
+/// The removed values are `old.difference(current)` where `current` is the domain of `var` in the variables store.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct VarDelta<Domain> {
+    pub var: usize,
+    pub event: FDEvent,
+    /// The domain of `var` at the last propagation.
+    pub old: Domain,
+}
+
 pub trait Propagator<VStore> {
     /// Returns `false` if it failed to propagate (a variable has an empty domain after propagation).
     fn propagate(&mut self, store: &mut VStore) -> bool;
+
+    /// Propagates and reports whether the propagator reached its fixpoint or is subsumed.
+    /// The constraint store calls this method, it does not reschedule a propagator at its fixpoint for its own modifications, and a subsumed propagator is removed without calling `is_subsumed`.
+    /// It defaults to `propagate`, with the result `NotFixpoint` on success.
+    fn propagate_status(&mut self, store: &mut VStore) -> PropagatorStatus {
+        if self.propagate(store) {
+            PropagatorStatus::NotFixpoint
+        } else {
+            PropagatorStatus::Failed
+        }
+    }
+
+    /// Returns `true` if the propagator keeps an internal state, modified by `propagate`, that must be restored on backtracking.
+    /// The constraint store saves a copy of the active stateful propagators in its labels.
+    fn is_stateful(&self) -> bool {
+        false
+    }
+
+    /// Returns `true` if the stateful propagator trails the modifications of its internal state, the constraint store then keeps the position of its trail (see `Propagator::mark`) in its labels instead of a copy.
+    fn is_trailed(&self) -> bool {
+        false
+    }
+
+    /// The current position in the trail of the internal state, it is only called on trailed propagators.
+    fn mark(&mut self) -> usize {
+        0
+    }
+
+    /// Restores the internal state at the position `mark` of the trail.
+    /// As for the trail memory of the variables, the marks are restored in a depth-first order: the modifications made after a mark are undone before an older mark is restored.
+    fn undo(&mut self, _mark: usize) {}
+
+    /// The cost class of the propagator, it defaults to `Linear` which suits most of the n-ary propagators.
+    fn cost(&self) -> PropagatorCost {
+        PropagatorCost::Linear
+    }
+
+    /// Returns `true` if the constraint store must call `propagate_delta` instead of `propagate_status` when the delta is available.
+    fn is_incremental(&self) -> bool {
+        false
+    }
+
+    /// Incremental version of `propagate_status`, `delta` contains the variables (among the dependencies of the propagator) modified since its last propagation.
+    /// The delta is not available on the first propagation of the propagator, when it is rescheduled without reacting to a modification, or if a variable was modified outside of the constraint store, in which case `propagate_status` is called.
+    /// Only the modifications matching the dependencies of the propagator are reported, and the propagator also receives its own modifications when it is rescheduled.
+    fn propagate_delta(
+        &mut self,
+        vstore: &mut VStore,
+        _delta: &[VarDelta<VStore::Item>],
+    ) -> PropagatorStatus
+    where
+        VStore: Collection,
+    {
+        self.propagate_status(vstore)
+    }
 }
 
 pub trait PropagatorDependencies<Event> {
     /// Each event on a variable that can change the result of the `is_subsumed` method should be listed here.
     fn dependencies(&self) -> Vec<(usize, Event)>;
+
+    /// Returns `true` if the dependencies change with the internal state of the propagator (e.g. watched literals).
+    /// The constraint store moves the subscriptions of the propagator to its current dependencies after each propagation and on backtracking, so the dependencies must stay valid in the ancestors of the current node.
+    fn has_dynamic_dependencies(&self) -> bool {
+        false
+    }
 }
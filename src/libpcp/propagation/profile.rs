@@ -0,0 +1,140 @@
+// Copyright 2018 Pierre Talbot (IRCAM)
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+
+//     http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! The profile of the propagators collected by the constraints store when the profiling is enabled (see `Store::enable_profiling`).
+
+use kernel::*;
+use model::*;
+use propagation::concept::*;
+use propagation::ops::PropagatorStatus;
+use std::cmp::Reverse;
+use std::time::Duration;
+use vec_map::VecMap;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct PropagatorStats {
+    pub num_calls: usize,
+    /// Number of calls modifying a variable.
+    pub num_prunings: usize,
+    pub num_failures: usize,
+    /// Cumulative time spent in the propagator, including its subsumption check.
+    pub time: Duration,
+}
+
+/// A copy of the propagator profiled at an index, only used for display, or `None` if the index was reused by another propagator.
+type PropagatorLabel<VStore, Event> = Option<Box<dyn PropagatorConcept<VStore, Event>>>;
+
+/// The statistics are indexed by propagator, a propagator index can be reused on backtracking (e.g. by a branching constraint), in which case the statistics of the propagators sharing this index are accumulated and displayed as such.
+#[derive(Debug)]
+pub struct Profile<VStore, Event> {
+    stats: VecMap<(PropagatorLabel<VStore, Event>, PropagatorStats)>,
+}
+
+impl<VStore, Event> Profile<VStore, Event> {
+    pub fn new() -> Self {
+        Profile {
+            stats: VecMap::new(),
+        }
+    }
+
+    pub fn stats(&self, p_idx: usize) -> Option<&PropagatorStats> {
+        self.stats.get(p_idx).map(|(_, stats)| stats)
+    }
+
+    /// The statistics of the propagators called at least once, in increasing order of their index.
+    pub fn iter(&self) -> impl Iterator<Item = (usize, &PropagatorStats)> {
+        self.stats.iter().map(|(p_idx, (_, stats))| (p_idx, stats))
+    }
+
+    /// `true` if the statistics at `p_idx` accumulate several propagators allocated at this index.
+    pub fn is_aggregate(&self, p_idx: usize) -> bool {
+        self.stats.get(p_idx).is_some_and(|(p, _)| p.is_none())
+    }
+
+    pub fn reset(&mut self) {
+        self.stats.clear();
+    }
+
+    /// Called when a new propagator is allocated at `p_idx`.
+    pub(crate) fn reuse(&mut self, p_idx: usize) {
+        if let Some((p, _)) = self.stats.get_mut(p_idx) {
+            *p = None;
+        }
+    }
+
+    pub(crate) fn record(
+        &mut self,
+        p_idx: usize,
+        p: &dyn PropagatorConcept<VStore, Event>,
+        status: PropagatorStatus,
+        pruned: bool,
+        time: Duration,
+    ) {
+        let (_, stats) = self
+            .stats
+            .entry(p_idx)
+            .or_insert_with(|| (Some(p.bclone()), PropagatorStats::default()));
+        stats.num_calls += 1;
+        if pruned {
+            stats.num_prunings += 1;
+        }
+        if status == PropagatorStatus::Failed {
+            stats.num_failures += 1;
+        }
+        stats.time += time;
+    }
+}
+
+impl<VStore, Event> Default for Profile<VStore, Event> {
+    fn default() -> Self {
+        Profile::new()
+    }
+}
+
+impl<VStore, Event> Clone for Profile<VStore, Event> {
+    fn clone(&self) -> Self {
+        let stats = self
+            .stats
+            .iter()
+            .map(|(p_idx, (p, stats))| (p_idx, (p.as_ref().map(|p| p.bclone()), *stats)))
+            .collect();
+        Profile { stats }
+    }
+}
+
+impl<VStore, Event> DisplayStateful<Model> for Profile<VStore, Event> {
+    /// Displays the propagators by decreasing time.
+    fn display(&self, model: &Model) {
+        let mut stats: Vec<_> = self.stats.iter().collect();
+        stats.sort_by_key(|(_, (_, stats))| Reverse(stats.time));
+        println!(
+            "{:>12} {:>10} {:>10} {:>10}  constraint",
+            "time (ms)", "calls", "prunings", "failures"
+        );
+        for (p_idx, (p, stats)) in stats {
+            print!(
+                "{:>12.3} {:>10} {:>10} {:>10}  ",
+                stats.time.as_secs_f64() * 1000.0,
+                stats.num_calls,
+                stats.num_prunings,
+                stats.num_failures
+            );
+            match p {
+                Some(p) => p.display(model),
+                None => print!("propagators at index {}", p_idx),
+            }
+            println!();
+        }
+    }
+}
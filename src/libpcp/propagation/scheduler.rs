@@ -12,8 +12,14 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use propagation::ops::PropagatorCost;
+
 pub trait Scheduler {
     fn new(capacity: usize) -> Self;
+    /// Extends the capacity of the scheduler to at least `capacity` propagators, the scheduled propagators are kept.
+    fn reserve(&mut self, capacity: usize);
+    /// Informs the scheduler of the cost class of the propagator `idx`, it is called before `idx` is scheduled for the first time.
+    fn set_cost(&mut self, _idx: usize, _cost: PropagatorCost) {}
     fn schedule(&mut self, idx: usize);
     fn unschedule(&mut self, idx: usize);
     fn pop(&mut self) -> Option<usize>;
@@ -0,0 +1,149 @@
+// Copyright 2018 Pierre Talbot (IRCAM)
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+
+//     http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use bit_set::BitSet;
+use propagation::ops::PropagatorCost;
+use propagation::Scheduler;
+use std::collections::VecDeque;
+
+// There is one FIFO queue per cost class and `pop` always drains
+// the cheapest non-empty queue first. Hence cheap propagators reach
+// their fixed point before an expensive global constraint is run
+// again. Propagators without cost are considered `Linear`.
+// As in `RelaxedFifo`, unschedule might not preserve the ordering
+// inside a queue.
+
+#[derive(Debug)]
+pub struct CostQueues {
+    inside_queue: BitSet,
+    queues: Vec<VecDeque<usize>>,
+    costs: Vec<PropagatorCost>,
+}
+
+impl Scheduler for CostQueues {
+    fn new(capacity: usize) -> CostQueues {
+        CostQueues {
+            inside_queue: BitSet::with_capacity(capacity),
+            queues: vec![VecDeque::new(); PropagatorCost::size()],
+            costs: vec![PropagatorCost::Linear; capacity],
+        }
+    }
+
+    fn reserve(&mut self, capacity: usize) {
+        if capacity > self.costs.len() {
+            self.inside_queue.reserve_len(capacity);
+            self.costs.resize(capacity, PropagatorCost::Linear);
+        }
+    }
+
+    fn set_cost(&mut self, idx: usize, cost: PropagatorCost) {
+        self.unschedule(idx);
+        self.costs[idx] = cost;
+    }
+
+    fn schedule(&mut self, idx: usize) {
+        assert!(idx < self.costs.len());
+        if !self.inside_queue.contains(idx) {
+            self.inside_queue.insert(idx);
+            self.queues[self.costs[idx] as usize].push_back(idx);
+        }
+    }
+
+    fn unschedule(&mut self, idx: usize) {
+        assert!(idx < self.costs.len());
+        if self.inside_queue.contains(idx) {
+            let queue = &mut self.queues[self.costs[idx] as usize];
+            let queue_idx = queue.iter().position(|&e| e == idx);
+            assert!(queue_idx.is_some());
+            queue.swap_remove_front(queue_idx.unwrap());
+            self.inside_queue.remove(idx);
+        }
+    }
+
+    fn pop(&mut self) -> Option<usize> {
+        let res = self.queues.iter_mut().filter_map(|q| q.pop_front()).next();
+        if let Some(idx) = res {
+            self.inside_queue.remove(idx);
+        }
+        res
+    }
+
+    fn is_empty(&self) -> bool {
+        self.inside_queue.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use propagation::ops::PropagatorCost::*;
+    use propagation::Scheduler;
+
+    fn scheduler_with_costs(costs: Vec<PropagatorCost>) -> CostQueues {
+        let mut scheduler: CostQueues = Scheduler::new(costs.len());
+        for (idx, cost) in costs.into_iter().enumerate() {
+            scheduler.set_cost(idx, cost);
+        }
+        scheduler
+    }
+
+    #[test]
+    fn cheapest_first_test() {
+        let mut scheduler = scheduler_with_costs(vec![Quadratic, Binary, Linear, Binary]);
+        for idx in 0..4 {
+            scheduler.schedule(idx);
+        }
+        assert_eq!(scheduler.pop(), Some(1));
+        assert_eq!(scheduler.pop(), Some(3));
+        // A cheap propagator scheduled later is still run before the expensive ones.
+        scheduler.schedule(1);
+        assert_eq!(scheduler.pop(), Some(1));
+        assert_eq!(scheduler.pop(), Some(2));
+        assert!(!scheduler.is_empty());
+        assert_eq!(scheduler.pop(), Some(0));
+        assert_eq!(scheduler.pop(), None);
+        assert!(scheduler.is_empty());
+    }
+
+    #[test]
+    fn schedule_twice_and_unschedule_test() {
+        let mut scheduler = scheduler_with_costs(vec![Cubic, Unary, Cubic]);
+        scheduler.schedule(0);
+        scheduler.schedule(2);
+        scheduler.schedule(2);
+        scheduler.schedule(1);
+        scheduler.unschedule(0);
+        scheduler.unschedule(0);
+        assert_eq!(scheduler.pop(), Some(1));
+        assert_eq!(scheduler.pop(), Some(2));
+        assert_eq!(scheduler.pop(), None);
+    }
+
+    #[test]
+    fn default_cost_test() {
+        let mut scheduler: CostQueues = Scheduler::new(2);
+        scheduler.set_cost(1, Exponential);
+        scheduler.schedule(1);
+        scheduler.schedule(0);
+        assert_eq!(scheduler.pop(), Some(0));
+        assert_eq!(scheduler.pop(), Some(1));
+    }
+
+    #[test]
+    #[should_panic]
+    fn schedule_outofbound() {
+        let mut scheduler: CostQueues = Scheduler::new(3);
+        scheduler.schedule(3);
+    }
+}
@@ -12,5 +12,7 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+pub mod cost_queues;
 pub mod relaxed_fifo;
+pub use propagation::schedulers::cost_queues::CostQueues;
 pub use propagation::schedulers::relaxed_fifo::RelaxedFifo;
@@ -39,6 +39,14 @@ impl Scheduler for RelaxedFifo {
         }
     }
 
+    fn reserve(&mut self, capacity: usize) {
+        if capacity > self.capacity {
+            self.inside_queue.reserve_len(capacity);
+            self.queue.reserve(capacity - self.capacity);
+            self.capacity = capacity;
+        }
+    }
+
     fn schedule(&mut self, idx: usize) {
         assert!(idx < self.capacity);
         if !self.inside_queue.contains(idx) {
@@ -21,19 +21,36 @@ use kernel::*;
 use model::*;
 use propagation::concept::*;
 use propagation::ops::*;
+use propagation::profile::Profile;
 use propagation::Reactor;
 use propagation::Scheduler;
 use std::ops::{Index, IndexMut};
+use std::time::Instant;
 use trilean::SKleene;
 use trilean::SKleene::*;
 use variable::ops::*;
+use vec_map::VecMap;
+
+// The reactor is kept across consistency calls and search nodes: it is copied in the labels alongside `active`.
+// The propagators `0..subscribed` are already subscribed to the reactor (if active), hence a child node only schedules the new propagators (such as a branching constraint) and the ones reacting to the delta.
+// This assumes that a node is labelled at its fixed point, which is the case of the nodes labelled after `Propagation`.
+// The incremental propagators (see `Propagator::propagate_delta`) are advised of the variables they react to, the variables store keeps the old domains of these variables until their next propagation.
+// A propagator scheduled without reacting to a delta (newly allocated or in a clone) is in `full` and runs `propagate` instead.
 
 #[derive(Debug)]
 pub struct Store<VStore, Event, Reactor, Scheduler> {
     propagators: Vec<Box<dyn PropagatorConcept<VStore, Event> + 'static>>,
     active: BitSet,
     reactor: Reactor,
+    /// Number of variables the reactor was created for.
+    reactor_vars: usize,
+    subscribed: usize,
+    incremental: BitSet,
+    full: BitSet,
     scheduler: Scheduler,
+    profile: Option<Profile<VStore, Event>>,
+    /// The current subscriptions of the propagators with dynamic dependencies (see `PropagatorDependencies::has_dynamic_dependencies`).
+    dynamic: VecMap<Vec<(usize, Event)>>,
 }
 
 impl<VStore, Event, R, S> Empty for Store<VStore, Event, R, S>
@@ -46,8 +63,14 @@ where
         Store {
             propagators: vec![],
             active: BitSet::new(),
-            reactor: Reactor::new(0, 0),
+            reactor: Reactor::new(0, Event::size()),
+            reactor_vars: 0,
+            subscribed: 0,
+            incremental: BitSet::new(),
+            full: BitSet::new(),
             scheduler: Scheduler::new(0),
+            profile: None,
+            dynamic: VecMap::new(),
         }
     }
 }
@@ -69,6 +92,30 @@ impl<VStore, Event, R, S> Cardinality for Store<VStore, Event, R, S> {
 }
 
 impl<VStore, Event, R, S> Store<VStore, Event, R, S> {
+    /// Records the statistics of each propagator during the consistency calls, the profile is kept across the search nodes.
+    /// If the profiling is already enabled, the current profile is kept.
+    pub fn enable_profiling(&mut self) {
+        if self.profile.is_none() {
+            self.profile = Some(Profile::new());
+        }
+    }
+
+    pub fn disable_profiling(&mut self) {
+        self.profile = None;
+    }
+
+    /// The profile of the propagators, if the profiling is enabled.
+    pub fn profile(&self) -> Option<&Profile<VStore, Event>> {
+        self.profile.as_ref()
+    }
+
+    /// Empties the profile, for example between two searches.
+    pub fn reset_profile(&mut self) {
+        if let Some(profile) = self.profile.as_mut() {
+            profile.reset();
+        }
+    }
+
     fn display_constraints(&self, model: &Model, indexes: Vec<usize>, header: &str) {
         let header_width = 15;
         print!("{:>width$} ", header, width = header_width);
@@ -117,34 +164,54 @@ impl<VStore, Event, R, S> DisplayStateful<Model> for Store<VStore, Event, R, S>
 
 impl<VStore, Event, R, S> Store<VStore, Event, R, S>
 where
-    VStore: Cardinality<Size = usize> + DrainDelta<Event>,
+    VStore: Cardinality<Size = usize> + DrainDelta<Event> + AdviseDelta,
     Event: EventIndex,
     R: Reactor + Cardinality<Size = usize>,
     S: Scheduler,
 {
-    fn prepare(&mut self, vstore: &VStore) {
-        self.init_reactor(vstore);
-        self.init_scheduler();
-    }
-
-    fn init_reactor(&mut self, vstore: &VStore) {
-        self.reactor = Reactor::new(vstore.size(), Event::size());
-        for p_idx in self.active.iter() {
-            let p_deps = self[p_idx].dependencies();
-            for (v, ev) in p_deps {
-                debug_assert!(v < vstore.size(),
-          "The propagator {:?} has a dependency to the variable {} which is not in the vstore (of size {}).\n\
-          Hint: you should not manually create `Identity` struct, if you do make sure they contain relevant index to the variable vstore.",
-          self[p_idx], v, vstore.size());
-                self.reactor.subscribe(v, ev, p_idx);
-            }
+    /// Subscribes and schedules the propagators allocated since the last call, and schedules the propagators reacting to the pending delta of `vstore`.
+    /// If variables were added to `vstore`, the reactor is created again and all the active propagators are scheduled.
+    fn prepare(&mut self, vstore: &mut VStore) {
+        if self.reactor_vars != vstore.size() {
+            self.reactor = Reactor::new(vstore.size(), Event::size());
+            self.reactor_vars = vstore.size();
+            self.subscribed = 0;
         }
+        self.scheduler.reserve(self.propagators.len());
+        let new_props: Vec<_> = self
+            .active
+            .iter()
+            .filter(|&p_idx| p_idx >= self.subscribed)
+            .collect();
+        for p_idx in new_props {
+            self.subscribe_prop(p_idx, vstore);
+            self.scheduler.set_cost(p_idx, self[p_idx].cost());
+            self.scheduler.schedule(p_idx);
+            self.full.insert(p_idx);
+        }
+        self.subscribed = self.propagators.len();
+        if !self.incremental.is_empty() {
+            vstore.record_old_domains();
+        }
+        self.react(vstore, None);
     }
 
-    fn init_scheduler(&mut self) {
-        self.scheduler = Scheduler::new(self.propagators.len());
-        for p_idx in self.active.iter() {
-            self.scheduler.schedule(p_idx);
+    fn subscribe_prop(&mut self, p_idx: usize, vstore: &VStore) {
+        let p_deps = self[p_idx].dependencies();
+        if self[p_idx].has_dynamic_dependencies() {
+            self.dynamic.insert(p_idx, p_deps.clone());
+        }
+        for (v, ev) in p_deps {
+            debug_assert!(v < vstore.size(),
+        "The propagator {:?} has a dependency to the variable {} which is not in the vstore (of size {}).\n\
+        Hint: you should not manually create `Identity` struct, if you do make sure they contain relevant index to the variable vstore.",
+        self[p_idx], v, vstore.size());
+            self.reactor.subscribe(v, ev, p_idx);
+        }
+        if self[p_idx].is_incremental() {
+            self.incremental.insert(p_idx);
+        } else {
+            self.incremental.remove(p_idx);
         }
     }
 
@@ -152,33 +219,68 @@ where
         let mut consistent = true;
         while !self.scheduler.is_empty() && consistent {
             while let Some(p_idx) = self.scheduler.pop() {
-                if !self.propagate_one(p_idx, vstore) {
-                    consistent = false;
-                    break;
+                match self.propagate_one(p_idx, vstore) {
+                    PropagatorStatus::Failed => {
+                        consistent = false;
+                        break;
+                    }
+                    PropagatorStatus::Fixpoint => self.react(vstore, Some(p_idx)),
+                    _ => self.react(vstore, None),
                 }
-                self.react(vstore);
             }
             // self.react(vstore); // For bulk reaction.
         }
         consistent
     }
 
-    fn propagate_one(&mut self, p_idx: usize, vstore: &mut VStore) -> bool {
+    fn propagate_one(&mut self, p_idx: usize, vstore: &mut VStore) -> PropagatorStatus {
         vstore.reset_changed();
-        let subsumed = self.propagator_consistency(p_idx, vstore);
-        match subsumed {
-            False => return false,
-            True => self.unlink_prop(p_idx),
-            Unknown => self.reschedule_prop(p_idx, vstore),
+        let start = self.profile.as_ref().map(|_| Instant::now());
+        let status = self.propagator_consistency(p_idx, vstore);
+        if let (Some(profile), Some(start)) = (self.profile.as_mut(), start) {
+            let pruned = vstore.has_changed();
+            profile.record(
+                p_idx,
+                &*self.propagators[p_idx],
+                status,
+                pruned,
+                start.elapsed(),
+            );
+        }
+        match status {
+            PropagatorStatus::Subsumed => self.unlink_prop(p_idx),
+            PropagatorStatus::NotFixpoint | PropagatorStatus::NotSubsumed => {
+                self.reschedule_prop(p_idx, vstore)
+            }
+            _ => (),
         };
-        true
+        if status != PropagatorStatus::Subsumed && self.dynamic.contains_key(p_idx) {
+            self.update_subscriptions(p_idx);
+        }
+        status
     }
 
-    fn propagator_consistency(&mut self, p_idx: usize, vstore: &mut VStore) -> SKleene {
-        if self[p_idx].propagate(vstore) {
-            self[p_idx].is_subsumed(vstore)
+    /// The status of the propagation of `p_idx`, `NotFixpoint` is only returned if the propagator is not subsumed.
+    fn propagator_consistency(&mut self, p_idx: usize, vstore: &mut VStore) -> PropagatorStatus {
+        let advice = if self.incremental.contains(p_idx) {
+            vstore.take_advice(p_idx)
         } else {
-            False
+            None
+        };
+        let status = match advice {
+            Some(delta) if !self.full.remove(p_idx) => self[p_idx].propagate_delta(vstore, &delta),
+            _ => {
+                self.full.remove(p_idx);
+                self[p_idx].propagate_status(vstore)
+            }
+        };
+        match status {
+            PropagatorStatus::NotFixpoint => match self[p_idx].is_subsumed(vstore) {
+                False => PropagatorStatus::Failed,
+                True => PropagatorStatus::Subsumed,
+                Unknown => PropagatorStatus::NotFixpoint,
+            },
+            status => status,
         }
     }
 
@@ -188,25 +290,59 @@ where
         }
     }
 
-    fn react(&mut self, vstore: &mut VStore) {
+    /// Schedules the propagators reacting to the delta of `vstore`, except `at_fixpoint` which is the propagator that just reached its fixpoint.
+    fn react(&mut self, vstore: &mut VStore, at_fixpoint: Option<usize>) {
+        let mut advices = vec![];
         for (v, ev) in vstore.drain_delta() {
             let reactions = self.reactor.react(v, ev);
-            for p in reactions.into_iter() {
+            for p in reactions.into_iter().filter(|&p| Some(p) != at_fixpoint) {
                 self.scheduler.schedule(p);
+                if self.incremental.contains(p) {
+                    advices.push((p, v));
+                }
             }
         }
+        for (p, v) in advices {
+            vstore.advise(p, v);
+        }
+        vstore.forget_old_domains();
     }
 
     fn unlink_prop(&mut self, p_idx: usize) {
         self.active.remove(p_idx);
         self.scheduler.unschedule(p_idx);
-        let deps = self[p_idx].dependencies();
+        let deps = match self.dynamic.remove(p_idx) {
+            Some(deps) => deps,
+            None => self[p_idx].dependencies(),
+        };
         for &(var, ev) in deps.iter() {
             self.reactor.unsubscribe(var, ev, p_idx)
         }
     }
 }
 
+impl<VStore, Event, R, S> Store<VStore, Event, R, S>
+where
+    Event: EventIndex,
+    R: Reactor,
+{
+    /// Moves the subscriptions of a propagator with dynamic dependencies to its current dependencies, returns `true` if they changed.
+    fn update_subscriptions(&mut self, p_idx: usize) -> bool {
+        let deps = self[p_idx].dependencies();
+        let old = self.dynamic.insert(p_idx, deps.clone()).unwrap_or_default();
+        let key = |&(v, ev): &(usize, Event)| (v, ev.to_index());
+        let old_keys: Vec<_> = old.iter().map(key).collect();
+        let new_keys: Vec<_> = deps.iter().map(key).collect();
+        for &(v, ev) in old.iter().filter(|d| !new_keys.contains(&key(d))) {
+            self.reactor.unsubscribe(v, ev, p_idx);
+        }
+        for &(v, ev) in deps.iter().filter(|d| !old_keys.contains(&key(d))) {
+            self.reactor.subscribe(v, ev, p_idx);
+        }
+        old_keys != new_keys
+    }
+}
+
 impl<VStore, Event, R, S> Index<usize> for Store<VStore, Event, R, S> {
     type Output = Box<dyn PropagatorConcept<VStore, Event> + 'static>;
     fn index(&self, index: usize) -> &Self::Output {
@@ -223,6 +359,9 @@ impl<VStore, Event, R, S> IndexMut<usize> for Store<VStore, Event, R, S> {
 impl<VStore, Event, R, S> Alloc for Store<VStore, Event, R, S> {
     fn alloc(&mut self, p: Self::Item) -> usize {
         let idx = self.propagators.len();
+        if let Some(profile) = self.profile.as_mut() {
+            profile.reuse(idx);
+        }
         self.propagators.push(p);
         self.active.insert(idx);
         idx
@@ -239,7 +378,7 @@ impl<VStore, Event, R, S> Subsumption<VStore> for Store<VStore, Event, R, S> {
 
 impl<VStore, Event, R, S> Consistency<VStore> for Store<VStore, Event, R, S>
 where
-    VStore: Cardinality<Size = usize> + DrainDelta<Event>,
+    VStore: Cardinality<Size = usize> + DrainDelta<Event> + AdviseDelta,
     Event: EventIndex,
     R: Reactor + Cardinality<Size = usize>,
     S: Scheduler,
@@ -260,13 +399,26 @@ where
 impl<VStore, Event, R, S> Clone for Store<VStore, Event, R, S>
 where
     Event: EventIndex,
-    R: Reactor,
+    R: Reactor + Clone,
     S: Scheduler,
 {
+    /// The subscriptions are kept but, since the clone might not be used with a variables store at its fixed point, all the active propagators are scheduled.
     fn clone(&self) -> Self {
-        let mut cstore = Store::empty();
+        let mut cstore: Self = Store::empty();
         cstore.propagators = self.propagators.iter().map(|p| p.bclone()).collect();
         cstore.active = self.active.clone();
+        cstore.reactor = self.reactor.clone();
+        cstore.reactor_vars = self.reactor_vars;
+        cstore.subscribed = self.subscribed;
+        cstore.incremental = self.incremental.clone();
+        cstore.scheduler.reserve(self.propagators.len());
+        for p_idx in self.active.iter().filter(|&p_idx| p_idx < self.subscribed) {
+            cstore.scheduler.set_cost(p_idx, self[p_idx].cost());
+            cstore.scheduler.schedule(p_idx);
+            cstore.full.insert(p_idx);
+        }
+        cstore.profile = self.profile.clone();
+        cstore.dynamic = self.dynamic.clone();
         cstore
     }
 }
@@ -303,22 +455,74 @@ where
     }
 }
 
+/// The label of a constraint store is the number of propagators and the active ones.
+/// The propagators with an internal state (see `Propagator::is_stateful`) are copied in the label since they are modified during propagation, except the trailed ones for which the label keeps the position of their trail.
+pub struct StoreLabel<VStore, Event, R> {
+    num_propagators: usize,
+    active: BitSet,
+    reactor: R,
+    reactor_vars: usize,
+    subscribed: usize,
+    stateful: Vec<(usize, Box<dyn PropagatorConcept<VStore, Event> + 'static>)>,
+    trailed: Vec<(usize, usize)>,
+    dynamic: VecMap<Vec<(usize, Event)>>,
+}
+
 impl<VStore, Event, R, S> Snapshot for FrozenStore<VStore, Event, R, S>
 where
     Event: EventIndex,
     R: Reactor + Clone,
     S: Scheduler,
 {
-    type Label = (usize, BitSet);
+    type Label = StoreLabel<VStore, Event, R>;
     type State = Store<VStore, Event, R, S>;
 
     fn label(&mut self) -> Self::Label {
-        (self.cstore.propagators.len(), self.cstore.active.clone())
+        let mut stateful = vec![];
+        let mut trailed = vec![];
+        for p_idx in self.cstore.active.iter() {
+            let p = &mut self.cstore.propagators[p_idx];
+            if p.is_trailed() {
+                trailed.push((p_idx, p.mark()));
+            } else if p.is_stateful() {
+                stateful.push((p_idx, p.bclone()));
+            }
+        }
+        let cstore = &self.cstore;
+        StoreLabel {
+            num_propagators: cstore.propagators.len(),
+            active: cstore.active.clone(),
+            reactor: cstore.reactor.clone(),
+            reactor_vars: cstore.reactor_vars,
+            subscribed: cstore.subscribed.min(cstore.propagators.len()),
+            stateful,
+            trailed,
+            dynamic: cstore.dynamic.clone(),
+        }
     }
 
     fn restore(mut self, label: Self::Label) -> Self::State {
-        self.cstore.propagators.truncate(label.0);
-        self.cstore.active = label.1;
+        self.cstore.propagators.truncate(label.num_propagators);
+        self.cstore.active = label.active;
+        self.cstore.reactor = label.reactor;
+        self.cstore.reactor_vars = label.reactor_vars;
+        self.cstore.subscribed = label.subscribed;
+        for (p_idx, p) in label.stateful {
+            self.cstore.propagators[p_idx] = p;
+        }
+        for (p_idx, mark) in label.trailed {
+            self.cstore.propagators[p_idx].undo(mark);
+        }
+        // The propagators left by a failed node are not relevant anymore.
+        while self.cstore.scheduler.pop().is_some() {}
+        // The dependencies of the propagators might have changed since the label was taken, the propagators are scheduled to check their new dependencies (e.g. a watched literal already true).
+        self.cstore.dynamic = label.dynamic;
+        let dynamic: Vec<_> = self.cstore.dynamic.keys().collect();
+        for p_idx in dynamic {
+            if self.cstore.update_subscriptions(p_idx) {
+                self.cstore.scheduler.schedule(p_idx);
+            }
+        }
         self.cstore
     }
 }
@@ -416,3 +620,363 @@ where
 //     assert_eq!(constraints.consistency(variables), expect);
 //   }
 // }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use concept::*;
+    use interval::interval::*;
+    use logic::NotFormula;
+    use propagation::events::FDEvent;
+    use propagation::CStoreFD;
+    use propagators::cmp::XLessY;
+    use std::cell::{Cell, RefCell};
+    use std::rc::Rc;
+    use term::identity::Identity;
+    use term::Constant;
+    use variable::VStoreFD;
+
+    type CStore = CStoreFD<VStoreFD>;
+
+    /// Counts the number of times it is propagated.
+    #[derive(Clone, Debug)]
+    struct Counter {
+        var: usize,
+        calls: Rc<Cell<usize>>,
+    }
+
+    impl Propagator<VStoreFD> for Counter {
+        fn propagate(&mut self, _vstore: &mut VStoreFD) -> bool {
+            self.calls.set(self.calls.get() + 1);
+            true
+        }
+    }
+
+    impl Subsumption<VStoreFD> for Counter {
+        fn is_subsumed(&self, _vstore: &VStoreFD) -> SKleene {
+            Unknown
+        }
+    }
+
+    impl PropagatorDependencies<FDEvent> for Counter {
+        fn dependencies(&self) -> Vec<(usize, FDEvent)> {
+            vec![(self.var, FDEvent::Inner)]
+        }
+    }
+
+    impl DisplayStateful<Model> for Counter {
+        fn display(&self, _model: &Model) {}
+    }
+
+    impl NotFormula<VStoreFD> for Counter {
+        fn not(&self) -> Formula<VStoreFD> {
+            unimplemented!()
+        }
+    }
+
+    /// The variables `x` and `y` in [0..10] with a counter on each.
+    fn counters() -> (VStoreFD, CStore, Var<VStoreFD>, Vec<Rc<Cell<usize>>>) {
+        let mut vstore = VStoreFD::empty();
+        let x = Box::new(vstore.alloc((0, 10).to_interval())) as Var<VStoreFD>;
+        vstore.alloc((0, 10).to_interval());
+        let mut cstore = CStore::empty();
+        let calls: Vec<_> = (0..2).map(|_| Rc::new(Cell::new(0))).collect();
+        for (var, calls) in calls.iter().enumerate() {
+            cstore.alloc(Box::new(Counter {
+                var,
+                calls: calls.clone(),
+            }));
+        }
+        (vstore, cstore, x, calls)
+    }
+
+    fn num_calls(calls: &[Rc<Cell<usize>>]) -> Vec<usize> {
+        calls.iter().map(|c| c.get()).collect()
+    }
+
+    #[test]
+    fn child_schedules_affected_propagators_test() {
+        let (mut vstore, mut cstore, x, calls) = counters();
+        assert_eq!(cstore.consistency(&mut vstore), Unknown);
+        assert_eq!(num_calls(&calls), vec![1, 1]);
+
+        let mut frozen = cstore.freeze();
+        let left = frozen.label();
+        let right = frozen.label();
+
+        // The left child fails, only `x < 0` and the counter of `x` are propagated.
+        let mut child = frozen.restore(left);
+        child.alloc(Box::new(XLessY::new(
+            x.bclone(),
+            Box::new(Constant::new(0)),
+        )));
+        let mut child_vstore = vstore.clone();
+        assert_eq!(child.consistency(&mut child_vstore), False);
+        assert_eq!(num_calls(&calls), vec![1, 1]);
+
+        let frozen = child.freeze();
+        let mut child = frozen.restore(right);
+        child.alloc(Box::new(XLessY::new(
+            x.bclone(),
+            Box::new(Constant::new(5)),
+        )));
+        assert_eq!(child.consistency(&mut vstore), Unknown);
+        assert_eq!(num_calls(&calls), vec![2, 1]);
+
+        // Nothing changed since the last call.
+        assert_eq!(child.consistency(&mut vstore), Unknown);
+        assert_eq!(num_calls(&calls), vec![2, 1]);
+    }
+
+    #[test]
+    fn clone_schedules_all_propagators_test() {
+        let (mut vstore, mut cstore, _, calls) = counters();
+        assert_eq!(cstore.consistency(&mut vstore), Unknown);
+        let mut copy = cstore.clone();
+        assert_eq!(copy.consistency(&mut vstore), Unknown);
+        assert_eq!(num_calls(&calls), vec![2, 2]);
+        assert_eq!(cstore.consistency(&mut vstore), Unknown);
+        assert_eq!(num_calls(&calls), vec![2, 2]);
+    }
+
+    #[test]
+    fn new_variables_test() {
+        let (mut vstore, mut cstore, _, mut calls) = counters();
+        assert_eq!(cstore.consistency(&mut vstore), Unknown);
+        vstore.alloc((0, 10).to_interval());
+        calls.push(Rc::new(Cell::new(0)));
+        cstore.alloc(Box::new(Counter {
+            var: 2,
+            calls: calls[2].clone(),
+        }));
+        assert_eq!(cstore.consistency(&mut vstore), Unknown);
+        assert_eq!(num_calls(&calls), vec![2, 2, 1]);
+    }
+
+    type Delta = Vec<VarDelta<Interval<i32>>>;
+
+    /// Records the deltas it receives, `None` for a call to `propagate`.
+    #[derive(Clone, Debug)]
+    struct Recorder {
+        calls: Rc<RefCell<Vec<Option<Delta>>>>,
+    }
+
+    impl Propagator<VStoreFD> for Recorder {
+        fn propagate(&mut self, _vstore: &mut VStoreFD) -> bool {
+            self.calls.borrow_mut().push(None);
+            true
+        }
+
+        fn is_incremental(&self) -> bool {
+            true
+        }
+
+        fn propagate_delta(
+            &mut self,
+            _vstore: &mut VStoreFD,
+            delta: &[VarDelta<Interval<i32>>],
+        ) -> PropagatorStatus {
+            self.calls.borrow_mut().push(Some(delta.to_vec()));
+            PropagatorStatus::NotFixpoint
+        }
+    }
+
+    impl Subsumption<VStoreFD> for Recorder {
+        fn is_subsumed(&self, _vstore: &VStoreFD) -> SKleene {
+            Unknown
+        }
+    }
+
+    impl PropagatorDependencies<FDEvent> for Recorder {
+        fn dependencies(&self) -> Vec<(usize, FDEvent)> {
+            vec![(0, FDEvent::Inner), (1, FDEvent::Inner)]
+        }
+    }
+
+    impl DisplayStateful<Model> for Recorder {
+        fn display(&self, _model: &Model) {}
+    }
+
+    impl NotFormula<VStoreFD> for Recorder {
+        fn not(&self) -> Formula<VStoreFD> {
+            unimplemented!()
+        }
+    }
+
+    #[test]
+    fn incremental_propagation_test() {
+        let (mut vstore, mut cstore, _, _) = counters();
+        let calls = Rc::new(RefCell::new(vec![]));
+        cstore.alloc(Box::new(Recorder {
+            calls: calls.clone(),
+        }));
+        let x = Identity::new(0);
+        let y = Identity::new(1);
+        let delta = |var, event, old: (i32, i32)| VarDelta {
+            var,
+            event,
+            old: old.to_interval(),
+        };
+
+        // The first propagation is not incremental.
+        assert_eq!(cstore.consistency(&mut vstore), Unknown);
+        assert_eq!(calls.borrow_mut().pop(), Some(None));
+
+        assert!(vstore.update(&x, (0, 5).to_interval()));
+        assert_eq!(cstore.consistency(&mut vstore), Unknown);
+        assert_eq!(
+            calls.borrow_mut().pop(),
+            Some(Some(vec![delta(0, FDEvent::Bound, (0, 10))]))
+        );
+
+        // The old domain is the one before the first modification.
+        assert!(vstore.update(&x, (2, 5).to_interval()));
+        assert!(vstore.update(&x, (2, 3).to_interval()));
+        assert!(vstore.update(&y, (7, 7).to_interval()));
+        assert_eq!(cstore.consistency(&mut vstore), Unknown);
+        assert_eq!(
+            calls.borrow_mut().pop(),
+            Some(Some(vec![
+                delta(0, FDEvent::Bound, (0, 5)),
+                delta(1, FDEvent::Assignment, (0, 10))
+            ]))
+        );
+
+        assert_eq!(cstore.consistency(&mut vstore), Unknown);
+        assert!(calls.borrow().is_empty());
+
+        // A clone is not at its fixed point.
+        let mut copy = cstore.clone();
+        assert_eq!(copy.consistency(&mut vstore), Unknown);
+        assert_eq!(calls.borrow_mut().pop(), Some(None));
+    }
+
+    /// Removes the upper bound of `var` and reports `status`.
+    #[derive(Clone, Debug)]
+    struct Shrink {
+        status: PropagatorStatus,
+        calls: Rc<Cell<usize>>,
+    }
+
+    impl Propagator<VStoreFD> for Shrink {
+        fn propagate(&mut self, vstore: &mut VStoreFD) -> bool {
+            self.calls.set(self.calls.get() + 1);
+            let x = vstore[0];
+            if x.lower() < x.upper() {
+                vstore.update(&Identity::new(0), x.shrink_right(x.upper() - 1))
+            } else {
+                true
+            }
+        }
+
+        fn propagate_status(&mut self, vstore: &mut VStoreFD) -> PropagatorStatus {
+            self.propagate(vstore);
+            self.status
+        }
+    }
+
+    impl Subsumption<VStoreFD> for Shrink {
+        fn is_subsumed(&self, _vstore: &VStoreFD) -> SKleene {
+            assert!(
+                self.status != PropagatorStatus::Subsumed
+                    && self.status != PropagatorStatus::NotSubsumed
+            );
+            Unknown
+        }
+    }
+
+    impl PropagatorDependencies<FDEvent> for Shrink {
+        fn dependencies(&self) -> Vec<(usize, FDEvent)> {
+            vec![(0, FDEvent::Inner)]
+        }
+    }
+
+    impl DisplayStateful<Model> for Shrink {
+        fn display(&self, _model: &Model) {}
+    }
+
+    impl NotFormula<VStoreFD> for Shrink {
+        fn not(&self) -> Formula<VStoreFD> {
+            unimplemented!()
+        }
+    }
+
+    fn shrink_test_one(status: PropagatorStatus, expected: SKleene, expected_calls: usize) {
+        let mut vstore = VStoreFD::empty();
+        vstore.alloc((0, 10).to_interval());
+        let mut cstore = CStore::empty();
+        let calls = Rc::new(Cell::new(0));
+        cstore.alloc(Box::new(Shrink {
+            status,
+            calls: calls.clone(),
+        }));
+        assert_eq!(cstore.consistency(&mut vstore), expected);
+        assert_eq!(calls.get(), expected_calls, "{:?}", status);
+    }
+
+    #[test]
+    fn propagator_status_test() {
+        // Rescheduled until `x` is assigned.
+        shrink_test_one(PropagatorStatus::NotFixpoint, Unknown, 11);
+        // Not rescheduled for its own modifications.
+        shrink_test_one(PropagatorStatus::Fixpoint, Unknown, 1);
+        // Rescheduled without calling `is_subsumed`.
+        shrink_test_one(PropagatorStatus::NotSubsumed, Unknown, 11);
+        // Removed without calling `is_subsumed`.
+        shrink_test_one(PropagatorStatus::Subsumed, True, 1);
+        shrink_test_one(PropagatorStatus::Failed, False, 1);
+    }
+
+    #[test]
+    fn profiling_test() {
+        let mut vstore = VStoreFD::empty();
+        let x = Box::new(vstore.alloc((0, 10).to_interval())) as Var<VStoreFD>;
+        let mut cstore = CStore::empty();
+        cstore.alloc(Box::new(Shrink {
+            status: PropagatorStatus::NotFixpoint,
+            calls: Rc::new(Cell::new(0)),
+        }));
+        assert!(cstore.profile().is_none());
+        cstore.enable_profiling();
+        assert_eq!(cstore.consistency(&mut vstore), Unknown);
+        let stats = *cstore.profile().unwrap().stats(0).unwrap();
+        // The last call does not modify `x`.
+        assert_eq!(
+            (stats.num_calls, stats.num_prunings, stats.num_failures),
+            (11, 10, 0)
+        );
+        cstore.profile().unwrap().display(&Model::new());
+
+        cstore.reset_profile();
+        cstore.alloc(Box::new(XLessY::new(x, Box::new(Constant::new(0)))));
+        assert_eq!(cstore.consistency(&mut vstore), False);
+        let profile = cstore.profile().unwrap();
+        assert_eq!(
+            profile.iter().map(|(p_idx, _)| p_idx).collect::<Vec<_>>(),
+            vec![1]
+        );
+        assert_eq!(profile.stats(1).unwrap().num_failures, 1);
+        assert!(!profile.is_aggregate(1));
+
+        // A propagator allocated at the same index on backtracking is aggregated with the previous one.
+        let mut cstore = CStore::empty();
+        cstore.enable_profiling();
+        let mut frozen = cstore.freeze();
+        let left = frozen.label();
+        let right = frozen.label();
+        let mut cstore = frozen.restore(left);
+        cstore.alloc(Box::new(XLessY::new(
+            Box::new(Constant::new(1)),
+            Box::new(Constant::new(0)),
+        )));
+        assert_eq!(cstore.consistency(&mut vstore), False);
+        let mut cstore = cstore.freeze().restore(right);
+        assert!(!cstore.profile().unwrap().is_aggregate(0));
+        cstore.alloc(Box::new(XLessY::new(
+            Box::new(Constant::new(0)),
+            Box::new(Constant::new(1)),
+        )));
+        assert!(cstore.profile().unwrap().is_aggregate(0));
+        cstore.profile().unwrap().display(&Model::new());
+    }
+}
@@ -77,6 +77,7 @@ where
 impl<VStore> DisplayStateful<Model> for AllEqual<VStore> {
     fn display(&self, model: &Model) {
         model.display_global("all_equal", &self.vars);
+        print!(" (decomposed)");
     }
 }
 
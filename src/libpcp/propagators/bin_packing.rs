@@ -0,0 +1,714 @@
+// Copyright 2018 Pierre Talbot (IRCAM)
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+
+//     http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! The constraint `bin_packing(loads, bins, sizes)` places each item `i` of size `sizes[i]` into the bin `bins[i]` (numbered from 0), such that `loads[j]` is the sum of the sizes of the items placed in `j`.
+//! The filtering is the one described in:
+//!   Shaw, P. (2004). A constraint for bin packing. In International Conference on Principles and Practice of Constraint Programming (pp. 648-662). Springer.
+//! It maintains the bounds of the loads from the items already packed and the candidate items, and uses a knapsack reasoning (`no_sum`) on each bin.
+
+use concept::*;
+use gcollections::*;
+use kernel::*;
+use logic::*;
+use model::*;
+use propagation::events::*;
+use propagation::*;
+use propagators::values::naturals;
+use std::fmt::Debug;
+use trilean::SKleene;
+use trilean::SKleene::*;
+
+#[derive(Debug)]
+pub struct BinPacking<VStore, Bound> {
+    loads: Vec<Var<VStore>>,
+    bins: Vec<Var<VStore>>,
+    sizes: Vec<Bound>,
+    /// The items by decreasing size.
+    order: Vec<usize>,
+    /// `indices[j]` is the bin `j` as a value of the variables in `bins`.
+    indices: Vec<Bound>,
+    negated: bool,
+}
+
+impl<VStore, Domain, Bound> BinPacking<VStore, Bound>
+where
+    VStore: Collection<Item = Domain>,
+    Domain: IntDomain<Item = Bound>,
+    Bound: IntBound,
+{
+    /// Precondition: `bins.len() == sizes.len()` and the sizes are non-negative.
+    pub fn new(loads: Vec<Var<VStore>>, bins: Vec<Var<VStore>>, sizes: Vec<Bound>) -> Self {
+        assert_eq!(
+            bins.len(),
+            sizes.len(),
+            "Every item in `BinPacking` must have a bin and a size."
+        );
+        assert!(
+            sizes.iter().all(|s| *s >= Bound::zero()),
+            "The sizes in `BinPacking` must be non-negative."
+        );
+        let mut order: Vec<_> = (0..sizes.len()).collect();
+        order.sort_by(|&i, &j| sizes[j].cmp(&sizes[i]));
+        let mut indices = naturals(loads.len());
+        indices.pop();
+        BinPacking {
+            loads,
+            bins,
+            sizes,
+            order,
+            indices,
+            negated: false,
+        }
+    }
+
+    fn total(&self) -> Bound {
+        sum(self.sizes.iter().cloned())
+    }
+
+    /// The bin of an item if it is assigned to a valid bin.
+    fn assigned(&self, bin: &Domain) -> Option<usize> {
+        if bin.is_singleton() {
+            self.indices.binary_search(&bin.lower()).ok()
+        } else {
+            None
+        }
+    }
+
+    /// For each bin, the total size of the items packed in it and the candidate items, by decreasing size.
+    fn packing(&self, bins: &[Domain]) -> (Vec<Bound>, Vec<Vec<usize>>) {
+        let m = self.loads.len();
+        let mut required = vec![Bound::zero(); m];
+        let mut candidates = vec![vec![]; m];
+        for &i in &self.order {
+            match self.assigned(&bins[i]) {
+                Some(j) => required[j] = required[j].clone() + self.sizes[i].clone(),
+                None => {
+                    for (j, index) in self.indices.iter().enumerate() {
+                        if bins[i].contains(index) {
+                            candidates[j].push(i);
+                        }
+                    }
+                }
+            }
+        }
+        (required, candidates)
+    }
+
+    fn sizes_of(&self, items: &[usize]) -> Vec<Bound> {
+        items.iter().map(|&i| self.sizes[i].clone()).collect()
+    }
+
+    fn read_bins(&self, vstore: &VStore) -> Vec<Domain> {
+        self.bins.iter().map(|b| b.read(vstore)).collect()
+    }
+
+    /// One pass of filtering, it returns `None` on failure and otherwise whether a domain changed.
+    fn filter(&mut self, vstore: &mut VStore) -> Option<bool> {
+        let m = self.loads.len();
+        let mut changed = false;
+        let (required, candidates) = self.packing(&self.read_bins(vstore));
+        // The load of a bin is between the size of its packed items and the size of its packed and candidate items.
+        for j in 0..m {
+            let possible = required[j].clone() + sum(self.sizes_of(&candidates[j]).into_iter());
+            let load = self.loads[j]
+                .read(vstore)
+                .intersection(&Domain::new(required[j].clone(), possible));
+            update(vstore, &mut self.loads[j], load, &mut changed)?;
+        }
+        // The sum of the loads is the total size of the items.
+        let total = self.total();
+        let loads: Vec<_> = self.loads.iter().map(|l| l.read(vstore)).collect();
+        let sum_lb = sum(loads.iter().map(|l| l.lower()));
+        let sum_ub = sum(loads.iter().map(|l| l.upper()));
+        for (var, load) in self.loads.iter_mut().zip(loads) {
+            let lb = total.clone() - (sum_ub.clone() - load.upper());
+            let ub = total.clone() - (sum_lb.clone() - load.lower());
+            let load = var.read(vstore).intersection(&Domain::new(lb, ub));
+            update(vstore, var, load, &mut changed)?;
+        }
+        // Knapsack reasoning on the candidates of each bin.
+        for j in 0..m {
+            let candidate_sizes = self.sizes_of(&candidates[j]);
+            let load = self.loads[j].read(vstore);
+            let lb = load.lower() - required[j].clone();
+            let ub = load.upper() - required[j].clone();
+            if no_sum(&candidate_sizes, lb.clone(), ub.clone()).is_some() {
+                return None;
+            }
+            let mut new_lb = load.lower();
+            let mut new_ub = load.upper();
+            if let Some((_, above)) = no_sum(&candidate_sizes, lb.clone(), lb) {
+                new_lb = required[j].clone() + above;
+            }
+            if let Some((below, _)) = no_sum(&candidate_sizes, ub.clone(), ub) {
+                new_ub = required[j].clone() + below;
+            }
+            let load = load.intersection(&Domain::new(new_lb, new_ub));
+            update(vstore, &mut self.loads[j], load, &mut changed)?;
+        }
+        // An item is removed from a bin if the bin cannot be filled with it, and packed into a bin that cannot be filled without it.
+        for j in 0..m {
+            let load = self.loads[j].read(vstore);
+            let lb = load.lower() - required[j].clone();
+            let ub = load.upper() - required[j].clone();
+            let candidate_sizes = self.sizes_of(&candidates[j]);
+            let possible = sum(candidate_sizes.iter().cloned());
+            for (c, &i) in candidates[j].iter().enumerate() {
+                let size = self.sizes[i].clone();
+                let mut others = candidate_sizes.clone();
+                others.remove(c);
+                let bin = self.bins[i].read(vstore);
+                let bin = if size > ub
+                    || no_sum(
+                        &others,
+                        lb.clone() - size.clone(),
+                        ub.clone() - size.clone(),
+                    )
+                    .is_some()
+                {
+                    bin.difference(&self.indices[j])
+                } else if possible.clone() - size < lb
+                    || no_sum(&others, lb.clone(), ub.clone()).is_some()
+                {
+                    bin.intersection(&Domain::singleton(self.indices[j].clone()))
+                } else {
+                    bin
+                };
+                update(vstore, &mut self.bins[i], bin, &mut changed)?;
+            }
+        }
+        Some(changed)
+    }
+
+    /// When all the items are packed, at least one load must differ from the size of its packed items.
+    fn propagate_negated(&mut self, vstore: &mut VStore) -> bool {
+        let bins = self.read_bins(vstore);
+        if bins.iter().any(|b| self.assigned(b).is_none()) {
+            return true;
+        }
+        let (required, _) = self.packing(&bins);
+        let mut unknown = vec![];
+        for (j, r) in required.iter().enumerate() {
+            let load = self.loads[j].read(vstore);
+            if !load.contains(r) {
+                return true;
+            }
+            if !load.is_singleton() {
+                unknown.push(j);
+            }
+        }
+        match unknown.len() {
+            0 => false,
+            1 => {
+                let j = unknown[0];
+                let load = self.loads[j].read(vstore).difference(&required[j]);
+                self.loads[j].update(vstore, load)
+            }
+            _ => true,
+        }
+    }
+}
+
+fn sum<Bound, I>(values: I) -> Bound
+where
+    Bound: IntBound,
+    I: Iterator<Item = Bound>,
+{
+    values.fold(Bound::zero(), |acc, v| acc + v)
+}
+
+/// Updates `var` with `dom` and records if the domain changed, it returns `None` on failure.
+fn update<VStore, Domain, Bound>(
+    vstore: &mut VStore,
+    var: &mut Var<VStore>,
+    dom: Domain,
+    changed: &mut bool,
+) -> Option<()>
+where
+    VStore: Collection<Item = Domain>,
+    Domain: IntDomain<Item = Bound>,
+    Bound: IntBound,
+{
+    if dom.size() != var.read(vstore).size() {
+        *changed = true;
+    }
+    if var.update(vstore, dom) {
+        Some(())
+    } else {
+        None
+    }
+}
+
+/// Detects that no subset of `sizes` (sorted by decreasing size) sums to a value in `[alpha..beta]`.
+/// In this case, it returns the largest sum `alpha' < alpha` and the smallest sum `beta' > beta` such that there is no sum in `]alpha'..beta'[`.
+/// It is not complete: `None` does not mean that such a subset exists.
+fn no_sum<Bound: IntBound>(sizes: &[Bound], alpha: Bound, beta: Bound) -> Option<(Bound, Bound)> {
+    let n = sizes.len();
+    if alpha <= Bound::zero() || beta >= sum(sizes.iter().cloned()) {
+        return None;
+    }
+    // `x(i)` is the `i`-th largest size, starting from 1.
+    let x = |i: usize| sizes[i - 1].clone();
+    // `sum_a`: sum of the `k` largest sizes, `sum_c`: sum of the `k2` smallest sizes, `sum_b`: sum of the `k + 1` sizes following the smallest ones.
+    let mut sum_a = Bound::zero();
+    let mut sum_c = Bound::zero();
+    let mut k = 0;
+    let mut k2 = 0;
+    while sum_c.clone() + x(n - k2) < alpha {
+        sum_c = sum_c + x(n - k2);
+        k2 += 1;
+    }
+    let mut sum_b = x(n - k2);
+    while sum_a < alpha && sum_b <= beta {
+        k += 1;
+        sum_a = sum_a + x(k);
+        if sum_a < alpha {
+            k2 -= 1;
+            sum_b = sum_b + x(n - k2);
+            sum_c = sum_c - x(n - k2);
+            while sum_a.clone() + sum_c.clone() >= alpha {
+                k2 -= 1;
+                sum_c = sum_c - x(n - k2);
+                sum_b = sum_b + x(n - k2) - x(n - k2 - k - 1);
+            }
+        }
+    }
+    if sum_a < alpha {
+        Some((sum_a + sum_c, sum_b))
+    } else {
+        None
+    }
+}
+
+impl<VStore, Bound> Clone for BinPacking<VStore, Bound>
+where
+    VStore: Collection,
+    Bound: Clone,
+{
+    fn clone(&self) -> Self {
+        BinPacking {
+            loads: self.loads.iter().map(|v| v.bclone()).collect(),
+            bins: self.bins.iter().map(|v| v.bclone()).collect(),
+            sizes: self.sizes.clone(),
+            order: self.order.clone(),
+            indices: self.indices.clone(),
+            negated: self.negated,
+        }
+    }
+}
+
+impl<VStore, Bound> DisplayStateful<Model> for BinPacking<VStore, Bound>
+where
+    Bound: Debug,
+{
+    fn display(&self, model: &Model) {
+        if self.negated {
+            print!("not ");
+        }
+        print!("bin_packing(");
+        model.display_array(&self.loads);
+        print!(", ");
+        model.display_array(&self.bins);
+        print!(", {:?})", self.sizes);
+    }
+}
+
+impl<VStore, Domain, Bound> NotFormula<VStore> for BinPacking<VStore, Bound>
+where
+    VStore: VStoreConcept<Item = Domain> + 'static,
+    Domain: IntDomain<Item = Bound> + 'static,
+    Bound: IntBound + 'static,
+{
+    fn not(&self) -> Formula<VStore> {
+        let mut not = self.clone();
+        not.negated = !self.negated;
+        Box::new(not)
+    }
+}
+
+impl<VStore, Domain, Bound> Subsumption<VStore> for BinPacking<VStore, Bound>
+where
+    VStore: Collection<Item = Domain>,
+    Domain: IntDomain<Item = Bound>,
+    Bound: IntBound,
+{
+    fn is_subsumed(&self, vstore: &VStore) -> SKleene {
+        // False: an item cannot be packed in any bin, or a load cannot be reached.
+        // True: all the items are packed and the loads are equal to the sizes of their packed items.
+        // Unknown: Everything else.
+        let bins = self.read_bins(vstore);
+        let (required, candidates) = self.packing(&bins);
+        let packable = bins
+            .iter()
+            .all(|b| self.indices.iter().any(|j| b.contains(j)));
+        let mut reachable = packable;
+        let mut packed = bins.iter().all(|b| self.assigned(b).is_some());
+        for j in 0..self.loads.len() {
+            let load = self.loads[j].read(vstore);
+            let possible = required[j].clone() + sum(self.sizes_of(&candidates[j]).into_iter());
+            reachable = reachable && load.upper() >= required[j] && load.lower() <= possible;
+            packed = packed && load.is_singleton() && load.lower() == required[j];
+        }
+        let entailed = if !reachable {
+            False
+        } else if packed {
+            True
+        } else {
+            Unknown
+        };
+        if self.negated {
+            !entailed
+        } else {
+            entailed
+        }
+    }
+}
+
+impl<VStore, Domain, Bound> Propagator<VStore> for BinPacking<VStore, Bound>
+where
+    VStore: Collection<Item = Domain>,
+    Domain: IntDomain<Item = Bound>,
+    Bound: IntBound,
+{
+    fn propagate(&mut self, vstore: &mut VStore) -> bool {
+        if self.negated {
+            return self.propagate_negated(vstore);
+        }
+        let m = self.loads.len();
+        if m == 0 {
+            return self.bins.is_empty();
+        }
+        let valid = Domain::new(Bound::zero(), self.indices[m - 1].clone());
+        for bin in &mut self.bins {
+            let dom = bin.read(vstore).intersection(&valid);
+            if !bin.update(vstore, dom) {
+                return false;
+            }
+        }
+        loop {
+            match self.filter(vstore) {
+                None => return false,
+                Some(false) => return true,
+                Some(true) => (),
+            }
+        }
+    }
+
+    fn cost(&self) -> PropagatorCost {
+        PropagatorCost::Quadratic
+    }
+}
+
+impl<VStore, Bound> PropagatorDependencies<FDEvent> for BinPacking<VStore, Bound> {
+    fn dependencies(&self) -> Vec<(usize, FDEvent)> {
+        let mut deps: Vec<_> = self
+            .bins
+            .iter()
+            .flat_map(|v| v.dependencies(FDEvent::Inner))
+            .collect();
+        let event = if self.negated {
+            FDEvent::Assignment
+        } else {
+            FDEvent::Bound
+        };
+        for load in &self.loads {
+            deps.append(&mut load.dependencies(event));
+        }
+        deps
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use gcollections::ops::*;
+    use interval::interval::*;
+    use interval::interval_set::*;
+    use propagation::events::FDEvent::*;
+    use propagators::cmp::*;
+    use propagators::test::*;
+    use search::test::*;
+    use term::*;
+
+    #[test]
+    fn no_sum_test() {
+        let sizes = vec![5, 5, 3];
+        // Sums: 0, 3, 5, 8, 10, 13.
+        assert_eq!(no_sum(&sizes, 6, 7), Some((5, 8)));
+        assert_eq!(no_sum(&sizes, 11, 12), Some((10, 13)));
+        assert_eq!(no_sum(&sizes, 4, 4), Some((3, 5)));
+        assert_eq!(no_sum(&sizes, 6, 8), None);
+        assert_eq!(no_sum(&sizes, 0, 2), None);
+        assert_eq!(no_sum(&sizes, 12, 13), None);
+    }
+
+    #[test]
+    fn bin_packing_test() {
+        let zero = (0, 0).to_interval();
+        let one = (1, 1).to_interval();
+        let dom0_1 = (0, 1).to_interval();
+        let dom0_10 = (0, 10).to_interval();
+        let dom0_4 = (0, 4).to_interval();
+        let dom5_10 = (5, 10).to_interval();
+        let three = (3, 3).to_interval();
+        let four = (4, 4).to_interval();
+        let five = (5, 5).to_interval();
+        let seven = (7, 7).to_interval();
+
+        // 2 bins and items of sizes [3, 4, 5]: [load0, load1, bin0, bin1, bin2].
+        bin_packing_test_one(
+            1,
+            vec![dom0_10, dom0_10, dom0_1, dom0_1, dom0_1],
+            Unknown,
+            Unknown,
+            vec![(0, Bound), (1, Bound)],
+            true,
+        );
+        // The item of size 5 is packed in the first bin.
+        bin_packing_test_one(
+            2,
+            vec![dom0_10, dom0_10, dom0_1, dom0_1, zero],
+            Unknown,
+            Unknown,
+            vec![(0, Bound), (1, Bound)],
+            true,
+        );
+        // Only the items of sizes 3 and 4 fit in a bin of load 7.
+        bin_packing_test_one(
+            3,
+            vec![seven, dom0_10, dom0_1, dom0_1, dom0_1],
+            Unknown,
+            True,
+            vec![
+                (1, Assignment),
+                (2, Assignment),
+                (3, Assignment),
+                (4, Assignment),
+            ],
+            true,
+        );
+        // The sizes 3 + 5 and 4 + 5 are too large, and 3 + 4 leaves 5 to the other bin.
+        bin_packing_test_one(
+            4,
+            vec![dom0_4, dom0_10, dom0_1, dom0_1, dom0_1],
+            Unknown,
+            Unknown,
+            vec![(0, Bound), (1, Bound), (4, Assignment)],
+            true,
+        );
+        // No subset of {3, 4, 5} sums to 6.
+        bin_packing_test_one(
+            5,
+            vec![(6, 6).to_interval(), dom0_10, dom0_1, dom0_1, dom0_1],
+            Unknown,
+            Unknown,
+            vec![],
+            false,
+        );
+        bin_packing_test_one(
+            6,
+            vec![five, seven, one, one, zero],
+            True,
+            True,
+            vec![],
+            true,
+        );
+        bin_packing_test_one(
+            7,
+            vec![four, seven, one, one, zero],
+            False,
+            False,
+            vec![],
+            false,
+        );
+        bin_packing_test_one(
+            8,
+            vec![dom5_10, three, dom0_1, dom0_1, dom0_1],
+            Unknown,
+            True,
+            vec![
+                (0, Assignment),
+                (2, Assignment),
+                (3, Assignment),
+                (4, Assignment),
+            ],
+            true,
+        );
+    }
+
+    fn bin_packing_test_one(
+        test_num: u32,
+        doms: Vec<Interval<i32>>,
+        before: SKleene,
+        after: SKleene,
+        delta_expected: Vec<(usize, FDEvent)>,
+        propagate_success: bool,
+    ) {
+        nary_propagator_test(
+            test_num,
+            |mut loads: Vec<FDVar>| {
+                let bins = loads.split_off(2);
+                BinPacking::new(loads, bins, vec![3, 4, 5])
+            },
+            doms,
+            before,
+            after,
+            delta_expected,
+            propagate_success,
+        );
+    }
+
+    #[test]
+    fn negated_bin_packing_test() {
+        let one = (1, 1).to_interval();
+        let zero = (0, 0).to_interval();
+        let dom0_10 = (0, 10).to_interval();
+        let dom5_10 = (5, 10).to_interval();
+        let seven = (7, 7).to_interval();
+        let five = (5, 5).to_interval();
+
+        negated_bin_packing_test_one(
+            1,
+            vec![dom0_10, dom0_10, (0, 1).to_interval(), one, zero],
+            Unknown,
+            Unknown,
+            vec![],
+            true,
+        );
+        negated_bin_packing_test_one(
+            2,
+            vec![dom5_10, seven, one, one, zero],
+            Unknown,
+            True,
+            vec![(0, Bound)],
+            true,
+        );
+        negated_bin_packing_test_one(
+            3,
+            vec![five, seven, one, one, zero],
+            False,
+            False,
+            vec![],
+            false,
+        );
+        negated_bin_packing_test_one(
+            4,
+            vec![five, dom0_10, zero, one, zero],
+            True,
+            True,
+            vec![],
+            true,
+        );
+    }
+
+    fn negated_bin_packing_test_one(
+        test_num: u32,
+        doms: Vec<Interval<i32>>,
+        before: SKleene,
+        after: SKleene,
+        delta_expected: Vec<(usize, FDEvent)>,
+        propagate_success: bool,
+    ) {
+        nary_propagator_test(
+            test_num,
+            |mut loads: Vec<FDVar>| {
+                let bins = loads.split_off(2);
+                let mut bin_packing = BinPacking::new(loads, bins, vec![3, 4, 5]);
+                bin_packing.negated = true;
+                bin_packing
+            },
+            doms,
+            before,
+            after,
+            delta_expected,
+            propagate_success,
+        );
+    }
+
+    /// Number of solutions of a bin packing problem with `loads` and `sizes`, where every item can be packed in any bin.
+    /// With `decomposition`, the constraint is decomposed with `load[j] = sum(i in items)(sizes[i] * (bins[i] = j))`.
+    fn count_bin_packing(loads: &[(i32, i32)], sizes: &[i32], decomposition: bool) -> usize {
+        let mut space = FDSpace::empty();
+        let m = loads.len() as i32;
+        let bins: Vec<_> = sizes
+            .iter()
+            .map(|_| Box::new(space.vstore.alloc((0, m - 1).to_interval_set())) as Var<VStore>)
+            .collect();
+        let loads: Vec<_> = loads
+            .iter()
+            .map(|&l| Box::new(space.vstore.alloc(l.to_interval_set())) as Var<VStore>)
+            .collect();
+        if decomposition {
+            for (j, load) in loads.iter().enumerate() {
+                let mut coefs = vec![];
+                let mut vars = vec![];
+                for (i, bin) in bins.iter().enumerate() {
+                    let b = Boolean::new(&mut space.vstore);
+                    let bin_eq_j =
+                        Box::new(XEqY::new(bin.bclone(), Box::new(Constant::new(j as i32))));
+                    space
+                        .cstore
+                        .alloc(equivalence(Box::new(b.clone()), bin_eq_j));
+                    coefs.push(sizes[i]);
+                    vars.push(Box::new(b) as Var<VStore>);
+                }
+                coefs.push(-1);
+                vars.push(load.bclone());
+                space.cstore.alloc(Box::new(linear_eq(coefs, vars, 0)));
+            }
+        } else {
+            space
+                .cstore
+                .alloc(Box::new(BinPacking::new(loads, bins, sizes.to_vec())));
+        }
+        count_assignments(space, sizes.len())
+    }
+
+    /// Number of assignments of the items to the bins such that every load is in its domain.
+    fn brute_force(loads: &[(i32, i32)], sizes: &[i32]) -> usize {
+        let m = loads.len();
+        let n = sizes.len();
+        let mut solutions = 0;
+        for assignment in 0..m.pow(n as u32) {
+            let mut load = vec![0; m];
+            let mut a = assignment;
+            for size in sizes {
+                load[a % m] += size;
+                a /= m;
+            }
+            if load
+                .iter()
+                .zip(loads)
+                .all(|(l, &(lb, ub))| lb <= *l && *l <= ub)
+            {
+                solutions += 1;
+            }
+        }
+        solutions
+    }
+
+    #[test]
+    fn bin_packing_solutions_test() {
+        let instances = vec![
+            (vec![(0, 5), (0, 5), (0, 5)], vec![4, 3, 2, 2, 1]),
+            (vec![(3, 6), (2, 4), (0, 7)], vec![5, 3, 3, 2, 1]),
+            (vec![(4, 4), (6, 9)], vec![4, 3, 3, 2, 1, 1]),
+        ];
+        for (loads, sizes) in instances {
+            let expected = brute_force(&loads, &sizes);
+            assert_eq!(count_bin_packing(&loads, &sizes, false), expected);
+            assert_eq!(count_bin_packing(&loads, &sizes, true), expected);
+        }
+    }
+}
@@ -0,0 +1,523 @@
+// Copyright 2018 Pierre Talbot (IRCAM)
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+
+//     http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! The `Circuit` constraint holds if the successor variables `succ` form a Hamiltonian cycle, `succ[i]` is the node following `i` in the cycle and the nodes are numbered from 0.
+//! The subcircuit variant allows nodes to stay outside of the cycle, these nodes are their own successor (`succ[i] == i`).
+//! The filtering combines the domain consistency of `Distinct` on the successors with the elimination of subtours on the chains of assigned successors, as described in `Solving small TSPs with constraints`, Caseau and Laburthe, 1997.
+//! The pruning on the strongly connected components of the successor graph is optional: an edge between two components cannot be part of a cycle and the graph of a circuit must be strongly connected.
+
+use concept::*;
+use gcollections::*;
+use kernel::*;
+use logic::*;
+use model::*;
+use propagation::events::*;
+use propagation::*;
+use propagators::distinct::*;
+use propagators::values::naturals;
+use trilean::SKleene;
+use trilean::SKleene::*;
+
+#[derive(Debug)]
+pub struct Circuit<VStore> {
+    succ: Vec<Var<VStore>>,
+    distinct: Distinct<VStore>,
+    /// The nodes can be their own successor and stay outside of the cycle.
+    subcircuit: bool,
+    scc_pruning: bool,
+    /// The negation of the constraint: the successors do not form a (sub)circuit.
+    negated: bool,
+}
+
+impl<VStore> Circuit<VStore>
+where
+    VStore: Collection,
+{
+    /// Precondition: `succ.len() > 0`.
+    pub fn new(succ: Vec<Var<VStore>>) -> Self {
+        Circuit::with_self_loops(succ, false)
+    }
+
+    /// Precondition: `succ.len() > 0`.
+    pub fn subcircuit(succ: Vec<Var<VStore>>) -> Self {
+        Circuit::with_self_loops(succ, true)
+    }
+
+    fn with_self_loops(succ: Vec<Var<VStore>>, subcircuit: bool) -> Self {
+        assert!(
+            !succ.is_empty(),
+            "Successor array in `Circuit` must be non-empty."
+        );
+        let distinct = Distinct::with_consistency(
+            succ.iter().map(|s| s.bclone()).collect(),
+            DistinctConsistency::Domain,
+        );
+        Circuit {
+            succ,
+            distinct,
+            subcircuit,
+            scc_pruning: false,
+            negated: false,
+        }
+    }
+
+    /// Enables the pruning on the strongly connected components of the successor graph.
+    pub fn with_scc_pruning(mut self) -> Self {
+        self.scc_pruning = true;
+        self
+    }
+}
+
+impl<VStore> Clone for Circuit<VStore>
+where
+    VStore: Collection,
+{
+    fn clone(&self) -> Self {
+        Circuit {
+            succ: self.succ.iter().map(|s| s.bclone()).collect(),
+            distinct: self.distinct.clone(),
+            subcircuit: self.subcircuit,
+            scc_pruning: self.scc_pruning,
+            negated: self.negated,
+        }
+    }
+}
+
+impl<VStore> DisplayStateful<Model> for Circuit<VStore> {
+    fn display(&self, model: &Model) {
+        if self.negated {
+            print!("not ");
+        }
+        let name = if self.subcircuit {
+            "subcircuit"
+        } else {
+            "circuit"
+        };
+        model.display_global(name, &self.succ);
+    }
+}
+
+impl<VStore, Domain, Bound> NotFormula<VStore> for Circuit<VStore>
+where
+    VStore: VStoreConcept<Item = Domain> + 'static,
+    Domain: IntDomain<Item = Bound> + 'static,
+    Bound: IntBound + 'static,
+{
+    fn not(&self) -> Formula<VStore> {
+        let mut not = self.clone();
+        not.negated = !self.negated;
+        Box::new(not)
+    }
+}
+
+/// The successor of each node if it is assigned to a node.
+fn assigned<Domain, Bound>(doms: &[Domain], nodes: &[Bound]) -> Vec<Option<usize>>
+where
+    Domain: IntDomain<Item = Bound>,
+    Bound: IntBound,
+{
+    doms.iter()
+        .map(|d| {
+            if d.is_singleton() {
+                nodes.binary_search(&d.lower()).ok()
+            } else {
+                None
+            }
+        })
+        .collect()
+}
+
+impl<VStore, Domain, Bound> Circuit<VStore>
+where
+    VStore: Collection<Item = Domain>,
+    Domain: IntDomain<Item = Bound>,
+    Bound: IntBound,
+{
+    fn read_all(&self, vstore: &VStore) -> Vec<Domain> {
+        self.succ.iter().map(|s| s.read(vstore)).collect()
+    }
+
+    /// The nodes `0..n`.
+    fn nodes(&self) -> Vec<Bound> {
+        naturals(self.succ.len() - 1)
+    }
+
+    /// `true` if the successors `succ` form a single cycle, the other nodes must be their own successor in a subcircuit.
+    fn is_circuit(&self, succ: &[usize]) -> bool {
+        let n = succ.len();
+        let start = match (0..n).find(|&i| succ[i] != i) {
+            Some(start) => start,
+            None => return self.subcircuit || n == 1,
+        };
+        let mut in_cycle = vec![false; n];
+        let mut i = start;
+        while !in_cycle[i] {
+            in_cycle[i] = true;
+            i = succ[i];
+        }
+        i == start && (0..n).all(|j| in_cycle[j] || (self.subcircuit && succ[j] == j))
+    }
+
+    /// `Some(b)` if every successor is assigned, `b` is `true` if they form a (sub)circuit.
+    fn assigned_circuit(&self, vstore: &VStore) -> Option<bool> {
+        let succ: Option<Vec<_>> = assigned(&self.read_all(vstore), &self.nodes())
+            .into_iter()
+            .collect();
+        succ.map(|succ| self.is_circuit(&succ))
+    }
+
+    /// The successors are nodes, and a node cannot be its own successor in a circuit of more than one node.
+    fn restrict_range(&mut self, vstore: &mut VStore, nodes: &[Bound]) -> bool {
+        let n = nodes.len();
+        for (i, s) in self.succ.iter_mut().enumerate() {
+            let mut dom = s
+                .read(vstore)
+                .shrink_left(nodes[0].clone())
+                .shrink_right(nodes[n - 1].clone());
+            if !self.subcircuit && n > 1 {
+                dom = dom.difference(&nodes[i]);
+            }
+            if !s.update(vstore, dom) {
+                return false;
+            }
+        }
+        true
+    }
+
+    /// The first node of a chain of assigned successors is removed from the successors of the last node, unless closing the chain forms a (sub)circuit.
+    /// In a subcircuit, the nodes outside of a cycle of assigned successors are their own successor.
+    fn eliminate_subtours(&mut self, vstore: &mut VStore, nodes: &[Bound]) -> bool {
+        let n = nodes.len();
+        let doms = self.read_all(vstore);
+        let succ = assigned(&doms, nodes);
+        let mut has_pred = vec![false; n];
+        for &j in succ.iter().flatten() {
+            has_pred[j] = true;
+        }
+        // `owner[i]` is the first node of the chain or cycle containing `i`.
+        let mut owner = vec![None; n];
+        for first in (0..n).filter(|&i| !has_pred[i]) {
+            let mut last = first;
+            let mut len = 1;
+            owner[first] = Some(first);
+            while let Some(next) = succ[last] {
+                if owner[next].is_some() {
+                    // Two nodes have the same successor.
+                    return false;
+                }
+                owner[next] = Some(first);
+                last = next;
+                len += 1;
+            }
+            let closable = if self.subcircuit {
+                (0..n).all(|j| owner[j] == Some(first) || doms[j].contains(&nodes[j]))
+            } else {
+                len == n
+            };
+            if len > 1 && !closable {
+                let dom = self.succ[last].read(vstore).difference(&nodes[first]);
+                if !self.succ[last].update(vstore, dom) {
+                    return false;
+                }
+            }
+        }
+        // The remaining nodes are in cycles of assigned successors.
+        for start in 0..n {
+            if owner[start].is_some() || succ[start] == Some(start) {
+                continue;
+            }
+            let mut i = start;
+            let mut len = 0;
+            while owner[i].is_none() {
+                owner[i] = Some(start);
+                len += 1;
+                match succ[i] {
+                    Some(next) => i = next,
+                    None => return false,
+                }
+            }
+            if !self.subcircuit {
+                if len < n {
+                    return false;
+                }
+            } else {
+                for j in (0..n).filter(|&j| owner[j] != Some(start)) {
+                    let dom = self.succ[j]
+                        .read(vstore)
+                        .intersection(&Domain::singleton(nodes[j].clone()));
+                    if !self.succ[j].update(vstore, dom) {
+                        return false;
+                    }
+                }
+            }
+        }
+        true
+    }
+
+    /// The edges between two strongly connected components of the successor graph are removed.
+    /// In a subcircuit, the nodes that cannot be their own successor must be in the same component and the nodes outside of this component are their own successor.
+    fn prune_scc(&mut self, vstore: &mut VStore, nodes: &[Bound]) -> bool {
+        let n = nodes.len();
+        let doms = self.read_all(vstore);
+        let graph: Vec<Vec<usize>> = doms
+            .iter()
+            .enumerate()
+            .map(|(i, d)| {
+                (0..n)
+                    .filter(|&j| j != i && d.contains(&nodes[j]))
+                    .collect()
+            })
+            .collect();
+        let scc = strongly_connected_components(&graph);
+        if !self.subcircuit {
+            return scc.iter().all(|&c| c == scc[0]);
+        }
+        let mandatory: Vec<_> = (0..n).filter(|&i| !doms[i].contains(&nodes[i])).collect();
+        let component = mandatory.first().map(|&i| scc[i]);
+        if mandatory.iter().any(|&i| Some(scc[i]) != component) {
+            return false;
+        }
+        for (i, mut dom) in doms.into_iter().enumerate() {
+            for &j in &graph[i] {
+                let keep = match component {
+                    Some(c) => scc[i] == c && scc[j] == c,
+                    None => scc[i] == scc[j],
+                };
+                if !keep {
+                    dom = dom.difference(&nodes[j]);
+                }
+            }
+            if !self.succ[i].update(vstore, dom) {
+                return false;
+            }
+        }
+        true
+    }
+}
+
+impl<VStore, Domain, Bound> Subsumption<VStore> for Circuit<VStore>
+where
+    VStore: Collection<Item = Domain>,
+    Domain: IntDomain<Item = Bound>,
+    Bound: IntBound,
+{
+    fn is_subsumed(&self, vstore: &VStore) -> SKleene {
+        // False: two successors are equal or the assigned successors do not form a (sub)circuit.
+        // True: the assigned successors form a (sub)circuit.
+        // Unknown: Everything else.
+        let status = match self.assigned_circuit(vstore) {
+            Some(true) => True,
+            Some(false) => False,
+            None if self.distinct.is_subsumed(vstore) == False => False,
+            None => Unknown,
+        };
+        if self.negated {
+            !status
+        } else {
+            status
+        }
+    }
+}
+
+impl<VStore, Domain, Bound> Propagator<VStore> for Circuit<VStore>
+where
+    VStore: Collection<Item = Domain>,
+    Domain: IntDomain<Item = Bound>,
+    Bound: IntBound,
+{
+    fn propagate(&mut self, vstore: &mut VStore) -> bool {
+        if self.negated {
+            return self.assigned_circuit(vstore) != Some(true);
+        }
+        let nodes = self.nodes();
+        self.restrict_range(vstore, &nodes)
+            && self.distinct.propagate(vstore)
+            && self.eliminate_subtours(vstore, &nodes)
+            && (!self.scc_pruning || self.prune_scc(vstore, &nodes))
+    }
+
+    fn cost(&self) -> PropagatorCost {
+        PropagatorCost::Quadratic
+    }
+}
+
+impl<VStore> PropagatorDependencies<FDEvent> for Circuit<VStore> {
+    fn dependencies(&self) -> Vec<(usize, FDEvent)> {
+        let mut deps: Vec<_> = self
+            .succ
+            .iter()
+            .flat_map(|s| s.dependencies(FDEvent::Inner))
+            .collect();
+        deps.sort();
+        deps.dedup();
+        deps
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use gcollections::ops::*;
+    use interval::interval_set::*;
+    use search::test::*;
+    use variable::ops::*;
+    use variable::VStoreSet;
+
+    type Doms = Vec<Vec<(i32, i32)>>;
+
+    fn circuit_space(doms: Doms, subcircuit: bool, scc_pruning: bool) -> FDSpace {
+        let mut space = FDSpace::empty();
+        let succ: Vec<_> = doms
+            .into_iter()
+            .map(|d| Box::new(space.vstore.alloc(d.to_interval_set())) as Var<VStore>)
+            .collect();
+        let circuit = if subcircuit {
+            Circuit::subcircuit(succ)
+        } else {
+            Circuit::new(succ)
+        };
+        let circuit = if scc_pruning {
+            circuit.with_scc_pruning()
+        } else {
+            circuit
+        };
+        space.cstore.alloc(Box::new(circuit));
+        space
+    }
+
+    fn circuit_test_one(
+        test_num: u32,
+        (doms, subcircuit, scc_pruning): (Doms, bool, bool),
+        expected_status: SKleene,
+        expected: Doms,
+    ) {
+        let mut space = circuit_space(doms, subcircuit, scc_pruning);
+        let status = space.cstore.consistency(&mut space.vstore);
+        assert_eq!(status, expected_status, "Test {}: wrong status.", test_num);
+        if status != False {
+            let doms: Vec<_> = space.vstore.iter().cloned().collect();
+            let expected: Vec<_> = expected.into_iter().map(|d| d.to_interval_set()).collect();
+            assert_eq!(doms, expected, "Test {}: wrong domains.", test_num);
+        }
+    }
+
+    #[test]
+    fn circuit_test() {
+        let d0 = vec![(0, 0)];
+        let d1 = vec![(1, 1)];
+        let d2 = vec![(2, 2)];
+        let d3 = vec![(3, 3)];
+        let d4 = vec![(4, 4)];
+        let d02 = vec![(0, 0), (2, 2)];
+        let d0_2 = vec![(0, 2)];
+
+        // 0 -> 1 cannot be closed by 1 -> 0.
+        circuit_test_one(
+            1,
+            (vec![d1.clone(), d0_2.clone(), d0_2.clone()], false, false),
+            True,
+            vec![d1.clone(), d2.clone(), d0.clone()],
+        );
+        circuit_test_one(
+            2,
+            (vec![d1.clone(), d0.clone(), d0_2.clone()], false, false),
+            False,
+            vec![],
+        );
+        // The subtour 0 -> 1 -> 0 is a subcircuit, 2 is outside of the cycle.
+        circuit_test_one(
+            3,
+            (vec![d1.clone(), d0.clone(), d0_2.clone()], true, false),
+            True,
+            vec![d1.clone(), d0.clone(), d2.clone()],
+        );
+        circuit_test_one(
+            4,
+            (vec![d1.clone(), d02.clone(), d0_2.clone()], true, false),
+            Unknown,
+            vec![d1.clone(), d02.clone(), d02.clone()],
+        );
+
+        // Two strongly connected components {0, 1, 2} and {3, 4, 5}.
+        let components = vec![
+            vec![(1, 2)],
+            d02.clone(),
+            vec![(0, 1)],
+            vec![(4, 5)],
+            vec![(3, 3), (5, 5)],
+            vec![(3, 4)],
+        ];
+        circuit_test_one(
+            5,
+            (components.clone(), false, false),
+            Unknown,
+            components.clone(),
+        );
+        circuit_test_one(6, (components, false, true), False, vec![]);
+
+        // The nodes 0, 1 and 2 must be in the subcircuit, 3 and 4 cannot reach them.
+        let mandatory = vec![
+            vec![(1, 2)],
+            d02.clone(),
+            vec![(0, 1)],
+            vec![(3, 4)],
+            vec![(3, 4)],
+        ];
+        circuit_test_one(
+            7,
+            (mandatory.clone(), true, false),
+            Unknown,
+            mandatory.clone(),
+        );
+        let mut pruned = mandatory.clone();
+        pruned[3] = d3;
+        pruned[4] = d4;
+        circuit_test_one(8, (mandatory, true, true), Unknown, pruned);
+    }
+
+    #[test]
+    fn negated_circuit_test() {
+        // 0 -> 1 -> 2 -> 0 is a circuit, 0 -> 1 -> 2 -> 1 is not.
+        for &(last, expected) in &[(0, False), (1, True)] {
+            let mut vstore = VStoreSet::empty();
+            let mut succ: Vec<_> = (0..3)
+                .map(|_| Box::new(vstore.alloc((0, 2).to_interval_set())) as Var<VStoreSet>)
+                .collect();
+            let mut not_circuit = Circuit::new(succ.iter().map(|s| s.bclone()).collect()).not();
+            assert!(succ[0].update(&mut vstore, (1, 1).to_interval_set()));
+            assert!(succ[1].update(&mut vstore, (2, 2).to_interval_set()));
+            assert_eq!(not_circuit.is_subsumed(&vstore), Unknown);
+            assert!(not_circuit.propagate(&mut vstore));
+            assert!(succ[2].update(&mut vstore, (last, last).to_interval_set()));
+            assert_eq!(not_circuit.is_subsumed(&vstore), expected);
+            assert_eq!(not_circuit.propagate(&mut vstore), expected == True);
+        }
+    }
+
+    fn count_circuits(n: i32, subcircuit: bool, scc_pruning: bool) -> usize {
+        let space = circuit_space(vec![vec![(0, n - 1)]; n as usize], subcircuit, scc_pruning);
+        count_assignments(space, n as usize)
+    }
+
+    #[test]
+    fn circuit_solutions_test() {
+        for &scc_pruning in &[false, true] {
+            // (n-1)! Hamiltonian cycles.
+            assert_eq!(count_circuits(4, false, scc_pruning), 6);
+            assert_eq!(count_circuits(5, false, scc_pruning), 24);
+            // Sum over the cycles of length k = 0 or 2..4: C(4,k)*(k-1)!, the empty cycle counts once.
+            assert_eq!(count_circuits(4, true, scc_pruning), 1 + 6 + 8 + 6);
+        }
+    }
+}
@@ -0,0 +1,403 @@
+// Copyright 2018 Pierre Talbot (IRCAM)
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+
+//     http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! The `Clause` constraint is the disjunction of literals over 0/1 variables, a literal is a variable (positive) or its negation.
+//! The propagation watches two literals that are not false, as in SAT solvers: as long as both are not false, the propagator does nothing, otherwise a new literal to watch is searched and the last non-false literal is set to true if there is none.
+//! The propagator only subscribes to the watched literals and the constraint store moves its subscriptions with the watches (see `PropagatorDependencies::has_dynamic_dependencies`), so it is woken up only when a watched literal is assigned.
+//! The watches stay valid when backtracking (the literals can only become unassigned), so they do not need to be restored and the propagator is not stateful.
+
+use concept::*;
+use gcollections::*;
+use kernel::*;
+use logic::*;
+use model::*;
+use propagation::events::*;
+use propagation::*;
+use propagators::pseudo_boolean::*;
+use trilean::SKleene;
+use trilean::SKleene::*;
+
+#[derive(Debug)]
+pub struct Clause<VStore> {
+    vars: Vec<Var<VStore>>,
+    /// `positive[i]` is `false` if the literal `i` is the negation of `vars[i]`.
+    positive: Vec<bool>,
+    /// The indices of the two watched literals.
+    watches: [usize; 2],
+}
+
+impl<VStore> Clause<VStore> {
+    /// The clause `positives[0] \/ ... \/ not negatives[0] \/ ...`.
+    /// Precondition: the variables are 0/1 variables.
+    pub fn new(positives: Vec<Var<VStore>>, negatives: Vec<Var<VStore>>) -> Self {
+        let mut positive = vec![true; positives.len()];
+        positive.extend(vec![false; negatives.len()]);
+        let mut vars = positives;
+        vars.extend(negatives);
+        let watches = [0, if vars.len() > 1 { 1 } else { 0 }];
+        Clause {
+            vars,
+            positive,
+            watches,
+        }
+    }
+}
+
+impl<VStore> Clone for Clause<VStore>
+where
+    VStore: Collection,
+{
+    fn clone(&self) -> Self {
+        Clause {
+            vars: self.vars.iter().map(|v| v.bclone()).collect(),
+            positive: self.positive.clone(),
+            watches: self.watches,
+        }
+    }
+}
+
+impl<VStore> DisplayStateful<Model> for Clause<VStore> {
+    fn display(&self, model: &Model) {
+        if self.vars.is_empty() {
+            print!("false");
+        }
+        for (i, var) in self.vars.iter().enumerate() {
+            if i > 0 {
+                print!(" \\/ ");
+            }
+            if !self.positive[i] {
+                print!("not ");
+            }
+            var.display(model);
+        }
+    }
+}
+
+impl<VStore, Domain, Bound> NotFormula<VStore> for Clause<VStore>
+where
+    VStore: VStoreConcept<Item = Domain> + 'static,
+    Domain: IntDomain<Item = Bound> + 'static,
+    Bound: IntBound + 'static,
+{
+    /// All the literals are false: the sum of the negated literals is equal to their number.
+    fn not(&self) -> Formula<VStore> {
+        let n = self.vars.len();
+        Box::new(PseudoBoolean::with_literals(
+            vec![Bound::one(); n],
+            self.vars.iter().map(|v| v.bclone()).collect(),
+            self.positive.iter().map(|p| !p).collect(),
+            sum_of_ones(n),
+        ))
+    }
+}
+
+fn sum_of_ones<Bound: IntBound>(n: usize) -> Bound {
+    (0..n).fold(Bound::zero(), |acc, _| acc + Bound::one())
+}
+
+impl<VStore, Domain, Bound> Clause<VStore>
+where
+    VStore: Collection<Item = Domain>,
+    Domain: IntDomain<Item = Bound>,
+    Bound: IntBound,
+{
+    fn value(&self, vstore: &VStore, i: usize) -> SKleene {
+        literal_value(&self.vars[i].read(vstore), self.positive[i])
+    }
+
+    /// A literal that is not false and not watched, searched from the watched literal `from`.
+    fn find_watch(&self, vstore: &VStore, from: usize) -> Option<usize> {
+        let n = self.vars.len();
+        (1..n)
+            .map(|k| (from + k) % n)
+            .find(|&i| !self.watches.contains(&i) && self.value(vstore, i) != False)
+    }
+}
+
+impl<VStore, Domain, Bound> Subsumption<VStore> for Clause<VStore>
+where
+    VStore: Collection<Item = Domain>,
+    Domain: IntDomain<Item = Bound>,
+    Bound: IntBound,
+{
+    fn is_subsumed(&self, vstore: &VStore) -> SKleene {
+        // Only the watched literals are checked, unless one of them is false.
+        let watched: Vec<_> = self
+            .watches
+            .iter()
+            .map(|&w| self.value(vstore, w))
+            .collect();
+        if self.vars.is_empty() {
+            False
+        } else if watched.contains(&True) {
+            True
+        } else if !watched.contains(&False) {
+            Unknown
+        } else {
+            let values: Vec<_> = (0..self.vars.len())
+                .map(|i| self.value(vstore, i))
+                .collect();
+            if values.contains(&True) {
+                True
+            } else if values.contains(&Unknown) {
+                Unknown
+            } else {
+                False
+            }
+        }
+    }
+}
+
+impl<VStore, Domain, Bound> Propagator<VStore> for Clause<VStore>
+where
+    VStore: Collection<Item = Domain>,
+    Domain: IntDomain<Item = Bound>,
+    Bound: IntBound,
+{
+    fn propagate(&mut self, vstore: &mut VStore) -> bool {
+        match self.vars.len() {
+            0 => return false,
+            1 => return literal_update(vstore, &mut self.vars[0], self.positive[0]),
+            _ => (),
+        }
+        for k in 0..2 {
+            let w = self.watches[k];
+            if self.value(vstore, w) == False {
+                match self.find_watch(vstore, w) {
+                    Some(i) => self.watches[k] = i,
+                    None => {
+                        // All the other literals are false: the other watched literal must be true.
+                        let other = self.watches[1 - k];
+                        return match self.value(vstore, other) {
+                            False => false,
+                            True => true,
+                            Unknown => {
+                                literal_update(vstore, &mut self.vars[other], self.positive[other])
+                            }
+                        };
+                    }
+                }
+            }
+        }
+        true
+    }
+}
+
+impl<VStore> PropagatorDependencies<FDEvent> for Clause<VStore> {
+    /// Only the watched literals are subscribed.
+    fn dependencies(&self) -> Vec<(usize, FDEvent)> {
+        let mut deps: Vec<_> = self
+            .watches
+            .iter()
+            .filter(|&&w| w < self.vars.len())
+            .flat_map(|&w| self.vars[w].dependencies(FDEvent::Assignment))
+            .collect();
+        deps.sort();
+        deps.dedup();
+        deps
+    }
+
+    fn has_dynamic_dependencies(&self) -> bool {
+        true
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use gcollections::ops::*;
+    use interval::interval::*;
+    use interval::interval_set::*;
+    use propagation::events::FDEvent::*;
+    use propagators::test::*;
+    use search::test::*;
+    use variable::VStoreFD;
+
+    #[test]
+    fn clause_test() {
+        let zero = (0, 0).to_interval();
+        let one = (1, 1).to_interval();
+        let dom0_1 = (0, 1).to_interval();
+
+        // x0 \/ x1 \/ not x2
+        clause_test_one(
+            1,
+            vec![dom0_1, dom0_1, dom0_1],
+            Unknown,
+            Unknown,
+            vec![],
+            true,
+        );
+        clause_test_one(
+            2,
+            vec![zero, dom0_1, dom0_1],
+            Unknown,
+            Unknown,
+            vec![],
+            true,
+        );
+        clause_test_one(
+            3,
+            vec![zero, zero, dom0_1],
+            Unknown,
+            True,
+            vec![(2, Assignment)],
+            true,
+        );
+        clause_test_one(
+            4,
+            vec![zero, dom0_1, one],
+            Unknown,
+            True,
+            vec![(1, Assignment)],
+            true,
+        );
+        clause_test_one(5, vec![zero, zero, one], False, False, vec![], false);
+        clause_test_one(6, vec![one, dom0_1, one], True, True, vec![], true);
+        clause_test_one(7, vec![dom0_1, zero, zero], True, True, vec![], true);
+    }
+
+    fn clause_test_one(
+        test_num: u32,
+        doms: Vec<Interval<i32>>,
+        before: SKleene,
+        after: SKleene,
+        delta_expected: Vec<(usize, FDEvent)>,
+        propagate_success: bool,
+    ) {
+        nary_propagator_test(
+            test_num,
+            |mut vars: Vec<FDVar>| {
+                let negatives = vars.split_off(2);
+                Clause::new(vars, negatives)
+            },
+            doms,
+            before,
+            after,
+            delta_expected,
+            propagate_success,
+        );
+    }
+
+    #[test]
+    fn unit_clause_test() {
+        let dom0_1 = (0, 1).to_interval();
+        nary_propagator_test(
+            1,
+            |vars: Vec<FDVar>| Clause::new(vars, vec![]),
+            vec![dom0_1],
+            Unknown,
+            True,
+            vec![(0, Assignment)],
+            true,
+        );
+        nary_propagator_test(
+            2,
+            |vars: Vec<FDVar>| Clause::new(vec![], vars),
+            vec![(1, 1).to_interval()],
+            False,
+            False,
+            vec![],
+            false,
+        );
+    }
+
+    #[test]
+    fn watched_dependencies_test() {
+        let mut vstore = VStoreFD::empty();
+        let doms = vec![(0, 0), (0, 1), (0, 1), (0, 1)];
+        let vars: Vec<_> = doms
+            .into_iter()
+            .map(|d| Box::new(vstore.alloc(d.to_interval())) as FDVar)
+            .collect();
+        let mut clause = Clause::new(vars, vec![]);
+        assert_eq!(
+            clause.dependencies(),
+            vec![(0, Assignment), (1, Assignment)]
+        );
+        // The false literal `x0` is replaced by `x2`.
+        assert!(clause.propagate(&mut vstore));
+        assert_eq!(
+            clause.dependencies(),
+            vec![(1, Assignment), (2, Assignment)]
+        );
+    }
+
+    /// The clauses are given by the indices of their positive and negative variables.
+    type Clauses = Vec<(Vec<usize>, Vec<usize>)>;
+
+    fn count_clauses(n: usize, clauses: &Clauses, negated: bool) -> usize {
+        let mut space = FDSpace::empty();
+        let vars: Vec<_> = (0..n)
+            .map(|_| Box::new(space.vstore.alloc((0, 1).to_interval_set())) as Var<VStore>)
+            .collect();
+        for (positives, negatives) in clauses {
+            let lits = |idx: &Vec<usize>| idx.iter().map(|&i| vars[i].bclone()).collect();
+            let clause = Clause::new(lits(positives), lits(negatives));
+            if negated {
+                space.cstore.alloc(clause.not());
+            } else {
+                space.cstore.alloc(Box::new(clause));
+            }
+        }
+        count_assignments(space, n)
+    }
+
+    fn brute_force(n: usize, clauses: &Clauses, negated: bool) -> usize {
+        (0..(1 << n))
+            .filter(|&a: &usize| {
+                clauses.iter().all(|(positives, negatives)| {
+                    let sat = positives.iter().any(|&i| a & (1 << i) != 0)
+                        || negatives.iter().any(|&i| a & (1 << i) == 0);
+                    sat != negated
+                })
+            })
+            .count()
+    }
+
+    #[test]
+    fn clause_solutions_test() {
+        let instances: Vec<(usize, Clauses)> = vec![
+            (3, vec![(vec![0, 1, 2], vec![])]),
+            (
+                4,
+                vec![
+                    (vec![0, 1], vec![2]),
+                    (vec![3], vec![0]),
+                    (vec![1, 2, 3], vec![0]),
+                    (vec![], vec![1, 3]),
+                ],
+            ),
+            (
+                5,
+                vec![
+                    (vec![0, 1, 2, 3, 4], vec![]),
+                    (vec![], vec![0, 1]),
+                    (vec![2], vec![3, 4]),
+                    (vec![4], vec![]),
+                ],
+            ),
+        ];
+        for (n, clauses) in instances {
+            assert_eq!(
+                count_clauses(n, &clauses, false),
+                brute_force(n, &clauses, false)
+            );
+        }
+        // The negation of a single clause has one solution: all its literals are false.
+        let clauses = vec![(vec![0, 1], vec![2])];
+        assert_eq!(count_clauses(3, &clauses, true), 1);
+        assert_eq!(brute_force(3, &clauses, true), 1);
+    }
+}
@@ -0,0 +1,522 @@
+// Copyright 2018 Pierre Talbot (IRCAM)
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+
+//     http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! The linear constraint `a1*x1 + ... + an*xn op c` where `op` is one of `=`, `<=`, `>=` or `!=`.
+//! The bounds reasoning is done in a single pass over the terms by `WeightedSum::update`.
+
+use concept::*;
+use gcollections::*;
+use kernel::*;
+use logic::*;
+use model::*;
+use propagation::events::*;
+use propagation::*;
+use std::fmt::{Debug, Formatter, Result};
+use term::ops::*;
+use term::*;
+use trilean::SKleene;
+use trilean::SKleene::*;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LinearOp {
+    Eq,
+    Leq,
+    Geq,
+    Neq,
+}
+
+pub struct Linear<VStore>
+where
+    VStore: VStoreConcept,
+    VStore::Item: Collection,
+{
+    sum: WeightedSum<VStore>,
+    op: LinearOp,
+    c: <VStore::Item as Collection>::Item,
+}
+
+impl<VStore, Domain, Bound> Linear<VStore>
+where
+    VStore: VStoreConcept<Item = Domain>,
+    Domain: Collection<Item = Bound>,
+{
+    pub fn new(coefs: Vec<Bound>, vars: Vec<Var<VStore>>, op: LinearOp, c: Bound) -> Self {
+        Linear {
+            sum: WeightedSum::new(coefs, vars),
+            op,
+            c,
+        }
+    }
+}
+
+impl<VStore, Domain, Bound> Debug for Linear<VStore>
+where
+    VStore: VStoreConcept<Item = Domain>,
+    Domain: Collection<Item = Bound>,
+    Bound: Debug,
+{
+    fn fmt(&self, fmt: &mut Formatter) -> Result {
+        fmt.debug_struct("Linear")
+            .field("sum", &self.sum)
+            .field("op", &self.op)
+            .field("c", &self.c)
+            .finish()
+    }
+}
+
+impl<VStore, Domain, Bound> Clone for Linear<VStore>
+where
+    VStore: VStoreConcept<Item = Domain>,
+    Domain: Collection<Item = Bound>,
+    Bound: Clone,
+{
+    fn clone(&self) -> Self {
+        Linear {
+            sum: self.sum.clone(),
+            op: self.op,
+            c: self.c.clone(),
+        }
+    }
+}
+
+impl<VStore, Domain, Bound> DisplayStateful<Model> for Linear<VStore>
+where
+    VStore: VStoreConcept<Item = Domain>,
+    Domain: Collection<Item = Bound>,
+    Bound: Debug,
+{
+    fn display(&self, model: &Model) {
+        self.sum.display(model);
+        let op = match self.op {
+            LinearOp::Eq => "=",
+            LinearOp::Leq => "<=",
+            LinearOp::Geq => ">=",
+            LinearOp::Neq => "!=",
+        };
+        print!(" {} {:?}", op, self.c);
+    }
+}
+
+impl<VStore, Domain, Bound> NotFormula<VStore> for Linear<VStore>
+where
+    VStore: VStoreConcept<Item = Domain> + 'static,
+    Domain: IntDomain<Item = Bound> + 'static,
+    Bound: IntBound + 'static,
+{
+    fn not(&self) -> Formula<VStore> {
+        let (op, c) = match self.op {
+            LinearOp::Eq => (LinearOp::Neq, self.c.clone()),
+            LinearOp::Neq => (LinearOp::Eq, self.c.clone()),
+            LinearOp::Leq => (LinearOp::Geq, self.c.clone() + Bound::one()),
+            LinearOp::Geq => (LinearOp::Leq, self.c.clone() - Bound::one()),
+        };
+        Box::new(Linear {
+            sum: self.sum.clone(),
+            op,
+            c,
+        })
+    }
+}
+
+impl<VStore, Domain, Bound> Subsumption<VStore> for Linear<VStore>
+where
+    VStore: VStoreConcept<Item = Domain>,
+    Domain: IntDomain<Item = Bound>,
+    Bound: IntBound,
+{
+    fn is_subsumed(&self, store: &VStore) -> SKleene {
+        self.entailment(&self.sum.read(store))
+    }
+}
+
+impl<VStore, Domain, Bound> Linear<VStore>
+where
+    VStore: VStoreConcept<Item = Domain>,
+    Domain: IntDomain<Item = Bound>,
+    Bound: IntBound,
+{
+    fn entailment(&self, sum: &Domain) -> SKleene {
+        let c = self.c.clone();
+        let assigned_to_c = sum.lower() == c && sum.upper() == c;
+        let outside = c < sum.lower() || c > sum.upper();
+        match self.op {
+            LinearOp::Eq if outside => False,
+            LinearOp::Eq if assigned_to_c => True,
+            LinearOp::Neq if outside => True,
+            LinearOp::Neq if assigned_to_c => False,
+            LinearOp::Leq if sum.upper() <= c => True,
+            LinearOp::Leq if sum.lower() > c => False,
+            LinearOp::Geq if sum.lower() >= c => True,
+            LinearOp::Geq if sum.upper() < c => False,
+            _ => Unknown,
+        }
+    }
+
+    fn propagate_sum(&mut self, store: &mut VStore, sum: Domain) -> bool {
+        let c = self.c.clone();
+        match self.op {
+            LinearOp::Eq => self.sum.update(store, Domain::singleton(c)),
+            LinearOp::Leq => self.sum.update(store, sum.shrink_right(c)),
+            LinearOp::Geq => self.sum.update(store, sum.shrink_left(c)),
+            LinearOp::Neq => self.propagate_neq(store),
+        }
+    }
+
+    /// If all the variables but one are assigned, the value making the sum equal to `c` is removed from the last variable.
+    fn propagate_neq(&mut self, store: &mut VStore) -> bool {
+        let mut rest = self.c.clone();
+        let mut unassigned = None;
+        for (i, (a, x)) in self.sum.coefs.iter().zip(self.sum.vars.iter()).enumerate() {
+            if a.is_zero() {
+                continue;
+            }
+            let x = x.read(store);
+            if x.is_singleton() {
+                rest = rest - a.clone() * x.lower();
+            } else if unassigned.is_none() {
+                unassigned = Some(i);
+            } else {
+                return true;
+            }
+        }
+        match unassigned {
+            None => !rest.is_zero(),
+            Some(i) => {
+                let a = self.sum.coefs[i].clone();
+                if rest.mod_floor(&a).is_zero() {
+                    let x = self.sum.vars[i].read(store);
+                    let x = x.difference(&rest.div_floor(&a));
+                    self.sum.vars[i].update(store, x)
+                } else {
+                    true
+                }
+            }
+        }
+    }
+}
+
+impl<VStore, Domain, Bound> Propagator<VStore> for Linear<VStore>
+where
+    VStore: VStoreConcept<Item = Domain>,
+    Domain: IntDomain<Item = Bound>,
+    Bound: IntBound,
+{
+    fn propagate(&mut self, store: &mut VStore) -> bool {
+        let sum = self.sum.read(store);
+        self.propagate_sum(store, sum)
+    }
+
+    /// The sum is read once for the subsumption and the propagation.
+    fn propagate_status(&mut self, store: &mut VStore) -> PropagatorStatus {
+        let sum = self.sum.read(store);
+        match self.entailment(&sum) {
+            True => PropagatorStatus::Subsumed,
+            False => PropagatorStatus::Failed,
+            Unknown if self.propagate_sum(store, sum) => PropagatorStatus::NotSubsumed,
+            Unknown => PropagatorStatus::Failed,
+        }
+    }
+}
+
+impl<VStore, Domain, Bound> PropagatorDependencies<FDEvent> for Linear<VStore>
+where
+    VStore: VStoreConcept<Item = Domain>,
+    Domain: Collection<Item = Bound>,
+    Bound: IntBound,
+{
+    fn dependencies(&self) -> Vec<(usize, FDEvent)> {
+        self.sum.dependencies(FDEvent::Bound)
+    }
+}
+
+pub fn linear_eq<VStore, Domain, Bound>(
+    coefs: Vec<Bound>,
+    vars: Vec<Var<VStore>>,
+    c: Bound,
+) -> Linear<VStore>
+where
+    VStore: VStoreConcept<Item = Domain>,
+    Domain: Collection<Item = Bound>,
+{
+    Linear::new(coefs, vars, LinearOp::Eq, c)
+}
+
+pub fn linear_leq<VStore, Domain, Bound>(
+    coefs: Vec<Bound>,
+    vars: Vec<Var<VStore>>,
+    c: Bound,
+) -> Linear<VStore>
+where
+    VStore: VStoreConcept<Item = Domain>,
+    Domain: Collection<Item = Bound>,
+{
+    Linear::new(coefs, vars, LinearOp::Leq, c)
+}
+
+pub fn linear_geq<VStore, Domain, Bound>(
+    coefs: Vec<Bound>,
+    vars: Vec<Var<VStore>>,
+    c: Bound,
+) -> Linear<VStore>
+where
+    VStore: VStoreConcept<Item = Domain>,
+    Domain: Collection<Item = Bound>,
+{
+    Linear::new(coefs, vars, LinearOp::Geq, c)
+}
+
+pub fn linear_neq<VStore, Domain, Bound>(
+    coefs: Vec<Bound>,
+    vars: Vec<Var<VStore>>,
+    c: Bound,
+) -> Linear<VStore>
+where
+    VStore: VStoreConcept<Item = Domain>,
+    Domain: Collection<Item = Bound>,
+{
+    Linear::new(coefs, vars, LinearOp::Neq, c)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use gcollections::ops::*;
+    use gcollections::VectorStack;
+    use interval::interval::*;
+    use interval::interval_set::*;
+    use propagation::events::FDEvent::*;
+    use propagators::test::*;
+    use search::branch_and_bound::*;
+    use search::branching::*;
+    use search::engine::all_solution::*;
+    use search::engine::one_solution::*;
+    use search::propagation::*;
+    use search::test::*;
+
+    fn linear_test_one(
+        test_num: u32,
+        (coefs, op, c): (Vec<i32>, LinearOp, i32),
+        doms: Vec<Interval<i32>>,
+        before: SKleene,
+        after: SKleene,
+        delta_expected: Vec<(usize, FDEvent)>,
+        propagate_success: bool,
+    ) {
+        nary_propagator_test(
+            test_num,
+            |vars| Linear::new(coefs, vars, op, c),
+            doms,
+            before,
+            after,
+            delta_expected,
+            propagate_success,
+        );
+    }
+
+    #[test]
+    fn linear_test() {
+        use self::LinearOp::*;
+        let dom0_10 = (0, 10).to_interval();
+        let dom0_3 = (0, 3).to_interval();
+        let dom1_1 = (1, 1).to_interval();
+        let dom2_2 = (2, 2).to_interval();
+
+        // 2x + 3y = 6
+        linear_test_one(
+            1,
+            (vec![2, 3], Eq, 6),
+            vec![dom0_10, dom0_10],
+            Unknown,
+            Unknown,
+            vec![(0, Bound), (1, Bound)],
+            true,
+        );
+        // 2x - 3y = 6 with x in [0,3]: y = 0 and x = 3.
+        linear_test_one(
+            2,
+            (vec![2, -3], Eq, 6),
+            vec![dom0_3, dom0_10],
+            Unknown,
+            True,
+            vec![(0, Assignment), (1, Assignment)],
+            true,
+        );
+        linear_test_one(
+            3,
+            (vec![2, 3], Eq, 6),
+            vec![dom1_1, dom1_1],
+            False,
+            False,
+            vec![],
+            false,
+        );
+        // x + 2y + 3z <= 5
+        linear_test_one(
+            4,
+            (vec![1, 2, 3], Leq, 5),
+            vec![dom0_10, dom0_10, dom0_10],
+            Unknown,
+            Unknown,
+            vec![(0, Bound), (1, Bound), (2, Bound)],
+            true,
+        );
+        linear_test_one(
+            5,
+            (vec![1, 2, 3], Leq, 20),
+            vec![dom0_3, dom0_3, dom0_3],
+            True,
+            True,
+            vec![],
+            true,
+        );
+        // -x - y >= -1 with y = 1: x = 0.
+        linear_test_one(
+            6,
+            (vec![-1, -1], Geq, -1),
+            vec![dom0_10, dom1_1],
+            Unknown,
+            True,
+            vec![(0, Assignment)],
+            true,
+        );
+        linear_test_one(
+            7,
+            (vec![1, 1], Geq, 7),
+            vec![dom0_3, dom0_3],
+            False,
+            False,
+            vec![],
+            false,
+        );
+        // 2x + y != 4 with y = 2: x != 1.
+        linear_test_one(
+            8,
+            (vec![2, 1], Neq, 4),
+            vec![(1, 3).to_interval(), dom2_2],
+            Unknown,
+            True,
+            vec![(0, Bound)],
+            true,
+        );
+        linear_test_one(
+            9,
+            (vec![2, 1], Neq, 4),
+            vec![dom0_3, dom2_2],
+            Unknown,
+            Unknown,
+            vec![],
+            true,
+        );
+        linear_test_one(
+            10,
+            (vec![2, 1], Neq, 5),
+            vec![(1, 3).to_interval(), dom2_2],
+            Unknown,
+            Unknown,
+            vec![],
+            true,
+        );
+        linear_test_one(
+            11,
+            (vec![2, 1], Neq, 4),
+            vec![dom1_1, dom2_2],
+            False,
+            False,
+            vec![],
+            false,
+        );
+        linear_test_one(
+            12,
+            (vec![2, 1], Neq, 5),
+            vec![dom1_1, dom2_2],
+            True,
+            True,
+            vec![],
+            true,
+        );
+    }
+
+    #[test]
+    fn neq_hole_test() {
+        let mut space = FDSpace::empty();
+        let x = Box::new(space.vstore.alloc((0, 3).to_interval_set())) as Var<VStore>;
+        let y = Box::new(space.vstore.alloc((2, 2).to_interval_set())) as Var<VStore>;
+        let mut p = linear_neq(vec![2, 1], vec![x, y], 4);
+        assert!(p.propagate(&mut space.vstore));
+        assert_eq!(space.vstore[0], vec![(0, 0), (2, 3)].to_interval_set());
+    }
+
+    fn count_linear(op: LinearOp) -> usize {
+        let mut space = FDSpace::empty();
+        let vars: Vec<_> = (0..3)
+            .map(|_| Box::new(space.vstore.alloc((0, 4).to_interval_set())) as Var<VStore>)
+            .collect();
+        space
+            .cstore
+            .alloc(Box::new(Linear::new(vec![3, -2, 1], vars, op, 2)));
+        count_assignments(space, 3)
+    }
+
+    fn brute_force(op: LinearOp) -> usize {
+        let mut count = 0;
+        for x in 0..5 {
+            for y in 0..5 {
+                for z in 0..5 {
+                    let s = 3 * x - 2 * y + z;
+                    let sat = match op {
+                        LinearOp::Eq => s == 2,
+                        LinearOp::Leq => s <= 2,
+                        LinearOp::Geq => s >= 2,
+                        LinearOp::Neq => s != 2,
+                    };
+                    if sat {
+                        count += 1;
+                    }
+                }
+            }
+        }
+        count
+    }
+
+    #[test]
+    fn linear_solutions_test() {
+        for op in [LinearOp::Eq, LinearOp::Leq, LinearOp::Geq, LinearOp::Neq] {
+            assert_eq!(count_linear(op), brute_force(op), "{:?}", op);
+        }
+    }
+
+    #[test]
+    fn minimize_linear_objective_test() {
+        // minimize 3x - 2y subject to x + y >= 4 with x, y in [0,3].
+        let mut space = FDSpace::empty();
+        let x = Box::new(space.vstore.alloc((0, 3).to_interval_set())) as Var<VStore>;
+        let y = Box::new(space.vstore.alloc((0, 3).to_interval_set())) as Var<VStore>;
+        space.cstore.alloc(Box::new(linear_geq(
+            vec![1, 1],
+            vec![x.bclone(), y.bclone()],
+            4,
+        )));
+        let objective = Box::new(WeightedSum::new(vec![3, -2], vec![x, y]));
+        let mut search: AllSolution<OneSolution<_, VectorStack<_>, FDSpace>> =
+            AllSolution::new(OneSolution::new(BranchAndBound::new(
+                Mode::Minimize,
+                objective,
+                Propagation::new(Brancher::new(FirstSmallestVar, MiddleVal, BinarySplit)),
+            )));
+        search.start(&space);
+        let (_, status) = search.enter(space);
+        assert_eq!(status, Status::EndOfSearch);
+        assert_eq!(search.child.child.value, Some(-3));
+    }
+}
@@ -12,24 +12,42 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+pub mod linear;
+pub mod x_eq_abs_y;
+pub mod x_eq_max;
+pub mod x_eq_min;
 pub mod x_eq_y;
+pub mod x_eq_y_div_z;
+pub mod x_eq_y_mod_z;
 pub mod x_eq_y_mul_z;
 pub mod x_eq_y_plus_z;
 pub mod x_greater_y_plus_z;
 pub mod x_less_y;
 pub mod x_less_y_plus_z;
 pub mod x_neq_y;
+pub mod x_neq_y_mul_z;
+pub mod x_neq_y_plus_z;
 
 use concept::*;
 use gcollections::*;
+pub use propagators::cmp::linear::*;
+pub use propagators::cmp::x_eq_abs_y::XEqAbsY;
+pub use propagators::cmp::x_eq_max::{x_eq_max_y_z, XEqMax};
+pub use propagators::cmp::x_eq_min::{x_eq_min_y_z, XEqMin};
 pub use propagators::cmp::x_eq_y::XEqY;
+pub use propagators::cmp::x_eq_y_div_z::XEqYDivZ;
+pub use propagators::cmp::x_eq_y_mod_z::XEqYModZ;
 pub use propagators::cmp::x_eq_y_mul_z::XEqYMulZ;
 pub use propagators::cmp::x_eq_y_plus_z::XEqYPlusZ;
 pub use propagators::cmp::x_greater_y_plus_z::XGreaterYPlusZ;
 pub use propagators::cmp::x_less_y::XLessY;
 pub use propagators::cmp::x_less_y_plus_z::XLessYPlusZ;
 pub use propagators::cmp::x_neq_y::XNeqY;
+pub use propagators::cmp::x_neq_y_mul_z::XNeqYMulZ;
+pub use propagators::cmp::x_neq_y_plus_z::XNeqYPlusZ;
 use term::*;
+use trilean::SKleene;
+use trilean::SKleene::*;
 
 pub type XGreaterY<VStore> = XLessY<VStore>;
 pub type XGreaterEqY<VStore> = XLessY<VStore>;
@@ -85,6 +103,72 @@ where
     XLessYPlusZ::new(Box::new(Addition::new(x, -Bound::one())), y, z)
 }
 
+/// The negative and positive parts of `dom`, excluding 0, that are not empty.
+fn sign_parts<Domain, Bound>(dom: &Domain) -> Vec<Domain>
+where
+    Domain: IntDomain<Item = Bound>,
+    Bound: IntBound,
+{
+    let one = Bound::one();
+    vec![
+        dom.clone().shrink_right(-one.clone()),
+        dom.clone().shrink_left(one),
+    ]
+    .into_iter()
+    .filter(|part| !part.is_empty())
+    .collect()
+}
+
+/// The smallest interval containing all the `(lower, upper)` bounds.
+fn hull<Domain, Bound>(bounds: Vec<(Bound, Bound)>) -> Domain
+where
+    Domain: IntDomain<Item = Bound>,
+    Bound: IntBound,
+{
+    let lb = bounds.iter().map(|(l, _)| l.clone()).min();
+    let ub = bounds.into_iter().map(|(_, u)| u).max();
+    match (lb, ub) {
+        (Some(lb), Some(ub)) => Domain::new(lb, ub),
+        _ => Domain::empty(),
+    }
+}
+
+/// Entailment of `x = f(args)` where `image` contains every value of `f(args)`.
+/// `fixed` must be true only if `f(args)` is guaranteed to be defined and, when `image` is a singleton, equal to it.
+fn functional_subsumption<Domain, Bound>(x: &Domain, image: &Domain, fixed: bool) -> SKleene
+where
+    Domain: IntDomain<Item = Bound>,
+    Bound: IntBound,
+{
+    if !x.overlap(image) {
+        False
+    } else if fixed && x.is_singleton() && image.is_singleton() {
+        True
+    } else {
+        Unknown
+    }
+}
+
+/// Propagates `x != f(args)` by removing the value of `f(args)` from `x` once it is known (see `functional_subsumption`).
+fn remove_image<VStore, Domain, Bound>(
+    store: &mut VStore,
+    x: &mut Var<VStore>,
+    image: Domain,
+    fixed: bool,
+) -> bool
+where
+    VStore: Collection<Item = Domain>,
+    Domain: IntDomain<Item = Bound>,
+    Bound: IntBound,
+{
+    if fixed && image.is_singleton() {
+        let dom = x.read(store).difference(&image.lower());
+        x.update(store, dom)
+    } else {
+        true
+    }
+}
+
 // #[cfg(test)]
 // mod test {
 //   use super::*;
@@ -0,0 +1,308 @@
+// Copyright 2018 Pierre Talbot (IRCAM)
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+
+//     http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use concept::*;
+use gcollections::*;
+use kernel::*;
+use logic::*;
+use model::*;
+use propagation::events::*;
+use propagation::*;
+use propagators::cmp::{functional_subsumption, remove_image};
+use std::cmp::max;
+use trilean::SKleene;
+
+// x = |y|
+#[derive(Debug)]
+pub struct XEqAbsY<VStore> {
+    x: Var<VStore>,
+    y: Var<VStore>,
+    negated: bool,
+}
+
+impl<VStore> XEqAbsY<VStore> {
+    pub fn new(x: Var<VStore>, y: Var<VStore>) -> Self {
+        XEqAbsY {
+            x,
+            y,
+            negated: false,
+        }
+    }
+}
+
+impl<VStore> Clone for XEqAbsY<VStore>
+where
+    VStore: Collection,
+{
+    fn clone(&self) -> Self {
+        XEqAbsY {
+            x: self.x.bclone(),
+            y: self.y.bclone(),
+            negated: self.negated,
+        }
+    }
+}
+
+impl<VStore> DisplayStateful<Model> for XEqAbsY<VStore> {
+    fn display(&self, model: &Model) {
+        self.x.display(model);
+        print!("{}", if self.negated { " != |" } else { " = |" });
+        self.y.display(model);
+        print!("|");
+    }
+}
+
+impl<VStore, Domain, Bound> NotFormula<VStore> for XEqAbsY<VStore>
+where
+    VStore: VStoreConcept<Item = Domain> + 'static,
+    Domain: IntDomain<Item = Bound> + 'static,
+    Bound: IntBound + 'static,
+{
+    fn not(&self) -> Formula<VStore> {
+        let mut not = self.clone();
+        not.negated = !self.negated;
+        Box::new(not)
+    }
+}
+
+/// The values of `|y|`.
+fn abs<Domain, Bound>(y: &Domain) -> Domain
+where
+    Domain: IntDomain<Item = Bound>,
+    Bound: IntBound,
+{
+    let zero = Bound::zero();
+    if y.lower() >= zero {
+        Domain::new(y.lower(), y.upper())
+    } else if y.upper() <= zero {
+        Domain::new(-y.upper(), -y.lower())
+    } else {
+        Domain::new(zero, max(-y.lower(), y.upper()))
+    }
+}
+
+impl<VStore, Domain, Bound> Subsumption<VStore> for XEqAbsY<VStore>
+where
+    VStore: Collection<Item = Domain>,
+    Domain: IntDomain<Item = Bound>,
+    Bound: IntBound,
+{
+    fn is_subsumed(&self, store: &VStore) -> SKleene {
+        let x = self.x.read(store);
+        let y = self.y.read(store);
+        let entailed = functional_subsumption(&x, &abs(&y), y.is_singleton());
+        if self.negated {
+            !entailed
+        } else {
+            entailed
+        }
+    }
+}
+
+impl<VStore, Domain, Bound> Propagator<VStore> for XEqAbsY<VStore>
+where
+    VStore: Collection<Item = Domain>,
+    Domain: IntDomain<Item = Bound>,
+    Bound: IntBound,
+{
+    /// `y` is restricted to `[-x.upper()..x.upper()]` and its bounds are moved outside of `]-x.lower()..x.lower()[`.
+    fn propagate(&mut self, store: &mut VStore) -> bool {
+        let y = self.y.read(store);
+        if self.negated {
+            let fixed = y.is_singleton();
+            return remove_image(store, &mut self.x, abs(&y), fixed);
+        }
+        let x = self.x.read(store).intersection(&abs(&y));
+        if !self.x.update(store, x.clone()) {
+            return false;
+        }
+        let mut y = y.intersection(&Domain::new(-x.upper(), x.upper()));
+        if !y.is_empty() {
+            if y.lower() > -x.lower() {
+                y = y.shrink_left(x.lower());
+            }
+            if y.upper() < x.lower() {
+                y = y.shrink_right(-x.lower());
+            }
+        }
+        self.y.update(store, y)
+    }
+
+    fn cost(&self) -> PropagatorCost {
+        PropagatorCost::Binary
+    }
+}
+
+impl<VStore> PropagatorDependencies<FDEvent> for XEqAbsY<VStore> {
+    fn dependencies(&self) -> Vec<(usize, FDEvent)> {
+        let event = if self.negated {
+            FDEvent::Assignment
+        } else {
+            FDEvent::Bound
+        };
+        let mut deps = self.x.dependencies(event);
+        deps.append(&mut self.y.dependencies(event));
+        deps
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use interval::interval::*;
+    use propagation::events::FDEvent::*;
+    use propagators::test::*;
+    use trilean::SKleene::*;
+
+    #[test]
+    fn x_eq_abs_y_test() {
+        let dom_10_10 = (-10, 10).to_interval();
+        let dom_5_3 = (-5, -3).to_interval();
+        let dom_5_2 = (-5, 2).to_interval();
+        let dom3_5 = (3, 5).to_interval();
+        let dom0_4 = (0, 4).to_interval();
+        let dom1_2 = (1, 2).to_interval();
+        let three = (3, 3).to_interval();
+        let minus_three = (-3, -3).to_interval();
+
+        x_eq_abs_y_test_one(
+            1,
+            dom_10_10,
+            dom_10_10,
+            Unknown,
+            Unknown,
+            vec![(0, Bound)],
+            true,
+        );
+        x_eq_abs_y_test_one(
+            2,
+            dom_10_10,
+            dom_5_3,
+            Unknown,
+            Unknown,
+            vec![(0, Bound)],
+            true,
+        );
+        x_eq_abs_y_test_one(
+            3,
+            dom_10_10,
+            dom_5_2,
+            Unknown,
+            Unknown,
+            vec![(0, Bound)],
+            true,
+        );
+        x_eq_abs_y_test_one(
+            4,
+            dom0_4,
+            dom_10_10,
+            Unknown,
+            Unknown,
+            vec![(1, Bound)],
+            true,
+        );
+        // |y| >= 3 with y <= 2 gives y <= -3.
+        x_eq_abs_y_test_one(5, dom3_5, dom_5_2, Unknown, Unknown, vec![(1, Bound)], true);
+        x_eq_abs_y_test_one(
+            6,
+            three,
+            dom_5_2,
+            Unknown,
+            True,
+            vec![(1, Assignment)],
+            true,
+        );
+        x_eq_abs_y_test_one(
+            7,
+            dom_10_10,
+            minus_three,
+            Unknown,
+            True,
+            vec![(0, Assignment)],
+            true,
+        );
+        x_eq_abs_y_test_one(8, three, minus_three, True, True, vec![], true);
+        x_eq_abs_y_test_one(9, dom1_2, dom_5_3, False, False, vec![], false);
+        x_eq_abs_y_test_one(10, minus_three, dom_10_10, False, False, vec![], false);
+    }
+
+    fn x_eq_abs_y_test_one(
+        test_num: u32,
+        x: Interval<i32>,
+        y: Interval<i32>,
+        before: SKleene,
+        after: SKleene,
+        delta_expected: Vec<(usize, FDEvent)>,
+        propagate_success: bool,
+    ) {
+        binary_propagator_test(
+            test_num,
+            XEqAbsY::new,
+            x,
+            y,
+            before,
+            after,
+            delta_expected,
+            propagate_success,
+        );
+    }
+
+    #[test]
+    fn x_neq_abs_y_test() {
+        let dom0_10 = (0, 10).to_interval();
+        let dom3_10 = (3, 10).to_interval();
+        let dom_3_3 = (-3, 3).to_interval();
+        let three = (3, 3).to_interval();
+        let minus_three = (-3, -3).to_interval();
+
+        x_neq_abs_y_test_one(1, dom0_10, dom_3_3, Unknown, Unknown, vec![], true);
+        x_neq_abs_y_test_one(
+            2,
+            dom3_10,
+            minus_three,
+            Unknown,
+            True,
+            vec![(0, Bound)],
+            true,
+        );
+        x_neq_abs_y_test_one(3, three, minus_three, False, False, vec![], false);
+        x_neq_abs_y_test_one(4, minus_three, minus_three, True, True, vec![], true);
+    }
+
+    fn x_neq_abs_y_test_one(
+        test_num: u32,
+        x: Interval<i32>,
+        y: Interval<i32>,
+        before: SKleene,
+        after: SKleene,
+        delta_expected: Vec<(usize, FDEvent)>,
+        propagate_success: bool,
+    ) {
+        let negated = |x, y| {
+            let mut p = XEqAbsY::new(x, y);
+            p.negated = true;
+            p
+        };
+        binary_propagator_test(
+            test_num,
+            negated,
+            x,
+            y,
+            before,
+            after,
+            delta_expected,
+            propagate_success,
+        );
+    }
+}
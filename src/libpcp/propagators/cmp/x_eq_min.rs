@@ -0,0 +1,415 @@
+// Copyright 2018 Pierre Talbot (IRCAM)
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+
+//     http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use concept::*;
+use gcollections::*;
+use kernel::*;
+use logic::*;
+use model::*;
+use propagation::events::*;
+use propagation::*;
+use propagators::cmp::{functional_subsumption, remove_image};
+use trilean::SKleene;
+
+// x = min(vars)
+#[derive(Debug)]
+pub struct XEqMin<VStore> {
+    x: Var<VStore>,
+    vars: Vec<Var<VStore>>,
+    negated: bool,
+}
+
+impl<VStore> XEqMin<VStore> {
+    /// Precondition: `vars.len() > 0`.
+    pub fn new(x: Var<VStore>, vars: Vec<Var<VStore>>) -> Self {
+        assert!(
+            !vars.is_empty(),
+            "Variable array in `XEqMin` must be non-empty."
+        );
+        XEqMin {
+            x,
+            vars,
+            negated: false,
+        }
+    }
+}
+
+/// x = min(y, z)
+pub fn x_eq_min_y_z<VStore>(x: Var<VStore>, y: Var<VStore>, z: Var<VStore>) -> XEqMin<VStore> {
+    XEqMin::new(x, vec![y, z])
+}
+
+impl<VStore> Clone for XEqMin<VStore>
+where
+    VStore: Collection,
+{
+    fn clone(&self) -> Self {
+        XEqMin {
+            x: self.x.bclone(),
+            vars: self.vars.iter().map(|v| v.bclone()).collect(),
+            negated: self.negated,
+        }
+    }
+}
+
+impl<VStore> DisplayStateful<Model> for XEqMin<VStore> {
+    fn display(&self, model: &Model) {
+        self.x.display(model);
+        print!("{}", if self.negated { " != " } else { " = " });
+        model.display_global("min", &self.vars);
+    }
+}
+
+impl<VStore, Domain, Bound> NotFormula<VStore> for XEqMin<VStore>
+where
+    VStore: VStoreConcept<Item = Domain> + 'static,
+    Domain: IntDomain<Item = Bound> + 'static,
+    Bound: IntBound + 'static,
+{
+    fn not(&self) -> Formula<VStore> {
+        let mut not = self.clone();
+        not.negated = !self.negated;
+        Box::new(not)
+    }
+}
+
+impl<VStore, Domain, Bound> XEqMin<VStore>
+where
+    VStore: Collection<Item = Domain>,
+    Domain: IntDomain<Item = Bound>,
+    Bound: IntBound,
+{
+    /// The values of `min(vars)`.
+    /// Since `min` is defined everywhere, the minimum is known as soon as this interval is a singleton.
+    fn image(&self, store: &VStore) -> Domain {
+        let doms: Vec<_> = self.vars.iter().map(|v| v.read(store)).collect();
+        let lb = doms.iter().map(|d| d.lower()).min().unwrap();
+        let ub = doms.iter().map(|d| d.upper()).min().unwrap();
+        Domain::new(lb, ub)
+    }
+}
+
+impl<VStore, Domain, Bound> Subsumption<VStore> for XEqMin<VStore>
+where
+    VStore: Collection<Item = Domain>,
+    Domain: IntDomain<Item = Bound>,
+    Bound: IntBound,
+{
+    fn is_subsumed(&self, store: &VStore) -> SKleene {
+        let image = self.image(store);
+        let entailed = functional_subsumption(&self.x.read(store), &image, true);
+        if self.negated {
+            !entailed
+        } else {
+            entailed
+        }
+    }
+}
+
+impl<VStore, Domain, Bound> Propagator<VStore> for XEqMin<VStore>
+where
+    VStore: Collection<Item = Domain>,
+    Domain: IntDomain<Item = Bound>,
+    Bound: IntBound,
+{
+    /// Every variable is greater or equal to `x`, and if only one variable can be smaller than `x.upper()`, it must be.
+    fn propagate(&mut self, store: &mut VStore) -> bool {
+        let image = self.image(store);
+        if self.negated {
+            return remove_image(store, &mut self.x, image, true);
+        }
+        let x = self.x.read(store).intersection(&image);
+        if !self.x.update(store, x.clone()) {
+            return false;
+        }
+        for var in &mut self.vars {
+            let dom = var.read(store).shrink_left(x.lower());
+            if !var.update(store, dom) {
+                return false;
+            }
+        }
+        let mut supports = self
+            .vars
+            .iter()
+            .enumerate()
+            .filter(|(_, v)| v.read(store).lower() <= x.upper())
+            .map(|(i, _)| i);
+        match (supports.next(), supports.next()) {
+            (Some(i), None) => {
+                let dom = self.vars[i].read(store).shrink_right(x.upper());
+                self.vars[i].update(store, dom)
+            }
+            _ => true,
+        }
+    }
+}
+
+impl<VStore> PropagatorDependencies<FDEvent> for XEqMin<VStore> {
+    fn dependencies(&self) -> Vec<(usize, FDEvent)> {
+        let event = if self.negated {
+            FDEvent::Assignment
+        } else {
+            FDEvent::Bound
+        };
+        let mut deps = self.x.dependencies(event);
+        for var in &self.vars {
+            deps.append(&mut var.dependencies(event));
+        }
+        deps
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use interval::interval::*;
+    use propagation::events::FDEvent::*;
+    use propagators::test::*;
+    use trilean::SKleene::*;
+    use variable::VStoreFD;
+
+    #[test]
+    fn x_eq_min_y_z_test() {
+        let dom0_10 = (0, 10).to_interval();
+        let dom3_5 = (3, 5).to_interval();
+        let dom4_8 = (4, 8).to_interval();
+        let dom6_8 = (6, 8).to_interval();
+        let dom0_2 = (0, 2).to_interval();
+        let three = (3, 3).to_interval();
+        let four = (4, 4).to_interval();
+
+        trinary_propagator_test(
+            1,
+            x_eq_min_y_z,
+            dom0_10,
+            dom0_10,
+            dom0_10,
+            Unknown,
+            Unknown,
+            vec![],
+            true,
+        );
+        trinary_propagator_test(
+            2,
+            x_eq_min_y_z,
+            dom0_10,
+            dom3_5,
+            dom4_8,
+            Unknown,
+            Unknown,
+            vec![(0, Bound)],
+            true,
+        );
+        trinary_propagator_test(
+            3,
+            x_eq_min_y_z,
+            dom4_8,
+            dom3_5,
+            dom0_10,
+            Unknown,
+            Unknown,
+            vec![(0, Bound), (1, Bound), (2, Bound)],
+            true,
+        );
+        // Only `y` can be smaller than 5.
+        trinary_propagator_test(
+            4,
+            x_eq_min_y_z,
+            dom3_5,
+            dom0_10,
+            dom6_8,
+            Unknown,
+            Unknown,
+            vec![(1, Bound)],
+            true,
+        );
+        trinary_propagator_test(
+            5,
+            x_eq_min_y_z,
+            three,
+            dom0_10,
+            dom6_8,
+            Unknown,
+            True,
+            vec![(1, Assignment)],
+            true,
+        );
+        trinary_propagator_test(
+            6,
+            x_eq_min_y_z,
+            four,
+            three,
+            dom4_8,
+            False,
+            False,
+            vec![],
+            false,
+        );
+        trinary_propagator_test(
+            7,
+            x_eq_min_y_z,
+            three,
+            three,
+            dom4_8,
+            True,
+            True,
+            vec![],
+            true,
+        );
+        trinary_propagator_test(
+            8,
+            x_eq_min_y_z,
+            three,
+            three,
+            four,
+            True,
+            True,
+            vec![],
+            true,
+        );
+        trinary_propagator_test(
+            9,
+            x_eq_min_y_z,
+            dom0_2,
+            dom3_5,
+            dom4_8,
+            False,
+            False,
+            vec![],
+            false,
+        );
+    }
+
+    #[test]
+    fn x_eq_min_test() {
+        let dom0_10 = (0, 10).to_interval();
+        let dom2_6 = (2, 6).to_interval();
+        let dom5_9 = (5, 9).to_interval();
+        let three = (3, 3).to_interval();
+
+        x_eq_min_test_one(
+            1,
+            vec![dom0_10, dom2_6, dom5_9, dom5_9],
+            Unknown,
+            Unknown,
+            vec![(0, Bound)],
+            true,
+        );
+        x_eq_min_test_one(
+            2,
+            vec![three, dom0_10, dom5_9, dom5_9],
+            Unknown,
+            True,
+            vec![(1, Assignment)],
+            true,
+        );
+        x_eq_min_test_one(
+            3,
+            vec![three, dom2_6, dom0_10, dom5_9],
+            Unknown,
+            Unknown,
+            vec![(1, Bound), (2, Bound)],
+            true,
+        );
+        x_eq_min_test_one(
+            4,
+            vec![three, three, three, three],
+            True,
+            True,
+            vec![],
+            true,
+        );
+    }
+
+    fn x_eq_min_test_one(
+        test_num: u32,
+        doms: Vec<Interval<i32>>,
+        before: SKleene,
+        after: SKleene,
+        delta_expected: Vec<(usize, FDEvent)>,
+        propagate_success: bool,
+    ) {
+        nary_propagator_test(
+            test_num,
+            |mut vars: Vec<FDVar>| {
+                let x = vars.remove(0);
+                XEqMin::new(x, vars)
+            },
+            doms,
+            before,
+            after,
+            delta_expected,
+            propagate_success,
+        );
+    }
+
+    #[test]
+    fn x_neq_min_y_z_test() {
+        let dom0_10 = (0, 10).to_interval();
+        let dom3_10 = (3, 10).to_interval();
+        let three = (3, 3).to_interval();
+        let four = (4, 4).to_interval();
+
+        trinary_propagator_test(
+            1,
+            x_neq_min_y_z,
+            dom0_10,
+            dom0_10,
+            four,
+            Unknown,
+            Unknown,
+            vec![],
+            true,
+        );
+        trinary_propagator_test(
+            2,
+            x_neq_min_y_z,
+            dom3_10,
+            three,
+            four,
+            Unknown,
+            True,
+            vec![(0, Bound)],
+            true,
+        );
+        trinary_propagator_test(
+            3,
+            x_neq_min_y_z,
+            three,
+            three,
+            four,
+            False,
+            False,
+            vec![],
+            false,
+        );
+        trinary_propagator_test(
+            4,
+            x_neq_min_y_z,
+            four,
+            three,
+            four,
+            True,
+            True,
+            vec![],
+            true,
+        );
+    }
+
+    fn x_neq_min_y_z(x: FDVar, y: FDVar, z: FDVar) -> XEqMin<VStoreFD> {
+        let mut p = x_eq_min_y_z(x, y, z);
+        p.negated = true;
+        p
+    }
+}
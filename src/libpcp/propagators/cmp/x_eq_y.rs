@@ -94,10 +94,11 @@ where
     }
 }
 
-impl<VStore, Dom> Propagator<VStore> for XEqY<VStore>
+impl<VStore, Dom, Bound> Propagator<VStore> for XEqY<VStore>
 where
     VStore: Collection<Item = Dom>,
-    Dom: Intersection<Output = Dom> + Clone,
+    Dom: Bounded<Item = Bound> + Intersection<Output = Dom> + Subset + Clone,
+    Bound: PartialOrd,
 {
     fn propagate(&mut self, store: &mut VStore) -> bool {
         let x = self.x.read(store);
@@ -105,6 +106,26 @@ where
         let new = x.intersection(&y);
         self.x.update(store, new.clone()) && self.y.update(store, new)
     }
+
+    /// The domains are compared after the propagation since they can differ if `x` and `y` share a variable.
+    fn propagate_status(&mut self, store: &mut VStore) -> PropagatorStatus {
+        if !self.propagate(store) {
+            return PropagatorStatus::Failed;
+        }
+        let x = self.x.read(store);
+        let y = self.y.read(store);
+        if x.lower() == y.upper() && x.upper() == y.lower() {
+            PropagatorStatus::Subsumed
+        } else if x.is_subset(&y) && y.is_subset(&x) {
+            PropagatorStatus::Fixpoint
+        } else {
+            PropagatorStatus::NotFixpoint
+        }
+    }
+
+    fn cost(&self) -> PropagatorCost {
+        PropagatorCost::Binary
+    }
 }
 
 impl<VStore> PropagatorDependencies<FDEvent> for XEqY<VStore> {
@@ -121,6 +142,7 @@ mod test {
     use interval::interval::*;
     use propagation::events::FDEvent::*;
     use propagators::test::*;
+    use variable::VStoreFD;
 
     #[test]
     fn x_eq_y_test() {
@@ -191,4 +213,33 @@ mod test {
             propagate_success,
         );
     }
+
+    fn x_eq_y_status_test_one(
+        test_num: u32,
+        x: Interval<i32>,
+        y: Interval<i32>,
+        expected: PropagatorStatus,
+    ) {
+        let mut vstore = VStoreFD::empty();
+        let x = Box::new(vstore.alloc(x)) as FDVar;
+        let y = Box::new(vstore.alloc(y)) as FDVar;
+        let mut propagator = XEqY::new(x, y);
+        assert_eq!(
+            propagator.propagate_status(&mut vstore),
+            expected,
+            "Test {}.",
+            test_num
+        );
+    }
+
+    #[test]
+    fn x_eq_y_status_test() {
+        let dom0_10 = (0, 10).to_interval();
+        let dom5_15 = (5, 15).to_interval();
+        let dom10_20 = (10, 20).to_interval();
+        let dom11_20 = (11, 20).to_interval();
+        x_eq_y_status_test_one(1, dom0_10, dom5_15, PropagatorStatus::Fixpoint);
+        x_eq_y_status_test_one(2, dom0_10, dom10_20, PropagatorStatus::Subsumed);
+        x_eq_y_status_test_one(3, dom0_10, dom11_20, PropagatorStatus::Failed);
+    }
 }
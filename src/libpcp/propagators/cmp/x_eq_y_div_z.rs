@@ -0,0 +1,427 @@
+// Copyright 2018 Pierre Talbot (IRCAM)
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+
+//     http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use concept::*;
+use gcollections::*;
+use kernel::*;
+use logic::*;
+use model::*;
+use propagation::events::*;
+use propagation::*;
+use propagators::cmp::{functional_subsumption, hull, remove_image, sign_parts};
+use trilean::SKleene;
+
+// x = y div z
+// The quotient is rounded towards negative infinity (see `Integer::div_floor`) and `z` must be different from 0.
+// The divisor is only pruned of the value 0.
+#[derive(Debug)]
+pub struct XEqYDivZ<VStore> {
+    x: Var<VStore>,
+    y: Var<VStore>,
+    z: Var<VStore>,
+    negated: bool,
+}
+
+impl<VStore> XEqYDivZ<VStore> {
+    pub fn new(x: Var<VStore>, y: Var<VStore>, z: Var<VStore>) -> Self {
+        XEqYDivZ {
+            x,
+            y,
+            z,
+            negated: false,
+        }
+    }
+}
+
+impl<VStore> Clone for XEqYDivZ<VStore>
+where
+    VStore: Collection,
+{
+    fn clone(&self) -> Self {
+        XEqYDivZ {
+            x: self.x.bclone(),
+            y: self.y.bclone(),
+            z: self.z.bclone(),
+            negated: self.negated,
+        }
+    }
+}
+
+impl<VStore> DisplayStateful<Model> for XEqYDivZ<VStore> {
+    fn display(&self, model: &Model) {
+        self.x.display(model);
+        print!("{}", if self.negated { " != " } else { " = " });
+        self.y.display(model);
+        print!(" div ");
+        self.z.display(model);
+    }
+}
+
+impl<VStore, Domain, Bound> NotFormula<VStore> for XEqYDivZ<VStore>
+where
+    VStore: VStoreConcept<Item = Domain> + 'static,
+    Domain: IntDomain<Item = Bound> + 'static,
+    Bound: IntBound + 'static,
+{
+    fn not(&self) -> Formula<VStore> {
+        let mut not = self.clone();
+        not.negated = !self.negated;
+        Box::new(not)
+    }
+}
+
+/// The smallest and largest values of `f(a, b)` with `a` and `b` taken among the bounds of `dom_a` and `dom_b`.
+fn corners<Domain, Bound, F>(dom_a: &Domain, dom_b: &Domain, f: F) -> (Bound, Bound)
+where
+    Domain: IntDomain<Item = Bound>,
+    Bound: IntBound,
+    F: Fn(Bound, Bound) -> Bound,
+{
+    let values = [
+        f(dom_a.lower(), dom_b.lower()),
+        f(dom_a.lower(), dom_b.upper()),
+        f(dom_a.upper(), dom_b.lower()),
+        f(dom_a.upper(), dom_b.upper()),
+    ];
+    let min = values.iter().min().unwrap().clone();
+    let max = values.iter().max().unwrap().clone();
+    (min, max)
+}
+
+/// The values of `y div z`.
+/// The quotient is monotonic in `y` and `z` when `z` has a fixed sign, so its extrema are reached at the bounds.
+fn quotient<Domain, Bound>(y: &Domain, z: &Domain) -> Domain
+where
+    Domain: IntDomain<Item = Bound>,
+    Bound: IntBound,
+{
+    hull(
+        sign_parts(z)
+            .iter()
+            .map(|part| corners(y, part, |a, b| a.div_floor(&b)))
+            .collect(),
+    )
+}
+
+/// The values `y` such that `y div z` belongs to `x`.
+/// For a positive `z` we have `x * z <= y < (x + 1) * z`, and `(x + 1) * z < y <= x * z` for a negative `z`.
+fn dividend<Domain, Bound>(x: &Domain, z: &Domain) -> Domain
+where
+    Domain: IntDomain<Item = Bound>,
+    Bound: IntBound,
+{
+    let one = Bound::one();
+    hull(
+        sign_parts(z)
+            .into_iter()
+            .map(|part| {
+                let positive = part.lower() > Bound::zero();
+                let low = x.clone() * part.clone();
+                let high = (x.clone() + one.clone()) * part;
+                if positive {
+                    (low.lower(), high.upper() - one.clone())
+                } else {
+                    (high.lower() + one.clone(), low.upper())
+                }
+            })
+            .collect(),
+    )
+}
+
+impl<VStore, Domain, Bound> Subsumption<VStore> for XEqYDivZ<VStore>
+where
+    VStore: Collection<Item = Domain>,
+    Domain: IntDomain<Item = Bound>,
+    Bound: IntBound,
+{
+    fn is_subsumed(&self, store: &VStore) -> SKleene {
+        let x = self.x.read(store);
+        let y = self.y.read(store);
+        let z = self.z.read(store);
+        let fixed = y.is_singleton() && z.is_singleton();
+        let entailed = functional_subsumption(&x, &quotient(&y, &z), fixed);
+        if self.negated {
+            !entailed
+        } else {
+            entailed
+        }
+    }
+}
+
+impl<VStore, Domain, Bound> Propagator<VStore> for XEqYDivZ<VStore>
+where
+    VStore: Collection<Item = Domain>,
+    Domain: IntDomain<Item = Bound>,
+    Bound: IntBound,
+{
+    fn propagate(&mut self, store: &mut VStore) -> bool {
+        let y = self.y.read(store);
+        let z = self.z.read(store);
+        if self.negated {
+            let fixed = y.is_singleton() && z.is_singleton();
+            return remove_image(store, &mut self.x, quotient(&y, &z), fixed);
+        }
+        let z = z.difference(&Bound::zero());
+        if !self.z.update(store, z.clone()) {
+            return false;
+        }
+        let x = self.x.read(store).intersection(&quotient(&y, &z));
+        if !self.x.update(store, x.clone()) {
+            return false;
+        }
+        self.y.update(store, y.intersection(&dividend(&x, &z)))
+    }
+
+    fn cost(&self) -> PropagatorCost {
+        PropagatorCost::Ternary
+    }
+}
+
+impl<VStore> PropagatorDependencies<FDEvent> for XEqYDivZ<VStore> {
+    fn dependencies(&self) -> Vec<(usize, FDEvent)> {
+        let event = if self.negated {
+            FDEvent::Assignment
+        } else {
+            FDEvent::Bound
+        };
+        let mut deps = self.x.dependencies(event);
+        deps.append(&mut self.y.dependencies(event));
+        deps.append(&mut self.z.dependencies(event));
+        deps
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use interval::interval::*;
+    use propagation::events::FDEvent::*;
+    use propagators::test::*;
+    use trilean::SKleene::*;
+    use variable::VStoreFD;
+
+    #[test]
+    fn x_eq_y_div_z_test() {
+        let dom0_10 = (0, 10).to_interval();
+        let dom_10_10 = (-10, 10).to_interval();
+        let dom7_9 = (7, 9).to_interval();
+        let dom2_3 = (2, 3).to_interval();
+        let dom0_2 = (0, 2).to_interval();
+        let dom_3_3 = (-3, 3).to_interval();
+        let zero = (0, 0).to_interval();
+        let two = (2, 2).to_interval();
+        let three = (3, 3).to_interval();
+        let minus_three = (-3, -3).to_interval();
+        let minus_seven = (-7, -7).to_interval();
+
+        trinary_propagator_test(
+            1,
+            XEqYDivZ::new,
+            dom0_10,
+            dom7_9,
+            dom2_3,
+            Unknown,
+            Unknown,
+            vec![(0, Bound)],
+            true,
+        );
+        trinary_propagator_test(
+            2,
+            XEqYDivZ::new,
+            dom_10_10,
+            dom7_9,
+            two,
+            Unknown,
+            Unknown,
+            vec![(0, Bound)],
+            true,
+        );
+        // 7 div 2 = 3, -7 div 2 = -4 and 7 div -2 = -4.
+        trinary_propagator_test(
+            3,
+            XEqYDivZ::new,
+            dom_10_10,
+            (7, 7).to_interval(),
+            two,
+            Unknown,
+            True,
+            vec![(0, Assignment)],
+            true,
+        );
+        trinary_propagator_test(
+            4,
+            XEqYDivZ::new,
+            dom_10_10,
+            minus_seven,
+            two,
+            Unknown,
+            True,
+            vec![(0, Assignment)],
+            true,
+        );
+        trinary_propagator_test(
+            5,
+            XEqYDivZ::new,
+            dom_10_10,
+            (7, 7).to_interval(),
+            (-2, -2).to_interval(),
+            Unknown,
+            True,
+            vec![(0, Assignment)],
+            true,
+        );
+        // x = 3 and z = 2 gives y in [6..7].
+        trinary_propagator_test(
+            6,
+            XEqYDivZ::new,
+            three,
+            dom0_10,
+            two,
+            Unknown,
+            Unknown,
+            vec![(1, Bound)],
+            true,
+        );
+        // x = -3 and z = 2 gives y in [-6..-5].
+        trinary_propagator_test(
+            7,
+            XEqYDivZ::new,
+            minus_three,
+            dom_10_10,
+            two,
+            Unknown,
+            Unknown,
+            vec![(1, Bound)],
+            true,
+        );
+        // The divisor cannot be 0.
+        trinary_propagator_test(
+            8,
+            XEqYDivZ::new,
+            dom0_10,
+            dom0_10,
+            dom0_2,
+            Unknown,
+            Unknown,
+            vec![(2, Bound)],
+            true,
+        );
+        trinary_propagator_test(
+            9,
+            XEqYDivZ::new,
+            dom0_10,
+            dom0_10,
+            zero,
+            False,
+            False,
+            vec![],
+            false,
+        );
+        trinary_propagator_test(
+            10,
+            XEqYDivZ::new,
+            dom_10_10,
+            dom7_9,
+            dom_3_3,
+            Unknown,
+            Unknown,
+            vec![(0, Bound)],
+            true,
+        );
+        // x = 3 and z in [2..3] gives y in [6..11].
+        trinary_propagator_test(
+            11,
+            XEqYDivZ::new,
+            three,
+            dom0_10,
+            dom2_3,
+            Unknown,
+            Unknown,
+            vec![(1, Bound)],
+            true,
+        );
+        trinary_propagator_test(
+            12,
+            XEqYDivZ::new,
+            three,
+            dom0_2,
+            dom2_3,
+            False,
+            False,
+            vec![],
+            false,
+        );
+    }
+
+    #[test]
+    fn x_neq_y_div_z_test() {
+        let dom0_10 = (0, 10).to_interval();
+        let dom3_10 = (3, 10).to_interval();
+        let three = (3, 3).to_interval();
+        let seven = (7, 7).to_interval();
+        let two = (2, 2).to_interval();
+        let zero = (0, 0).to_interval();
+
+        trinary_propagator_test(
+            1,
+            x_neq_y_div_z,
+            dom0_10,
+            dom0_10,
+            two,
+            Unknown,
+            Unknown,
+            vec![],
+            true,
+        );
+        trinary_propagator_test(
+            2,
+            x_neq_y_div_z,
+            dom3_10,
+            seven,
+            two,
+            Unknown,
+            True,
+            vec![(0, Bound)],
+            true,
+        );
+        trinary_propagator_test(
+            3,
+            x_neq_y_div_z,
+            three,
+            seven,
+            two,
+            False,
+            False,
+            vec![],
+            false,
+        );
+        trinary_propagator_test(
+            4,
+            x_neq_y_div_z,
+            dom0_10,
+            seven,
+            zero,
+            True,
+            True,
+            vec![],
+            true,
+        );
+    }
+
+    fn x_neq_y_div_z(x: FDVar, y: FDVar, z: FDVar) -> XEqYDivZ<VStoreFD> {
+        let mut p = XEqYDivZ::new(x, y, z);
+        p.negated = true;
+        p
+    }
+}
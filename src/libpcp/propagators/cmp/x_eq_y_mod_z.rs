@@ -0,0 +1,413 @@
+// Copyright 2018 Pierre Talbot (IRCAM)
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+
+//     http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use concept::*;
+use gcollections::*;
+use kernel::*;
+use logic::*;
+use model::*;
+use propagation::events::*;
+use propagation::*;
+use propagators::cmp::{functional_subsumption, hull, remove_image, sign_parts};
+use std::cmp::{max, min};
+use trilean::SKleene;
+
+// x = y mod z
+// The remainder has the sign of `z` (see `Integer::mod_floor`) and `z` must be different from 0.
+#[derive(Debug)]
+pub struct XEqYModZ<VStore> {
+    x: Var<VStore>,
+    y: Var<VStore>,
+    z: Var<VStore>,
+    negated: bool,
+}
+
+impl<VStore> XEqYModZ<VStore> {
+    pub fn new(x: Var<VStore>, y: Var<VStore>, z: Var<VStore>) -> Self {
+        XEqYModZ {
+            x,
+            y,
+            z,
+            negated: false,
+        }
+    }
+}
+
+impl<VStore> Clone for XEqYModZ<VStore>
+where
+    VStore: Collection,
+{
+    fn clone(&self) -> Self {
+        XEqYModZ {
+            x: self.x.bclone(),
+            y: self.y.bclone(),
+            z: self.z.bclone(),
+            negated: self.negated,
+        }
+    }
+}
+
+impl<VStore> DisplayStateful<Model> for XEqYModZ<VStore> {
+    fn display(&self, model: &Model) {
+        self.x.display(model);
+        print!("{}", if self.negated { " != " } else { " = " });
+        self.y.display(model);
+        print!(" mod ");
+        self.z.display(model);
+    }
+}
+
+impl<VStore, Domain, Bound> NotFormula<VStore> for XEqYModZ<VStore>
+where
+    VStore: VStoreConcept<Item = Domain> + 'static,
+    Domain: IntDomain<Item = Bound> + 'static,
+    Bound: IntBound + 'static,
+{
+    fn not(&self) -> Formula<VStore> {
+        let mut not = self.clone();
+        not.negated = !self.negated;
+        Box::new(not)
+    }
+}
+
+/// The values of `y mod z`.
+/// With a positive `z` the remainder is in `[0..z-1]`, and it is also smaller than `y` if `y` is positive (symmetrically for a negative `z`).
+fn remainder<Domain, Bound>(y: &Domain, z: &Domain) -> Domain
+where
+    Domain: IntDomain<Item = Bound>,
+    Bound: IntBound,
+{
+    let zero = Bound::zero();
+    let one = Bound::one();
+    if y.is_singleton() && z.is_singleton() && !z.lower().is_zero() {
+        return Domain::singleton(y.lower().mod_floor(&z.lower()));
+    }
+    hull(
+        sign_parts(z)
+            .into_iter()
+            .map(|part| {
+                if part.lower() > zero {
+                    let mut ub = part.upper() - one.clone();
+                    if y.lower() >= zero {
+                        ub = min(ub, y.upper());
+                    }
+                    (zero.clone(), ub)
+                } else {
+                    let mut lb = part.lower() + one.clone();
+                    if y.upper() <= zero {
+                        lb = max(lb, y.lower());
+                    }
+                    (lb, zero.clone())
+                }
+            })
+            .collect(),
+    )
+}
+
+impl<VStore, Domain, Bound> Subsumption<VStore> for XEqYModZ<VStore>
+where
+    VStore: Collection<Item = Domain>,
+    Domain: IntDomain<Item = Bound>,
+    Bound: IntBound,
+{
+    fn is_subsumed(&self, store: &VStore) -> SKleene {
+        let x = self.x.read(store);
+        let y = self.y.read(store);
+        let z = self.z.read(store);
+        let fixed = y.is_singleton() && z.is_singleton();
+        let entailed = functional_subsumption(&x, &remainder(&y, &z), fixed);
+        if self.negated {
+            !entailed
+        } else {
+            entailed
+        }
+    }
+}
+
+impl<VStore, Domain, Bound> Propagator<VStore> for XEqYModZ<VStore>
+where
+    VStore: Collection<Item = Domain>,
+    Domain: IntDomain<Item = Bound>,
+    Bound: IntBound,
+{
+    /// Besides the bounds of the remainder, a non-zero `x` implies that `y` is not zero and that `|z| > |x|` with `z` of the same sign as `x`.
+    fn propagate(&mut self, store: &mut VStore) -> bool {
+        let y = self.y.read(store);
+        let z = self.z.read(store);
+        if self.negated {
+            let fixed = y.is_singleton() && z.is_singleton();
+            return remove_image(store, &mut self.x, remainder(&y, &z), fixed);
+        }
+        let zero = Bound::zero();
+        let one = Bound::one();
+        let x = self.x.read(store).intersection(&remainder(&y, &z));
+        if !self.x.update(store, x.clone()) {
+            return false;
+        }
+        let mut z = z.difference(&zero);
+        if x.lower() > zero {
+            z = z.shrink_left(x.lower() + one);
+        } else if x.upper() < zero {
+            z = z.shrink_right(x.upper() - one);
+        }
+        if !self.z.update(store, z) {
+            return false;
+        }
+        if x.contains(&zero) {
+            true
+        } else {
+            self.y.update(store, y.difference(&zero))
+        }
+    }
+
+    fn cost(&self) -> PropagatorCost {
+        PropagatorCost::Ternary
+    }
+}
+
+impl<VStore> PropagatorDependencies<FDEvent> for XEqYModZ<VStore> {
+    fn dependencies(&self) -> Vec<(usize, FDEvent)> {
+        let event = if self.negated {
+            FDEvent::Assignment
+        } else {
+            FDEvent::Bound
+        };
+        let mut deps = self.x.dependencies(event);
+        deps.append(&mut self.y.dependencies(event));
+        deps.append(&mut self.z.dependencies(event));
+        deps
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use interval::interval::*;
+    use propagation::events::FDEvent::*;
+    use propagators::test::*;
+    use trilean::SKleene::*;
+    use variable::VStoreFD;
+
+    #[test]
+    fn x_eq_y_mod_z_test() {
+        let dom_10_10 = (-10, 10).to_interval();
+        let dom0_10 = (0, 10).to_interval();
+        let dom0_2 = (0, 2).to_interval();
+        let dom3_5 = (3, 5).to_interval();
+        let dom_5_3 = (-5, -3).to_interval();
+        let dom_3_3 = (-3, 3).to_interval();
+        let zero = (0, 0).to_interval();
+        let one = (1, 1).to_interval();
+        let two = (2, 2).to_interval();
+        let three = (3, 3).to_interval();
+        let seven = (7, 7).to_interval();
+
+        trinary_propagator_test(
+            1,
+            XEqYModZ::new,
+            dom_10_10,
+            dom0_10,
+            dom3_5,
+            Unknown,
+            Unknown,
+            vec![(0, Bound)],
+            true,
+        );
+        trinary_propagator_test(
+            2,
+            XEqYModZ::new,
+            dom_10_10,
+            dom0_10,
+            dom_5_3,
+            Unknown,
+            Unknown,
+            vec![(0, Bound)],
+            true,
+        );
+        // The remainder is smaller than a positive dividend.
+        trinary_propagator_test(
+            3,
+            XEqYModZ::new,
+            dom_10_10,
+            dom0_2,
+            dom3_5,
+            Unknown,
+            Unknown,
+            vec![(0, Bound)],
+            true,
+        );
+        trinary_propagator_test(
+            4,
+            XEqYModZ::new,
+            dom_10_10,
+            dom_3_3,
+            dom_3_3,
+            Unknown,
+            Unknown,
+            vec![(0, Bound)],
+            true,
+        );
+        // 7 mod 3 = 1, -7 mod 3 = 2 and 7 mod -3 = -2.
+        trinary_propagator_test(
+            5,
+            XEqYModZ::new,
+            dom_10_10,
+            seven,
+            three,
+            Unknown,
+            True,
+            vec![(0, Assignment)],
+            true,
+        );
+        trinary_propagator_test(
+            6,
+            XEqYModZ::new,
+            dom_10_10,
+            (-7, -7).to_interval(),
+            three,
+            Unknown,
+            True,
+            vec![(0, Assignment)],
+            true,
+        );
+        trinary_propagator_test(
+            7,
+            XEqYModZ::new,
+            dom_10_10,
+            seven,
+            (-3, -3).to_interval(),
+            Unknown,
+            True,
+            vec![(0, Assignment)],
+            true,
+        );
+        trinary_propagator_test(
+            8,
+            XEqYModZ::new,
+            two,
+            seven,
+            three,
+            False,
+            False,
+            vec![],
+            false,
+        );
+        // x = 3 implies z > 3 and y != 0.
+        trinary_propagator_test(
+            9,
+            XEqYModZ::new,
+            three,
+            dom0_10,
+            (0, 5).to_interval(),
+            Unknown,
+            Unknown,
+            vec![(1, Bound), (2, Bound)],
+            true,
+        );
+        // x = 1 implies y != 0.
+        trinary_propagator_test(
+            10,
+            XEqYModZ::new,
+            one,
+            dom0_10,
+            dom3_5,
+            Unknown,
+            Unknown,
+            vec![(1, Bound)],
+            true,
+        );
+        trinary_propagator_test(
+            11,
+            XEqYModZ::new,
+            dom0_10,
+            dom0_10,
+            zero,
+            False,
+            False,
+            vec![],
+            false,
+        );
+        trinary_propagator_test(
+            12,
+            XEqYModZ::new,
+            zero,
+            zero,
+            dom3_5,
+            Unknown,
+            Unknown,
+            vec![],
+            true,
+        );
+    }
+
+    #[test]
+    fn x_neq_y_mod_z_test() {
+        let dom0_10 = (0, 10).to_interval();
+        let dom1_10 = (1, 10).to_interval();
+        let one = (1, 1).to_interval();
+        let three = (3, 3).to_interval();
+        let seven = (7, 7).to_interval();
+
+        trinary_propagator_test(
+            1,
+            x_neq_y_mod_z,
+            dom0_10,
+            dom0_10,
+            three,
+            Unknown,
+            Unknown,
+            vec![],
+            true,
+        );
+        trinary_propagator_test(
+            2,
+            x_neq_y_mod_z,
+            dom1_10,
+            seven,
+            three,
+            Unknown,
+            True,
+            vec![(0, Bound)],
+            true,
+        );
+        trinary_propagator_test(
+            3,
+            x_neq_y_mod_z,
+            one,
+            seven,
+            three,
+            False,
+            False,
+            vec![],
+            false,
+        );
+        trinary_propagator_test(
+            4,
+            x_neq_y_mod_z,
+            three,
+            seven,
+            three,
+            True,
+            True,
+            vec![],
+            true,
+        );
+    }
+
+    fn x_neq_y_mod_z(x: FDVar, y: FDVar, z: FDVar) -> XEqYModZ<VStoreFD> {
+        let mut p = XEqYModZ::new(x, y, z);
+        p.negated = true;
+        p
+    }
+}
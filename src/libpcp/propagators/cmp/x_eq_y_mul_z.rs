@@ -20,13 +20,14 @@ use logic::*;
 use model::*;
 use propagation::events::*;
 use propagation::*;
+use propagators::cmp::{sign_parts, XNeqYMulZ};
+use std::cmp::{max, min};
 use std::ops::*;
 use trilean::SKleene;
 use trilean::SKleene::*;
 
-/// TODO see K. Apt Section 6.5.4 for a better implementation.
-
 // x = y * z
+// The factors are pruned by division as described in K. Apt, Principles of Constraint Programming, Section 6.5.4.
 #[derive(Debug)]
 pub struct XEqYMulZ<VStore> {
     x: Var<VStore>,
@@ -59,9 +60,18 @@ impl<VStore> DisplayStateful<Model> for XEqYMulZ<VStore> {
     }
 }
 
-impl<VStore> NotFormula<VStore> for XEqYMulZ<VStore> {
+impl<VStore, Domain, Bound> NotFormula<VStore> for XEqYMulZ<VStore>
+where
+    VStore: VStoreConcept<Item = Domain> + 'static,
+    Domain: IntDomain<Item = Bound> + 'static,
+    Bound: IntBound + 'static,
+{
     fn not(&self) -> Formula<VStore> {
-        unimplemented!();
+        Box::new(XNeqYMulZ::new(
+            self.x.bclone(),
+            self.y.bclone(),
+            self.z.bclone(),
+        ))
     }
 }
 
@@ -91,17 +101,81 @@ where
     }
 }
 
-impl<VStore, Dom> Propagator<VStore> for XEqYMulZ<VStore>
+/// The values `y` such that `x = y * z` for some `x` and `z` in their domains.
+/// Returns `None` if `y` can take any value, which happens when `x` and `z` both contain 0.
+/// The division is computed separately on the negative and positive parts of `z` to exclude 0 from the divisors.
+fn divide<Domain, Bound>(x: &Domain, z: &Domain) -> Option<Domain>
 where
-    VStore: Collection<Item = Dom>,
-    Dom: Bounded + Intersection<Output = Dom> + Mul<Output = Dom>,
+    Domain: IntDomain<Item = Bound>,
+    Bound: IntBound,
+{
+    let zero = Bound::zero();
+    if x.contains(&zero) && z.contains(&zero) {
+        return None;
+    }
+    let mut quotient: Option<(Bound, Bound)> = None;
+    for part in sign_parts(z) {
+        // The quotient `x / z` is monotonic in `x` and `z` when `z` has a fixed sign: its extrema are reached at the bounds.
+        let corners = [
+            (x.lower(), part.lower()),
+            (x.lower(), part.upper()),
+            (x.upper(), part.lower()),
+            (x.upper(), part.upper()),
+        ];
+        let lb = corners
+            .iter()
+            .map(|(a, b)| div_ceil(a.clone(), b))
+            .min()
+            .unwrap();
+        let ub = corners.iter().map(|(a, b)| a.div_floor(b)).max().unwrap();
+        if lb <= ub {
+            quotient = Some(match quotient {
+                None => (lb, ub),
+                Some((l, u)) => (min(l, lb), max(u, ub)),
+            });
+        }
+    }
+    let quotient = match quotient {
+        None => Domain::empty(),
+        Some((lb, ub)) => Domain::new(lb, ub),
+    };
+    // `y` cannot be 0 if 0 is not in `x`.
+    if x.contains(&zero) {
+        Some(quotient)
+    } else {
+        Some(quotient.difference(&zero))
+    }
+}
+
+impl<VStore, Domain, Bound> Propagator<VStore> for XEqYMulZ<VStore>
+where
+    VStore: Collection<Item = Domain>,
+    Domain: IntDomain<Item = Bound>,
+    Bound: IntBound,
 {
     fn propagate(&mut self, store: &mut VStore) -> bool {
         let x = self.x.read(store);
         let y = self.y.read(store);
         let z = self.z.read(store);
-        let yz = y * z;
-        self.x.update(store, x.intersection(&yz))
+        let y = match divide(&x, &z) {
+            Some(quotient) => y.intersection(&quotient),
+            None => y,
+        };
+        if !self.y.update(store, y.clone()) {
+            return false;
+        }
+        let z = match divide(&x, &y) {
+            Some(quotient) => z.intersection(&quotient),
+            None => z,
+        };
+        if !self.z.update(store, z.clone()) {
+            return false;
+        }
+        self.x.update(store, x.intersection(&(y * z)))
+    }
+
+    fn cost(&self) -> PropagatorCost {
+        PropagatorCost::Ternary
     }
 }
 
@@ -118,8 +192,11 @@ impl<VStore> PropagatorDependencies<FDEvent> for XEqYMulZ<VStore> {
 mod test {
     use super::*;
     use interval::interval::*;
+    use interval::interval_set::*;
     use propagation::events::FDEvent::*;
+    use propagators::cmp::XEqYPlusZ;
     use propagators::test::*;
+    use search::test::*;
 
     #[test]
     fn x_eq_y_mul_z_test() {
@@ -144,6 +221,70 @@ mod test {
             vec![(0, Assignment)],
             true,
         );
+        // 6 = y * [2..3] so y is in [2..3].
+        x_eq_y_mul_z_test_one(
+            6,
+            (6, 6).to_interval(),
+            (1, 10).to_interval(),
+            (2, 3).to_interval(),
+            Unknown,
+            Unknown,
+            vec![(1, Bound)],
+            true,
+        );
+        // A negative product with a positive factor: the other factor is negative.
+        x_eq_y_mul_z_test_one(
+            7,
+            (-12, -6).to_interval(),
+            (-10, 10).to_interval(),
+            (2, 3).to_interval(),
+            Unknown,
+            Unknown,
+            vec![(1, Bound)],
+            true,
+        );
+        x_eq_y_mul_z_test_one(
+            8,
+            dom1_2,
+            (-3, 3).to_interval(),
+            (0, 0).to_interval(),
+            False,
+            False,
+            vec![],
+            false,
+        );
+        // Nothing can be deduced from 0 = y * z if y and z contain 0.
+        x_eq_y_mul_z_test_one(
+            9,
+            (0, 0).to_interval(),
+            (0, 4).to_interval(),
+            (-5, 5).to_interval(),
+            Unknown,
+            Unknown,
+            vec![],
+            true,
+        );
+        // The factors of a non-zero product are not zero.
+        x_eq_y_mul_z_test_one(
+            10,
+            (5, 5).to_interval(),
+            (0, 5).to_interval(),
+            (0, 5).to_interval(),
+            Unknown,
+            Unknown,
+            vec![(1, Bound), (2, Bound)],
+            true,
+        );
+        x_eq_y_mul_z_test_one(
+            11,
+            (1, 3).to_interval(),
+            (2, 2).to_interval(),
+            (-5, 5).to_interval(),
+            Unknown,
+            True,
+            vec![(0, Assignment), (2, Assignment)],
+            true,
+        );
     }
 
     fn x_eq_y_mul_z_test_one(
@@ -168,4 +309,74 @@ mod test {
             propagate_success,
         );
     }
+
+    /// Number of assignments of `x`, `y` and `z` in `[-3..3]` satisfying the formula built by `make`.
+    fn count_solutions<F>(make: F) -> usize
+    where
+        F: FnOnce(Vec<Var<VStore>>) -> Formula<VStore>,
+    {
+        let mut space = FDSpace::empty();
+        let vars = (0..3)
+            .map(|_| Box::new(space.vstore.alloc((-3, 3).to_interval_set())) as Var<VStore>)
+            .collect();
+        space.cstore.alloc(make(vars));
+        count_assignments(space, 3)
+    }
+
+    fn brute_force<F>(accept: F) -> usize
+    where
+        F: Fn(i32, i32, i32) -> bool,
+    {
+        let mut count = 0;
+        for x in -3..4 {
+            for y in -3..4 {
+                for z in -3..4 {
+                    if accept(x, y, z) {
+                        count += 1;
+                    }
+                }
+            }
+        }
+        count
+    }
+
+    fn mul(vars: &[Var<VStore>]) -> Formula<VStore> {
+        Box::new(XEqYMulZ::new(
+            vars[0].bclone(),
+            vars[1].bclone(),
+            vars[2].bclone(),
+        ))
+    }
+
+    #[test]
+    fn x_eq_y_mul_z_solutions_test() {
+        assert_eq!(
+            count_solutions(|vars| mul(&vars)),
+            brute_force(|x, y, z| x == y * z)
+        );
+        assert_eq!(
+            count_solutions(|vars| mul(&vars).not()),
+            brute_force(|x, y, z| x != y * z)
+        );
+        // x = y * z \/ x = y + z
+        assert_eq!(
+            count_solutions(|vars| {
+                let plus = Box::new(XEqYPlusZ::new(
+                    vars[0].bclone(),
+                    vars[1].bclone(),
+                    vars[2].bclone(),
+                ));
+                Box::new(Disjunction::new(vec![mul(&vars), plus]))
+            }),
+            brute_force(|x, y, z| x == y * z || x == y + z)
+        );
+        // not(x = y + z) /\ not(x = y * z)
+        assert_eq!(
+            count_solutions(|vars| {
+                let plus = XEqYPlusZ::new(vars[0].bclone(), vars[1].bclone(), vars[2].bclone());
+                Box::new(Conjunction::new(vec![plus.not(), mul(&vars).not()]))
+            }),
+            brute_force(|x, y, z| x != y * z && x != y + z)
+        );
+    }
 }
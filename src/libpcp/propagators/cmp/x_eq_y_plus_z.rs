@@ -19,11 +19,16 @@ use logic::*;
 use model::*;
 use propagation::events::*;
 use propagation::*;
-use propagators::cmp::{x_geq_y_plus_z, x_leq_y_plus_z, XGreaterEqYPlusZ, XLessEqYPlusZ};
+use propagators::cmp::{
+    x_geq_y_plus_z, x_leq_y_plus_z, XGreaterEqYPlusZ, XLessEqYPlusZ, XNeqYPlusZ,
+};
 use trilean::SKleene;
 
-#[derive(Clone, Debug)]
+#[derive(Debug)]
 pub struct XEqYPlusZ<VStore: Collection> {
+    x: Var<VStore>,
+    y: Var<VStore>,
+    z: Var<VStore>,
     geq: XGreaterEqYPlusZ<VStore>,
     leq: XLessEqYPlusZ<VStore>,
 }
@@ -37,7 +42,25 @@ where
     pub fn new(x: Var<VStore>, y: Var<VStore>, z: Var<VStore>) -> Self {
         XEqYPlusZ {
             geq: x_geq_y_plus_z(x.bclone(), y.bclone(), z.bclone()),
-            leq: x_leq_y_plus_z(x, y, z),
+            leq: x_leq_y_plus_z(x.bclone(), y.bclone(), z.bclone()),
+            x,
+            y,
+            z,
+        }
+    }
+}
+
+impl<VStore> Clone for XEqYPlusZ<VStore>
+where
+    VStore: Collection,
+{
+    fn clone(&self) -> Self {
+        XEqYPlusZ {
+            x: self.x.bclone(),
+            y: self.y.bclone(),
+            z: self.z.bclone(),
+            geq: self.geq.clone(),
+            leq: self.leq.clone(),
         }
     }
 }
@@ -47,11 +70,11 @@ where
     VStore: Collection,
 {
     fn display(&self, model: &Model) {
-        self.geq.x.display(model);
+        self.x.display(model);
         print!(" = ");
-        self.geq.y.display(model);
+        self.y.display(model);
         print!(" + ");
-        self.geq.z.display(model);
+        self.z.display(model);
         print!(" (decomposed)");
     }
 }
@@ -67,12 +90,18 @@ where
     }
 }
 
-impl<VStore> NotFormula<VStore> for XEqYPlusZ<VStore>
+impl<VStore, Domain, Bound> NotFormula<VStore> for XEqYPlusZ<VStore>
 where
-    VStore: Collection,
+    VStore: VStoreConcept<Item = Domain> + 'static,
+    Domain: IntDomain<Item = Bound> + 'static,
+    Bound: IntBound + 'static,
 {
     fn not(&self) -> Formula<VStore> {
-        unimplemented!()
+        Box::new(XNeqYPlusZ::new(
+            self.x.bclone(),
+            self.y.bclone(),
+            self.z.bclone(),
+        ))
     }
 }
 
@@ -85,6 +114,10 @@ where
     fn propagate(&mut self, store: &mut VStore) -> bool {
         self.geq.propagate(store) && self.leq.propagate(store)
     }
+
+    fn cost(&self) -> PropagatorCost {
+        PropagatorCost::Ternary
+    }
 }
 
 impl<VStore> PropagatorDependencies<FDEvent> for XEqYPlusZ<VStore>
@@ -116,6 +116,10 @@ where
                 .z
                 .update(store, z.strict_shrink_right(x.upper() - y.lower()))
     }
+
+    fn cost(&self) -> PropagatorCost {
+        PropagatorCost::Ternary
+    }
 }
 
 impl<VStore> PropagatorDependencies<FDEvent> for XGreaterYPlusZ<VStore> {
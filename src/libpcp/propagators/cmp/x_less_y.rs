@@ -106,6 +106,26 @@ where
         self.x.update(store, x.strict_shrink_right(y.upper()))
             && self.y.update(store, y.strict_shrink_left(x.lower()))
     }
+
+    /// The bounds are checked again after the propagation since it is not idempotent if `x` and `y` share a variable.
+    fn propagate_status(&mut self, store: &mut VStore) -> PropagatorStatus {
+        if !self.propagate(store) {
+            return PropagatorStatus::Failed;
+        }
+        let x = self.x.read(store);
+        let y = self.y.read(store);
+        if x.upper() < y.lower() {
+            PropagatorStatus::Subsumed
+        } else if x.upper() < y.upper() && x.lower() < y.lower() {
+            PropagatorStatus::Fixpoint
+        } else {
+            PropagatorStatus::NotFixpoint
+        }
+    }
+
+    fn cost(&self) -> PropagatorCost {
+        PropagatorCost::Binary
+    }
 }
 
 impl<VStore> PropagatorDependencies<FDEvent> for XLessY<VStore> {
@@ -122,6 +142,8 @@ mod test {
     use interval::interval::*;
     use propagation::events::FDEvent::*;
     use propagators::test::*;
+    use term::Addition;
+    use variable::VStoreFD;
 
     #[test]
     fn x_less_y_test() {
@@ -186,4 +208,38 @@ mod test {
             propagate_success,
         );
     }
+
+    fn x_less_y_status_test_one(
+        test_num: u32,
+        x: Interval<i32>,
+        y: Interval<i32>,
+        y_offset: Option<i32>,
+        expected: PropagatorStatus,
+    ) {
+        let mut vstore = VStoreFD::empty();
+        let x = Box::new(vstore.alloc(x)) as FDVar;
+        let y = match y_offset {
+            Some(offset) => Box::new(Addition::new(x.bclone(), offset)) as FDVar,
+            None => Box::new(vstore.alloc(y)) as FDVar,
+        };
+        let mut propagator = XLessY::new(x, y);
+        assert_eq!(
+            propagator.propagate_status(&mut vstore),
+            expected,
+            "Test {}.",
+            test_num
+        );
+    }
+
+    #[test]
+    fn x_less_y_status_test() {
+        let dom0_10 = (0, 10).to_interval();
+        let dom5_15 = (5, 15).to_interval();
+        let dom11_20 = (11, 20).to_interval();
+        x_less_y_status_test_one(1, dom0_10, dom5_15, None, PropagatorStatus::Fixpoint);
+        x_less_y_status_test_one(2, dom0_10, dom11_20, None, PropagatorStatus::Subsumed);
+        x_less_y_status_test_one(3, dom11_20, dom0_10, None, PropagatorStatus::Failed);
+        // `x < x + 1` shares `x` but is at its fixpoint.
+        x_less_y_status_test_one(4, dom0_10, dom0_10, Some(1), PropagatorStatus::Fixpoint);
+    }
 }
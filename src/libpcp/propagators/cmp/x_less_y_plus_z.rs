@@ -116,6 +116,10 @@ where
                 .z
                 .update(store, z.strict_shrink_left(x.lower() - y.upper()))
     }
+
+    fn cost(&self) -> PropagatorCost {
+        PropagatorCost::Ternary
+    }
 }
 
 impl<VStore> PropagatorDependencies<FDEvent> for XLessYPlusZ<VStore> {
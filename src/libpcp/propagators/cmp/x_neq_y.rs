@@ -76,7 +76,7 @@ where
 impl<VStore, Dom, Bound> Propagator<VStore> for XNeqY<VStore>
 where
     VStore: Collection<Item = Dom>,
-    Dom: Bounded<Item = Bound> + Cardinality + Difference<Bound, Output = Dom>,
+    Dom: Bounded<Item = Bound> + Cardinality + Difference<Bound, Output = Dom> + Disjoint,
     Bound: PartialOrd,
 {
     fn propagate(&mut self, store: &mut VStore) -> bool {
@@ -91,6 +91,26 @@ where
             true
         }
     }
+
+    /// Without a singleton, the propagation does not modify the domains and the propagator is at its fixpoint.
+    fn propagate_status(&mut self, store: &mut VStore) -> PropagatorStatus {
+        if !self.propagate(store) {
+            return PropagatorStatus::Failed;
+        }
+        let x = self.x.read(store);
+        let y = self.y.read(store);
+        if x.is_disjoint(&y) {
+            PropagatorStatus::Subsumed
+        } else if !x.is_singleton() && !y.is_singleton() {
+            PropagatorStatus::Fixpoint
+        } else {
+            PropagatorStatus::NotSubsumed
+        }
+    }
+
+    fn cost(&self) -> PropagatorCost {
+        PropagatorCost::Binary
+    }
 }
 
 impl<VStore> PropagatorDependencies<FDEvent> for XNeqY<VStore>
@@ -110,6 +130,7 @@ mod test {
     use propagation::events::FDEvent::*;
     use propagators::test::*;
     use trilean::SKleene::*;
+    use variable::VStoreFD;
 
     #[test]
     fn x_neq_y_test() {
@@ -152,4 +173,36 @@ mod test {
             propagate_success,
         );
     }
+
+    fn x_neq_y_status_test_one(
+        test_num: u32,
+        x: Interval<i32>,
+        y: Interval<i32>,
+        expected: PropagatorStatus,
+    ) {
+        let mut vstore = VStoreFD::empty();
+        let x = Box::new(vstore.alloc(x)) as FDVar;
+        let y = Box::new(vstore.alloc(y)) as FDVar;
+        let mut propagator = XNeqY::new(x, y);
+        assert_eq!(
+            propagator.propagate_status(&mut vstore),
+            expected,
+            "Test {}.",
+            test_num
+        );
+    }
+
+    #[test]
+    fn x_neq_y_status_test() {
+        let dom0_10 = (0, 10).to_interval();
+        let dom5_15 = (5, 15).to_interval();
+        let dom11_20 = (11, 20).to_interval();
+        let zero = (0, 0).to_interval();
+        let one = (1, 1).to_interval();
+        x_neq_y_status_test_one(1, dom0_10, dom5_15, PropagatorStatus::Fixpoint);
+        x_neq_y_status_test_one(2, dom0_10, dom11_20, PropagatorStatus::Subsumed);
+        x_neq_y_status_test_one(3, zero, dom0_10, PropagatorStatus::Subsumed);
+        x_neq_y_status_test_one(4, one, dom0_10, PropagatorStatus::NotSubsumed);
+        x_neq_y_status_test_one(5, one, one, PropagatorStatus::Failed);
+    }
 }
@@ -0,0 +1,254 @@
+// Copyright 2018 Pierre Talbot (IRCAM)
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+
+//     http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use concept::*;
+use gcollections::*;
+use kernel::*;
+use logic::*;
+use model::*;
+use propagation::events::*;
+use propagation::*;
+use propagators::cmp::XEqYMulZ;
+use trilean::SKleene;
+use trilean::SKleene::*;
+
+// x != y * z
+#[derive(Debug)]
+pub struct XNeqYMulZ<VStore> {
+    x: Var<VStore>,
+    y: Var<VStore>,
+    z: Var<VStore>,
+}
+
+impl<VStore> XNeqYMulZ<VStore> {
+    pub fn new(x: Var<VStore>, y: Var<VStore>, z: Var<VStore>) -> Self {
+        XNeqYMulZ { x, y, z }
+    }
+}
+
+impl<VStore> Clone for XNeqYMulZ<VStore>
+where
+    VStore: Collection,
+{
+    fn clone(&self) -> Self {
+        XNeqYMulZ::new(self.x.bclone(), self.y.bclone(), self.z.bclone())
+    }
+}
+
+impl<VStore> DisplayStateful<Model> for XNeqYMulZ<VStore> {
+    fn display(&self, model: &Model) {
+        self.x.display(model);
+        print!(" != ");
+        self.y.display(model);
+        print!(" * ");
+        self.z.display(model);
+    }
+}
+
+impl<VStore, Domain, Bound> NotFormula<VStore> for XNeqYMulZ<VStore>
+where
+    VStore: VStoreConcept<Item = Domain> + 'static,
+    Domain: IntDomain<Item = Bound> + 'static,
+    Bound: IntBound + 'static,
+{
+    fn not(&self) -> Formula<VStore> {
+        Box::new(XEqYMulZ::new(
+            self.x.bclone(),
+            self.y.bclone(),
+            self.z.bclone(),
+        ))
+    }
+}
+
+impl<VStore, Domain, Bound> Subsumption<VStore> for XNeqYMulZ<VStore>
+where
+    VStore: Collection<Item = Domain>,
+    Domain: IntDomain<Item = Bound>,
+    Bound: IntBound,
+{
+    fn is_subsumed(&self, store: &VStore) -> SKleene {
+        // True: x and y * z do not overlap.
+        // False: x and y * z are singletons and equal.
+        // Unknown: Everything else.
+        let x = self.x.read(store);
+        let yz = self.y.read(store) * self.z.read(store);
+        if !yz.overlap(&x) {
+            True
+        } else if yz.is_singleton() && x.is_singleton() {
+            False
+        } else {
+            Unknown
+        }
+    }
+}
+
+/// Removes `x / other` from `factor` when `x = other * factor` has an integer solution.
+/// If `other` is 0, it fails when `x` is 0 since every `factor` satisfies the equality.
+fn prune_factor<VStore, Domain, Bound>(
+    store: &mut VStore,
+    factor: &mut Var<VStore>,
+    x: Bound,
+    other: Bound,
+) -> bool
+where
+    VStore: Collection<Item = Domain>,
+    Domain: IntDomain<Item = Bound>,
+    Bound: IntBound,
+{
+    if other.is_zero() {
+        !x.is_zero()
+    } else if x.mod_floor(&other).is_zero() {
+        let dom = factor.read(store).difference(&x.div_floor(&other));
+        factor.update(store, dom)
+    } else {
+        true
+    }
+}
+
+impl<VStore, Domain, Bound> Propagator<VStore> for XNeqYMulZ<VStore>
+where
+    VStore: Collection<Item = Domain>,
+    Domain: IntDomain<Item = Bound>,
+    Bound: IntBound,
+{
+    /// When two variables are assigned, the value making the equality true is removed from the third one.
+    fn propagate(&mut self, store: &mut VStore) -> bool {
+        let x = self.x.read(store);
+        let y = self.y.read(store);
+        let z = self.z.read(store);
+        if y.is_singleton() && z.is_singleton() {
+            self.x.update(store, x.difference(&(y.lower() * z.lower())))
+        } else if x.is_singleton() && y.is_singleton() {
+            prune_factor(store, &mut self.z, x.lower(), y.lower())
+        } else if x.is_singleton() && z.is_singleton() {
+            prune_factor(store, &mut self.y, x.lower(), z.lower())
+        } else {
+            true
+        }
+    }
+
+    fn cost(&self) -> PropagatorCost {
+        PropagatorCost::Ternary
+    }
+}
+
+impl<VStore> PropagatorDependencies<FDEvent> for XNeqYMulZ<VStore> {
+    fn dependencies(&self) -> Vec<(usize, FDEvent)> {
+        let mut deps = self.x.dependencies(FDEvent::Assignment);
+        deps.append(&mut self.y.dependencies(FDEvent::Assignment));
+        deps.append(&mut self.z.dependencies(FDEvent::Assignment));
+        deps
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use interval::interval::*;
+    use propagation::events::FDEvent::*;
+    use propagators::test::*;
+
+    #[test]
+    fn x_neq_y_mul_z_test() {
+        let dom0_5 = (0, 5).to_interval();
+        let dom3_5 = (3, 5).to_interval();
+        let dom6_10 = (6, 10).to_interval();
+        let zero = (0, 0).to_interval();
+        let one = (1, 1).to_interval();
+        let two = (2, 2).to_interval();
+        let three = (3, 3).to_interval();
+        let six = (6, 6).to_interval();
+        let seven = (7, 7).to_interval();
+
+        trinary_propagator_test(
+            1,
+            XNeqYMulZ::new,
+            dom6_10,
+            two,
+            three,
+            Unknown,
+            True,
+            vec![(0, Bound)],
+            true,
+        );
+        trinary_propagator_test(
+            2,
+            XNeqYMulZ::new,
+            six,
+            two,
+            three,
+            False,
+            False,
+            vec![],
+            false,
+        );
+        trinary_propagator_test(
+            3,
+            XNeqYMulZ::new,
+            six,
+            two,
+            dom3_5,
+            Unknown,
+            True,
+            vec![(2, Bound)],
+            true,
+        );
+        trinary_propagator_test(
+            4,
+            XNeqYMulZ::new,
+            six,
+            dom3_5,
+            two,
+            Unknown,
+            True,
+            vec![(1, Bound)],
+            true,
+        );
+        // 7 is not a multiple of 2.
+        trinary_propagator_test(
+            5,
+            XNeqYMulZ::new,
+            seven,
+            two,
+            dom3_5,
+            Unknown,
+            Unknown,
+            vec![],
+            true,
+        );
+        // 0 = 0 * z for every z.
+        trinary_propagator_test(
+            6,
+            XNeqYMulZ::new,
+            zero,
+            zero,
+            dom0_5,
+            False,
+            False,
+            vec![],
+            false,
+        );
+        trinary_propagator_test(
+            7,
+            XNeqYMulZ::new,
+            one,
+            zero,
+            dom0_5,
+            True,
+            True,
+            vec![],
+            true,
+        );
+    }
+}
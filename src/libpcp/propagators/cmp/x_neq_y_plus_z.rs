@@ -0,0 +1,207 @@
+// Copyright 2018 Pierre Talbot (IRCAM)
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+
+//     http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use concept::*;
+use gcollections::*;
+use kernel::*;
+use logic::*;
+use model::*;
+use propagation::events::*;
+use propagation::*;
+use propagators::cmp::XEqYPlusZ;
+use trilean::SKleene;
+use trilean::SKleene::*;
+
+// x != y + z
+#[derive(Debug)]
+pub struct XNeqYPlusZ<VStore> {
+    x: Var<VStore>,
+    y: Var<VStore>,
+    z: Var<VStore>,
+}
+
+impl<VStore> XNeqYPlusZ<VStore> {
+    pub fn new(x: Var<VStore>, y: Var<VStore>, z: Var<VStore>) -> Self {
+        XNeqYPlusZ { x, y, z }
+    }
+}
+
+impl<VStore> Clone for XNeqYPlusZ<VStore>
+where
+    VStore: Collection,
+{
+    fn clone(&self) -> Self {
+        XNeqYPlusZ::new(self.x.bclone(), self.y.bclone(), self.z.bclone())
+    }
+}
+
+impl<VStore> DisplayStateful<Model> for XNeqYPlusZ<VStore> {
+    fn display(&self, model: &Model) {
+        self.x.display(model);
+        print!(" != ");
+        self.y.display(model);
+        print!(" + ");
+        self.z.display(model);
+    }
+}
+
+impl<VStore, Domain, Bound> NotFormula<VStore> for XNeqYPlusZ<VStore>
+where
+    VStore: VStoreConcept<Item = Domain> + 'static,
+    Domain: IntDomain<Item = Bound> + 'static,
+    Bound: IntBound + 'static,
+{
+    fn not(&self) -> Formula<VStore> {
+        Box::new(XEqYPlusZ::new(
+            self.x.bclone(),
+            self.y.bclone(),
+            self.z.bclone(),
+        ))
+    }
+}
+
+impl<VStore, Domain, Bound> Subsumption<VStore> for XNeqYPlusZ<VStore>
+where
+    VStore: Collection<Item = Domain>,
+    Domain: IntDomain<Item = Bound>,
+    Bound: IntBound,
+{
+    fn is_subsumed(&self, store: &VStore) -> SKleene {
+        // True: x and y + z do not overlap.
+        // False: x and y + z are singletons and equal.
+        // Unknown: Everything else.
+        let x = self.x.read(store);
+        let y = self.y.read(store);
+        let z = self.z.read(store);
+        if x.upper() < y.lower() + z.lower() || x.lower() > y.upper() + z.upper() {
+            True
+        } else if x.is_singleton() && y.is_singleton() && z.is_singleton() {
+            False
+        } else {
+            Unknown
+        }
+    }
+}
+
+impl<VStore, Domain, Bound> Propagator<VStore> for XNeqYPlusZ<VStore>
+where
+    VStore: Collection<Item = Domain>,
+    Domain: IntDomain<Item = Bound>,
+    Bound: IntBound,
+{
+    /// When two variables are assigned, the value making the equality true is removed from the third one.
+    fn propagate(&mut self, store: &mut VStore) -> bool {
+        let x = self.x.read(store);
+        let y = self.y.read(store);
+        let z = self.z.read(store);
+        if y.is_singleton() && z.is_singleton() {
+            self.x.update(store, x.difference(&(y.lower() + z.lower())))
+        } else if x.is_singleton() && y.is_singleton() {
+            self.z.update(store, z.difference(&(x.lower() - y.lower())))
+        } else if x.is_singleton() && z.is_singleton() {
+            self.y.update(store, y.difference(&(x.lower() - z.lower())))
+        } else {
+            true
+        }
+    }
+
+    fn cost(&self) -> PropagatorCost {
+        PropagatorCost::Ternary
+    }
+}
+
+impl<VStore> PropagatorDependencies<FDEvent> for XNeqYPlusZ<VStore> {
+    fn dependencies(&self) -> Vec<(usize, FDEvent)> {
+        let mut deps = self.x.dependencies(FDEvent::Assignment);
+        deps.append(&mut self.y.dependencies(FDEvent::Assignment));
+        deps.append(&mut self.z.dependencies(FDEvent::Assignment));
+        deps
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use interval::interval::*;
+    use propagation::events::FDEvent::*;
+    use propagators::test::*;
+
+    #[test]
+    fn x_neq_y_plus_z_test() {
+        let dom0_10 = (0, 10).to_interval();
+        let dom3_10 = (3, 10).to_interval();
+        let dom3_6 = (3, 6).to_interval();
+        let one = (1, 1).to_interval();
+        let two = (2, 2).to_interval();
+        let three = (3, 3).to_interval();
+        let five = (5, 5).to_interval();
+
+        trinary_propagator_test(
+            1,
+            XNeqYPlusZ::new,
+            dom0_10,
+            dom0_10,
+            dom0_10,
+            Unknown,
+            Unknown,
+            vec![],
+            true,
+        );
+        trinary_propagator_test(
+            2,
+            XNeqYPlusZ::new,
+            dom3_10,
+            one,
+            two,
+            Unknown,
+            True,
+            vec![(0, Bound)],
+            true,
+        );
+        trinary_propagator_test(
+            3,
+            XNeqYPlusZ::new,
+            three,
+            one,
+            two,
+            False,
+            False,
+            vec![],
+            false,
+        );
+        trinary_propagator_test(
+            4,
+            XNeqYPlusZ::new,
+            five,
+            two,
+            dom3_6,
+            Unknown,
+            True,
+            vec![(2, Bound)],
+            true,
+        );
+        trinary_propagator_test(
+            5,
+            XNeqYPlusZ::new,
+            five,
+            dom3_6,
+            two,
+            Unknown,
+            True,
+            vec![(1, Bound)],
+            true,
+        );
+        trinary_propagator_test(6, XNeqYPlusZ::new, five, one, two, True, True, vec![], true);
+    }
+}
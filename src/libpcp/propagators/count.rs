@@ -0,0 +1,600 @@
+// Copyright 2018 Pierre Talbot (IRCAM)
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+
+//     http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! The counting constraint `among(vars, values) op count` where `among(vars, values)` is the number of variables taking a value in `values` and `op` is one of `=`, `<=`, `>=` or `!=`.
+//! The filtering compares `count` with the number of variables that are definitely in `values` and the number of variables that are possibly in `values`.
+
+use concept::*;
+use gcollections::*;
+use kernel::*;
+use logic::*;
+use model::*;
+use propagation::events::*;
+use propagation::*;
+use propagators::values::{naturals, restrict};
+use std::fmt::Debug;
+use term::*;
+use trilean::SKleene;
+use trilean::SKleene::*;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CountOp {
+    Eq,
+    Leq,
+    Geq,
+    Neq,
+}
+
+#[derive(Debug)]
+pub struct Among<VStore, Bound> {
+    vars: Vec<Var<VStore>>,
+    /// The counted values in increasing order.
+    values: Vec<Bound>,
+    op: CountOp,
+    count: Var<VStore>,
+    /// `naturals[i]` is the integer `i` for `i` in `[0..vars.len()]`.
+    naturals: Vec<Bound>,
+}
+
+pub type Count<VStore, Bound> = Among<VStore, Bound>;
+
+impl<VStore, Domain, Bound> Among<VStore, Bound>
+where
+    VStore: Collection<Item = Domain>,
+    Domain: IntDomain<Item = Bound>,
+    Bound: IntBound,
+{
+    /// `among(vars, values) = count`
+    pub fn new(vars: Vec<Var<VStore>>, values: Vec<Bound>, count: Var<VStore>) -> Self {
+        Among::with_op(vars, values, CountOp::Eq, count)
+    }
+
+    /// `among(vars, values) op count`
+    pub fn with_op(
+        vars: Vec<Var<VStore>>,
+        mut values: Vec<Bound>,
+        op: CountOp,
+        count: Var<VStore>,
+    ) -> Self {
+        values.sort();
+        values.dedup();
+        let naturals = naturals(vars.len());
+        Among {
+            vars,
+            values,
+            op,
+            count,
+            naturals,
+        }
+    }
+
+    /// The number of variables definitely and possibly taking a value in `values`.
+    fn occurrences(&self, vstore: &VStore) -> (usize, usize) {
+        self.vars
+            .iter()
+            .map(|v| v.read(vstore))
+            .fold((0, 0), |(sure, possible), dom| {
+                if restrict(dom.clone(), &self.values).is_empty() {
+                    (sure, possible)
+                } else if self.exclude(dom).is_empty() {
+                    (sure + 1, possible + 1)
+                } else {
+                    (sure, possible + 1)
+                }
+            })
+    }
+
+    /// Removes the values from `dom`, the removal is repeated since interval domains can only be shrunk on their bounds.
+    /// The result is empty if and only if every value of `dom` is in `values`.
+    fn exclude(&self, mut dom: Domain) -> Domain {
+        loop {
+            let next = self.values.iter().fold(dom.clone(), |d, v| d.difference(v));
+            if next.size() == dom.size() {
+                return next;
+            }
+            dom = next;
+        }
+    }
+
+    /// Forces the variables that are not decided yet to take (`inside`) or to avoid (`!inside`) the values.
+    fn decide_undecided(&mut self, vstore: &mut VStore, inside: bool) -> bool {
+        for i in 0..self.vars.len() {
+            let dom = self.vars[i].read(vstore);
+            let counted = restrict(dom.clone(), &self.values);
+            let excluded = self.exclude(dom);
+            if !counted.is_empty() && !excluded.is_empty() {
+                let dom = if inside { counted } else { excluded };
+                if !self.vars[i].update(vstore, dom) {
+                    return false;
+                }
+            }
+        }
+        true
+    }
+
+    fn propagate_neq(&mut self, vstore: &mut VStore, sure: usize, possible: usize) -> bool {
+        let count = self.count.read(vstore);
+        if sure == possible {
+            let count = count.difference(&self.naturals[sure]);
+            self.count.update(vstore, count)
+        } else if sure + 1 == possible && count.is_singleton() {
+            // The only undecided variable must not change the number of occurrences into `count`.
+            if count.lower() == self.naturals[sure] {
+                self.decide_undecided(vstore, true)
+            } else if count.lower() == self.naturals[possible] {
+                self.decide_undecided(vstore, false)
+            } else {
+                true
+            }
+        } else {
+            true
+        }
+    }
+}
+
+/// `among(vars, [value]) = count`
+pub fn count<VStore, Domain, Bound>(
+    vars: Vec<Var<VStore>>,
+    value: Bound,
+    count: Var<VStore>,
+) -> Count<VStore, Bound>
+where
+    VStore: Collection<Item = Domain>,
+    Domain: IntDomain<Item = Bound>,
+    Bound: IntBound,
+{
+    Among::new(vars, vec![value], count)
+}
+
+/// At most `n` variables are equal to `value`.
+pub fn at_most<VStore, Domain, Bound>(
+    vars: Vec<Var<VStore>>,
+    value: Bound,
+    n: Var<VStore>,
+) -> Count<VStore, Bound>
+where
+    VStore: Collection<Item = Domain>,
+    Domain: IntDomain<Item = Bound>,
+    Bound: IntBound,
+{
+    Among::with_op(vars, vec![value], CountOp::Leq, n)
+}
+
+/// At least `n` variables are equal to `value`.
+pub fn at_least<VStore, Domain, Bound>(
+    vars: Vec<Var<VStore>>,
+    value: Bound,
+    n: Var<VStore>,
+) -> Count<VStore, Bound>
+where
+    VStore: Collection<Item = Domain>,
+    Domain: IntDomain<Item = Bound>,
+    Bound: IntBound,
+{
+    Among::with_op(vars, vec![value], CountOp::Geq, n)
+}
+
+impl<VStore, Bound> Clone for Among<VStore, Bound>
+where
+    VStore: Collection,
+    Bound: Clone,
+{
+    fn clone(&self) -> Self {
+        Among {
+            vars: self.vars.iter().map(|v| v.bclone()).collect(),
+            values: self.values.clone(),
+            op: self.op,
+            count: self.count.bclone(),
+            naturals: self.naturals.clone(),
+        }
+    }
+}
+
+impl<VStore, Bound> DisplayStateful<Model> for Among<VStore, Bound>
+where
+    Bound: Debug,
+{
+    fn display(&self, model: &Model) {
+        print!("among(");
+        model.display_array(&self.vars);
+        print!(", {:?})", self.values);
+        let op = match self.op {
+            CountOp::Eq => "=",
+            CountOp::Leq => "<=",
+            CountOp::Geq => ">=",
+            CountOp::Neq => "!=",
+        };
+        print!(" {} ", op);
+        self.count.display(model);
+    }
+}
+
+impl<VStore, Domain, Bound> NotFormula<VStore> for Among<VStore, Bound>
+where
+    VStore: VStoreConcept<Item = Domain> + 'static,
+    Domain: IntDomain<Item = Bound> + 'static,
+    Bound: IntBound + 'static,
+{
+    fn not(&self) -> Formula<VStore> {
+        let (op, count): (_, Var<VStore>) = match self.op {
+            CountOp::Eq => (CountOp::Neq, self.count.bclone()),
+            CountOp::Neq => (CountOp::Eq, self.count.bclone()),
+            CountOp::Leq => (
+                CountOp::Geq,
+                Box::new(Addition::new(self.count.bclone(), Bound::one())),
+            ),
+            CountOp::Geq => (
+                CountOp::Leq,
+                Box::new(Addition::new(self.count.bclone(), -Bound::one())),
+            ),
+        };
+        let mut not = self.clone();
+        not.op = op;
+        not.count = count;
+        Box::new(not)
+    }
+}
+
+impl<VStore, Domain, Bound> Subsumption<VStore> for Among<VStore, Bound>
+where
+    VStore: Collection<Item = Domain>,
+    Domain: IntDomain<Item = Bound>,
+    Bound: IntBound,
+{
+    fn is_subsumed(&self, vstore: &VStore) -> SKleene {
+        // False: `count` cannot be equal to (resp. greater, smaller) any possible number of occurrences.
+        // True: `count` is equal to (resp. greater, smaller) every possible number of occurrences.
+        // Unknown: Everything else.
+        let (sure, possible) = self.occurrences(vstore);
+        let count = self.count.read(vstore);
+        let sure = self.naturals[sure].clone();
+        let possible = self.naturals[possible].clone();
+        let eq = || {
+            if !count.overlap(&Domain::new(sure.clone(), possible.clone())) {
+                False
+            } else if sure == possible && count.is_singleton() {
+                True
+            } else {
+                Unknown
+            }
+        };
+        match self.op {
+            CountOp::Eq => eq(),
+            CountOp::Neq => !eq(),
+            CountOp::Leq if possible <= count.lower() => True,
+            CountOp::Leq if sure > count.upper() => False,
+            CountOp::Geq if sure >= count.upper() => True,
+            CountOp::Geq if possible < count.lower() => False,
+            _ => Unknown,
+        }
+    }
+}
+
+impl<VStore, Domain, Bound> Propagator<VStore> for Among<VStore, Bound>
+where
+    VStore: Collection<Item = Domain>,
+    Domain: IntDomain<Item = Bound>,
+    Bound: IntBound,
+{
+    /// `count` is bounded by the number of variables definitely and possibly in `values`.
+    /// When one of these bounds is reached, the undecided variables are all excluded from (or restricted to) `values`.
+    fn propagate(&mut self, vstore: &mut VStore) -> bool {
+        let (sure, possible) = self.occurrences(vstore);
+        if self.op == CountOp::Neq {
+            return self.propagate_neq(vstore, sure, possible);
+        }
+        let mut count = self.count.read(vstore);
+        if self.op != CountOp::Geq {
+            count = count.shrink_left(self.naturals[sure].clone());
+        }
+        if self.op != CountOp::Leq {
+            count = count.shrink_right(self.naturals[possible].clone());
+        }
+        if !self.count.update(vstore, count.clone()) {
+            return false;
+        }
+        if sure == possible {
+            true
+        } else if self.op != CountOp::Geq && count.upper() == self.naturals[sure] {
+            self.decide_undecided(vstore, false)
+        } else if self.op != CountOp::Leq && count.lower() == self.naturals[possible] {
+            self.decide_undecided(vstore, true)
+        } else {
+            true
+        }
+    }
+}
+
+impl<VStore, Bound> PropagatorDependencies<FDEvent> for Among<VStore, Bound> {
+    fn dependencies(&self) -> Vec<(usize, FDEvent)> {
+        let mut deps: Vec<_> = self
+            .vars
+            .iter()
+            .flat_map(|v| v.dependencies(FDEvent::Inner))
+            .collect();
+        deps.append(&mut self.count.dependencies(FDEvent::Bound));
+        deps
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use gcollections::ops::*;
+    use interval::interval::*;
+    use interval::interval_set::*;
+    use propagation::events::FDEvent::*;
+    use propagators::test::*;
+    use search::test::*;
+    use variable::VStoreFD;
+
+    #[test]
+    fn among_test() {
+        let zero = (0, 0).to_interval();
+        let one = (1, 1).to_interval();
+        let two = (2, 2).to_interval();
+        let three = (3, 3).to_interval();
+        let dom0_1 = (0, 1).to_interval();
+        let dom0_2 = (0, 2).to_interval();
+        let dom0_3 = (0, 3).to_interval();
+        let dom1_2 = (1, 2).to_interval();
+        let dom2_3 = (2, 3).to_interval();
+
+        // among([x1, x2, x3], [1, 2]) = count
+        among_test_one(
+            1,
+            vec![dom0_3, dom0_3, dom0_3, dom0_3],
+            CountOp::Eq,
+            Unknown,
+            Unknown,
+            vec![],
+            true,
+        );
+        among_test_one(
+            2,
+            vec![one, dom0_3, zero, dom0_3],
+            CountOp::Eq,
+            Unknown,
+            Unknown,
+            vec![(3, Bound)],
+            true,
+        );
+        among_test_one(
+            3,
+            vec![one, dom1_2, zero, dom0_3],
+            CountOp::Eq,
+            Unknown,
+            True,
+            vec![(3, Assignment)],
+            true,
+        );
+        among_test_one(
+            4,
+            vec![one, dom0_2, three, one],
+            CountOp::Eq,
+            Unknown,
+            True,
+            vec![(1, Assignment)],
+            true,
+        );
+        among_test_one(
+            5,
+            vec![zero, dom0_3, dom0_3, two],
+            CountOp::Eq,
+            Unknown,
+            True,
+            vec![(1, Bound), (2, Bound)],
+            true,
+        );
+        among_test_one(
+            6,
+            vec![zero, dom2_3, dom0_1, two],
+            CountOp::Eq,
+            Unknown,
+            True,
+            vec![(1, Assignment), (2, Assignment)],
+            true,
+        );
+        among_test_one(
+            7,
+            vec![one, two, three, dom0_1],
+            CountOp::Eq,
+            False,
+            False,
+            vec![],
+            false,
+        );
+        // among([x1, x2, x3], [1, 2]) <= count
+        among_test_one(
+            8,
+            vec![one, two, dom0_3, dom0_3],
+            CountOp::Leq,
+            Unknown,
+            Unknown,
+            vec![(3, Bound)],
+            true,
+        );
+        among_test_one(
+            9,
+            vec![one, dom0_2, dom0_2, one],
+            CountOp::Leq,
+            Unknown,
+            True,
+            vec![(1, Assignment), (2, Assignment)],
+            true,
+        );
+        among_test_one(
+            10,
+            vec![one, two, dom0_3, one],
+            CountOp::Leq,
+            False,
+            False,
+            vec![],
+            false,
+        );
+        among_test_one(
+            11,
+            vec![one, dom0_3, zero, three],
+            CountOp::Leq,
+            True,
+            True,
+            vec![],
+            true,
+        );
+        // among([x1, x2, x3], [1, 2]) >= count
+        among_test_one(
+            12,
+            vec![one, dom0_3, zero, dom0_3],
+            CountOp::Geq,
+            Unknown,
+            Unknown,
+            vec![(3, Bound)],
+            true,
+        );
+        among_test_one(
+            13,
+            vec![one, dom0_3, dom0_3, three],
+            CountOp::Geq,
+            Unknown,
+            True,
+            vec![(1, Bound), (2, Bound)],
+            true,
+        );
+        among_test_one(
+            14,
+            vec![one, two, dom0_3, zero],
+            CountOp::Geq,
+            True,
+            True,
+            vec![],
+            true,
+        );
+        // among([x1, x2, x3], [1, 2]) != count
+        among_test_one(
+            15,
+            vec![one, two, zero, dom2_3],
+            CountOp::Neq,
+            Unknown,
+            True,
+            vec![(3, Assignment)],
+            true,
+        );
+        among_test_one(
+            16,
+            vec![one, two, dom0_3, two],
+            CountOp::Neq,
+            Unknown,
+            True,
+            vec![(2, Bound)],
+            true,
+        );
+        among_test_one(
+            17,
+            vec![one, two, zero, two],
+            CountOp::Neq,
+            False,
+            False,
+            vec![],
+            false,
+        );
+        among_test_one(
+            18,
+            vec![one, dom0_3, dom0_3, two],
+            CountOp::Neq,
+            Unknown,
+            Unknown,
+            vec![],
+            true,
+        );
+    }
+
+    fn among_test_one(
+        test_num: u32,
+        doms: Vec<Interval<i32>>,
+        op: CountOp,
+        before: SKleene,
+        after: SKleene,
+        delta_expected: Vec<(usize, FDEvent)>,
+        propagate_success: bool,
+    ) {
+        nary_propagator_test(
+            test_num,
+            |mut vars: Vec<FDVar>| {
+                let count = vars.pop().unwrap();
+                Among::with_op(vars, vec![2, 1], op, count)
+            },
+            doms,
+            before,
+            after,
+            delta_expected,
+            propagate_success,
+        );
+    }
+
+    #[test]
+    fn among_interval_holes_test() {
+        // `x` in `[1..3]` is not surely in `[1, 3]` although it cannot be restricted to these values.
+        let mut vstore = VStoreFD::empty();
+        let x = Box::new(vstore.alloc((1, 3).to_interval())) as FDVar;
+        let c = Box::new(vstore.alloc((0, 0).to_interval())) as FDVar;
+        let mut among = Among::new(vec![x.bclone()], vec![1, 3], c);
+        assert_eq!(among.is_subsumed(&vstore), Unknown);
+        assert!(among.propagate(&mut vstore));
+        assert_eq!(x.read(&vstore), (2, 2).to_interval());
+        assert_eq!(among.is_subsumed(&vstore), True);
+    }
+
+    /// Number of solutions of `among([x1, x2, x3], [1, 3]) op n` where the variables are in `[0..3]`.
+    fn count_among(op: CountOp, negated: bool) -> usize {
+        let mut space = FDSpace::empty();
+        let mut vars: Vec<_> = (0..4)
+            .map(|_| Box::new(space.vstore.alloc((0, 3).to_interval_set())) as Var<VStore>)
+            .collect();
+        let n = vars.pop().unwrap();
+        let among = Among::with_op(vars, vec![1, 3], op, n);
+        if negated {
+            space.cstore.alloc(among.not());
+        } else {
+            space.cstore.alloc(Box::new(among));
+        }
+        count_assignments(space, 4)
+    }
+
+    fn brute_force<F: Fn(i32, i32) -> bool>(accept: F) -> usize {
+        let mut solutions = 0;
+        for x1 in 0..4 {
+            for x2 in 0..4 {
+                for x3 in 0..4 {
+                    let occurrences = [x1, x2, x3].iter().filter(|&&x| x == 1 || x == 3).count();
+                    for n in 0..4 {
+                        if accept(occurrences as i32, n) {
+                            solutions += 1;
+                        }
+                    }
+                }
+            }
+        }
+        solutions
+    }
+
+    #[test]
+    fn among_solutions_test() {
+        assert_eq!(count_among(CountOp::Eq, false), brute_force(|o, n| o == n));
+        assert_eq!(count_among(CountOp::Leq, false), brute_force(|o, n| o <= n));
+        assert_eq!(count_among(CountOp::Geq, false), brute_force(|o, n| o >= n));
+        assert_eq!(count_among(CountOp::Neq, false), brute_force(|o, n| o != n));
+        assert_eq!(count_among(CountOp::Leq, true), brute_force(|o, n| o > n));
+        assert_eq!(count_among(CountOp::Geq, true), brute_force(|o, n| o < n));
+    }
+}
@@ -12,17 +12,34 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+//! The `Cumulative` constraint ensures that, at any time, the tasks running do not use more resources than the capacity available.
+//! It can be used as a propagator (allocated in the constraint store) or decomposed with `join` into more primitive constraints.
+//! The propagator is based on the compulsory parts of the tasks (time-tabling) and can optionally perform energetic reasoning as described in `Constraint-Based Scheduling`, Baptiste and al., 2001.
+//! The durations and resources are variables and we reason on their lower bounds.
+
 use concept::*;
+use gcollections::*;
+use kernel::*;
 use logic::*;
+use model::*;
+use propagation::events::*;
+use propagation::*;
 use propagators::*;
+use std::cmp::{max, min};
 use term::*;
+use trilean::SKleene;
+use trilean::SKleene::*;
 
+#[derive(Debug)]
 pub struct Cumulative<VStore> {
     starts: Vec<Var<VStore>>,
     durations: Vec<Var<VStore>>,
     resources: Vec<Var<VStore>>,
     capacity: Var<VStore>,
     intermediate: Vec<Vec<usize>>, // Given intermediate[j][i], if i left-overlap j, then it contains the number of resources used by i.
+    energetic: bool,
+    /// The negation is only checked: it fails when the tasks can never exceed the capacity.
+    negated: bool,
 }
 
 impl<VStore> Cumulative<VStore> {
@@ -41,8 +58,16 @@ impl<VStore> Cumulative<VStore> {
             resources,
             capacity,
             intermediate: vec![],
+            energetic: false,
+            negated: false,
         }
     }
+
+    /// The propagator also performs energetic reasoning, it prunes more than time-tabling but runs in O(n³).
+    pub fn with_energetic_reasoning(mut self) -> Self {
+        self.energetic = true;
+        self
+    }
 }
 
 impl<VStore, Domain, Bound> Cumulative<VStore>
@@ -60,6 +85,10 @@ where
     where
         CStore: IntCStore<VStore> + 'static,
     {
+        assert!(
+            !self.negated,
+            "The negation of `Cumulative` cannot be decomposed."
+        );
         let tasks = self.starts.len();
         // Special case where only one task needs to be scheduled.
         if tasks == 1 {
@@ -73,7 +102,7 @@ where
                 for i in 0..tasks {
                     if i != j {
                         // conj <-> s[i] <= s[j] /\ s[j] < s[i] + d[i]
-                        let conj = Box::new(Conjunction::new(vec![
+                        let conj = Conjunction::new(vec![
                             // s[i] <= s[j]
                             Box::new(x_leq_y(self.start_at(i), self.start_at(j))),
                             // s[j] < s[i] + d[i]
@@ -82,12 +111,11 @@ where
                                 self.start_at(i),
                                 self.duration_at(i),
                             )),
-                        ]));
+                        ]);
 
                         // bi <-> conj
                         let bi = Boolean::new(vstore);
-                        let equiv = equivalence(Box::new(bi.clone()), conj);
-                        cstore.alloc(equiv);
+                        cstore.alloc(Box::new(Reified::new(bi.clone(), conj)));
 
                         // r = bi * r[i]
                         let ri = self.resource_at(i);
@@ -131,18 +159,448 @@ where
     }
 }
 
+impl<VStore> Clone for Cumulative<VStore>
+where
+    VStore: Collection,
+{
+    fn clone(&self) -> Self {
+        Cumulative {
+            starts: self.starts.iter().map(|v| v.bclone()).collect(),
+            durations: self.durations.iter().map(|v| v.bclone()).collect(),
+            resources: self.resources.iter().map(|v| v.bclone()).collect(),
+            capacity: self.capacity.bclone(),
+            intermediate: self.intermediate.clone(),
+            energetic: self.energetic,
+            negated: self.negated,
+        }
+    }
+}
+
+impl<VStore, Domain, Bound> NotFormula<VStore> for Cumulative<VStore>
+where
+    VStore: VStoreConcept<Item = Domain> + 'static,
+    Domain: IntDomain<Item = Bound> + 'static,
+    Bound: IntBound + 'static,
+{
+    /// The decomposition of the negation needs intermediate variables, so the negation does not prune and fails when `Cumulative` is entailed.
+    fn not(&self) -> Formula<VStore> {
+        let mut not = self.clone();
+        not.negated = !self.negated;
+        Box::new(not)
+    }
+}
+
+impl<VStore> DisplayStateful<Model> for Cumulative<VStore> {
+    fn display(&self, model: &Model) {
+        if self.negated {
+            print!("not ");
+        }
+        print!("cumulative(");
+        model.display_array(&self.starts);
+        print!(", ");
+        model.display_array(&self.durations);
+        print!(", ");
+        model.display_array(&self.resources);
+        print!(", ");
+        self.capacity.display(model);
+        print!(")");
+    }
+}
+
+impl<VStore, Domain, Bound> Subsumption<VStore> for Cumulative<VStore>
+where
+    VStore: Collection<Item = Domain>,
+    Domain: IntDomain<Item = Bound>,
+    Bound: IntBound,
+{
+    fn is_subsumed(&self, vstore: &VStore) -> SKleene {
+        let entailed = self.entailment(vstore);
+        if self.negated {
+            !entailed
+        } else {
+            entailed
+        }
+    }
+}
+
+impl<VStore, Domain, Bound> Cumulative<VStore>
+where
+    VStore: Collection<Item = Domain>,
+    Domain: IntDomain<Item = Bound>,
+    Bound: IntBound,
+{
+    fn entailment(&self, vstore: &VStore) -> SKleene {
+        // False: the compulsory parts of the tasks exceed the capacity, or a task needs more resources than available, or (with energetic reasoning) an interval requires more energy than available.
+        // True: the tasks overlapping in the worst case never exceed the capacity.
+        // Unknown: Everything else.
+        let tasks = self.read_tasks(vstore);
+        let capacity = self.capacity.read(vstore);
+        let compulsory = max_height(&compulsory_profile(&tasks));
+        if compulsory > capacity.upper()
+            || tasks
+                .iter()
+                .any(|t| t.dmin > Bound::zero() && t.rmin > capacity.upper())
+        {
+            return False;
+        }
+        if self.energetic && energetic_overload(&tasks, capacity.upper()) {
+            return False;
+        }
+        let possible = profile(
+            tasks
+                .iter()
+                .filter(|t| t.dmax > Bound::zero() && t.rmax > Bound::zero())
+                .map(|t| {
+                    (
+                        t.est.clone(),
+                        t.lst.clone() + t.dmax.clone(),
+                        t.rmax.clone(),
+                    )
+                })
+                .collect(),
+        );
+        if max_height(&possible) <= capacity.lower() {
+            True
+        } else {
+            Unknown
+        }
+    }
+}
+
+impl<VStore, Domain, Bound> Cumulative<VStore>
+where
+    VStore: Collection<Item = Domain>,
+    Domain: IntDomain<Item = Bound>,
+    Bound: IntBound,
+{
+    fn read_tasks(&self, vstore: &VStore) -> Vec<Task<Bound>> {
+        (0..self.starts.len())
+            .map(|i| {
+                let s = self.starts[i].read(vstore);
+                let d = self.durations[i].read(vstore);
+                let r = self.resources[i].read(vstore);
+                Task {
+                    est: s.lower(),
+                    lst: s.upper(),
+                    dmin: d.lower(),
+                    dmax: d.upper(),
+                    rmin: r.lower(),
+                    rmax: r.upper(),
+                }
+            })
+            .collect()
+    }
+
+    /// A task cannot overlap with a segment of the profile if it would exceed the capacity.
+    /// The profile is made of the compulsory parts of the tasks, from which we remove the task being pruned.
+    fn time_tabling(&mut self, vstore: &mut VStore) -> bool {
+        let tasks = self.read_tasks(vstore);
+        let profile = compulsory_profile(&tasks);
+        let capacity = self.capacity.read(vstore);
+        if !self
+            .capacity
+            .update(vstore, capacity.shrink_left(max_height(&profile)))
+        {
+            return false;
+        }
+        let cap = self.capacity.read(vstore).upper();
+        for (i, task) in tasks.iter().enumerate() {
+            if task.dmin <= Bound::zero() {
+                continue;
+            }
+            let running = task.running_part();
+            let height_without_task = |seg: &Segment<Bound>| match task.compulsory_part() {
+                Some((ref lst, ref ect)) if *lst <= seg.start && seg.end <= *ect => {
+                    seg.height.clone() - task.rmin.clone()
+                }
+                _ => seg.height.clone(),
+            };
+
+            let mut rmax = cap.clone();
+            if let Some((ref lst, ref ect)) = running {
+                for seg in profile.iter().filter(|s| s.start < *ect && *lst < s.end) {
+                    rmax = min(rmax, cap.clone() - height_without_task(seg));
+                }
+            }
+            let r = self.resources[i].read(vstore);
+            if !self.resources[i].update(vstore, r.shrink_right(rmax)) {
+                return false;
+            }
+
+            if task.rmin > Bound::zero() {
+                let overload =
+                    |seg: &Segment<Bound>| height_without_task(seg) + task.rmin.clone() > cap;
+                let mut est = task.est.clone();
+                for seg in &profile {
+                    if seg.start >= est.clone() + task.dmin.clone() {
+                        break;
+                    }
+                    if seg.end > est && overload(seg) {
+                        est = seg.end.clone();
+                    }
+                }
+                let mut lst = task.lst.clone();
+                for seg in profile.iter().rev() {
+                    if seg.end <= lst {
+                        break;
+                    }
+                    if seg.start < lst.clone() + task.dmin.clone() && overload(seg) {
+                        lst = seg.start.clone() - task.dmin.clone();
+                    }
+                }
+                let s = self.starts[i].read(vstore);
+                if !self.starts[i].update(vstore, s.shrink_left(est).shrink_right(lst)) {
+                    return false;
+                }
+            }
+        }
+        true
+    }
+
+    /// For every interval `[t1, t2)`, the minimal energy (duration × resource) required by the tasks inside the interval must not exceed the energy available.
+    /// A task is pushed out of its earliest (resp. latest) position if the energy left by the other tasks is not sufficient.
+    fn energetic_reasoning(&mut self, vstore: &mut VStore) -> bool {
+        let tasks = self.read_tasks(vstore);
+        let cap = self.capacity.read(vstore).upper();
+        let mut ests: Vec<Bound> = tasks.iter().map(|t| t.est.clone()).collect();
+        let mut lsts: Vec<Bound> = tasks.iter().map(|t| t.lst.clone()).collect();
+        for (t1, t2) in energetic_intervals(&tasks) {
+            let available = cap.clone() * (t2.clone() - t1.clone());
+            let energies = minimal_energies(&tasks, &t1, &t2);
+            let total = sum(&energies);
+            if total > available {
+                return false;
+            }
+            for (i, task) in tasks.iter().enumerate() {
+                if task.rmin <= Bound::zero() {
+                    continue;
+                }
+                let free = available.clone() - total.clone() + energies[i].clone();
+                let longest = free.div_floor(&task.rmin);
+                if task.left_shift(&t1, &t2) * task.rmin.clone() > free {
+                    ests[i] = max(ests[i].clone(), t2.clone() - longest.clone());
+                }
+                if task.right_shift(&t1, &t2) * task.rmin.clone() > free {
+                    lsts[i] = min(lsts[i].clone(), t1.clone() + longest - task.dmin.clone());
+                }
+            }
+        }
+        for (i, (est, lst)) in ests.into_iter().zip(lsts).enumerate() {
+            let s = self.starts[i].read(vstore);
+            if !self.starts[i].update(vstore, s.shrink_left(est).shrink_right(lst)) {
+                return false;
+            }
+        }
+        true
+    }
+}
+
+impl<VStore, Domain, Bound> Propagator<VStore> for Cumulative<VStore>
+where
+    VStore: Collection<Item = Domain>,
+    Domain: IntDomain<Item = Bound>,
+    Bound: IntBound,
+{
+    fn propagate(&mut self, vstore: &mut VStore) -> bool {
+        if self.negated {
+            return self.entailment(vstore) != True;
+        }
+        self.time_tabling(vstore) && (!self.energetic || self.energetic_reasoning(vstore))
+    }
+
+    fn cost(&self) -> PropagatorCost {
+        PropagatorCost::Quadratic
+    }
+}
+
+impl<VStore> PropagatorDependencies<FDEvent> for Cumulative<VStore> {
+    fn dependencies(&self) -> Vec<(usize, FDEvent)> {
+        let mut deps: Vec<_> = self
+            .starts
+            .iter()
+            .chain(self.durations.iter())
+            .chain(self.resources.iter())
+            .chain(Some(&self.capacity))
+            .flat_map(|v| v.dependencies(FDEvent::Bound))
+            .collect();
+        deps.sort();
+        deps.dedup();
+        deps
+    }
+}
+
+/// Bounds of a task, `est` and `lst` are the earliest and latest starting times.
+struct Task<Bound> {
+    est: Bound,
+    lst: Bound,
+    dmin: Bound,
+    dmax: Bound,
+    rmin: Bound,
+    rmax: Bound,
+}
+
+impl<Bound> Task<Bound>
+where
+    Bound: IntBound,
+{
+    /// Earliest completion time.
+    fn ect(&self) -> Bound {
+        self.est.clone() + self.dmin.clone()
+    }
+
+    /// Latest completion time.
+    fn lct(&self) -> Bound {
+        self.lst.clone() + self.dmin.clone()
+    }
+
+    /// The interval `[lst, ect)` during which the task is necessarily running.
+    fn running_part(&self) -> Option<(Bound, Bound)> {
+        if self.lst < self.ect() {
+            Some((self.lst.clone(), self.ect()))
+        } else {
+            None
+        }
+    }
+
+    /// The running part of a task using resources.
+    fn compulsory_part(&self) -> Option<(Bound, Bound)> {
+        if self.rmin > Bound::zero() {
+            self.running_part()
+        } else {
+            None
+        }
+    }
+
+    /// Duration of the task inside `[t1, t2)` when it starts at `est`.
+    fn left_shift(&self, t1: &Bound, t2: &Bound) -> Bound {
+        overlap(&self.est, &self.ect(), t1, t2)
+    }
+
+    /// Duration of the task inside `[t1, t2)` when it starts at `lst`.
+    fn right_shift(&self, t1: &Bound, t2: &Bound) -> Bound {
+        overlap(&self.lst, &self.lct(), t1, t2)
+    }
+
+    fn minimal_intersection(&self, t1: &Bound, t2: &Bound) -> Bound {
+        min(self.left_shift(t1, t2), self.right_shift(t1, t2))
+    }
+}
+
+/// Length of the intersection between `[a1, a2)` and `[b1, b2)`.
+fn overlap<Bound: IntBound>(a1: &Bound, a2: &Bound, b1: &Bound, b2: &Bound) -> Bound {
+    max(
+        Bound::zero(),
+        min(a2.clone(), b2.clone()) - max(a1.clone(), b1.clone()),
+    )
+}
+
+/// The resources used during `[start, end)`.
+struct Segment<Bound> {
+    start: Bound,
+    end: Bound,
+    height: Bound,
+}
+
+/// Sum of the heights of the parts `[start, end)` given as `(start, end, height)`.
+/// The segments are sorted, disjoint and only those with a positive height are kept.
+fn profile<Bound: IntBound>(parts: Vec<(Bound, Bound, Bound)>) -> Vec<Segment<Bound>> {
+    let mut events = vec![];
+    for (start, end, height) in parts {
+        if start < end {
+            events.push((start, height.clone()));
+            events.push((end, -height));
+        }
+    }
+    events.sort_by(|a, b| a.0.cmp(&b.0));
+    let mut profile = vec![];
+    let mut height = Bound::zero();
+    let mut i = 0;
+    while i < events.len() {
+        let time = events[i].0.clone();
+        while i < events.len() && events[i].0 == time {
+            height = height + events[i].1.clone();
+            i += 1;
+        }
+        if i < events.len() && height > Bound::zero() {
+            profile.push(Segment {
+                start: time,
+                end: events[i].0.clone(),
+                height: height.clone(),
+            });
+        }
+    }
+    profile
+}
+
+fn compulsory_profile<Bound: IntBound>(tasks: &[Task<Bound>]) -> Vec<Segment<Bound>> {
+    profile(
+        tasks
+            .iter()
+            .filter_map(|t| {
+                t.compulsory_part()
+                    .map(|(lst, ect)| (lst, ect, t.rmin.clone()))
+            })
+            .collect(),
+    )
+}
+
+/// The intervals `[t1, t2)` relevant for the energetic reasoning.
+fn energetic_intervals<Bound: IntBound>(tasks: &[Task<Bound>]) -> Vec<(Bound, Bound)> {
+    let mut lefts: Vec<Bound> = tasks
+        .iter()
+        .flat_map(|t| vec![t.est.clone(), t.lst.clone(), t.ect()])
+        .collect();
+    let mut rights: Vec<Bound> = tasks
+        .iter()
+        .flat_map(|t| vec![t.ect(), t.lst.clone(), t.lct()])
+        .collect();
+    lefts.sort();
+    lefts.dedup();
+    rights.sort();
+    rights.dedup();
+    let mut intervals = vec![];
+    for t1 in &lefts {
+        for t2 in rights.iter().filter(|t2| *t2 > t1) {
+            intervals.push((t1.clone(), t2.clone()));
+        }
+    }
+    intervals
+}
+
+fn minimal_energies<Bound: IntBound>(tasks: &[Task<Bound>], t1: &Bound, t2: &Bound) -> Vec<Bound> {
+    tasks
+        .iter()
+        .map(|t| t.minimal_intersection(t1, t2) * t.rmin.clone())
+        .collect()
+}
+
+fn sum<Bound: IntBound>(values: &[Bound]) -> Bound {
+    values.iter().fold(Bound::zero(), |acc, v| acc + v.clone())
+}
+
+fn energetic_overload<Bound: IntBound>(tasks: &[Task<Bound>], cap: Bound) -> bool {
+    energetic_intervals(tasks)
+        .into_iter()
+        .any(|(t1, t2)| sum(&minimal_energies(tasks, &t1, &t2)) > cap.clone() * (t2 - t1))
+}
+
+fn max_height<Bound: IntBound>(profile: &[Segment<Bound>]) -> Bound {
+    profile
+        .iter()
+        .map(|s| s.height.clone())
+        .max()
+        .unwrap_or_else(Bound::zero)
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
     use gcollections::ops::*;
     use interval::interval::*;
+    use interval::interval_set::*;
     use interval::ops::Range;
-    use kernel::*;
-    use model::*;
-    use propagation::ops::Subsumption;
-    use propagation::CStoreFD;
-    use trilean::SKleene;
-    use trilean::SKleene::*;
+    use search::test::*;
+    use variable::ops::Iterable;
     use variable::VStoreCopy;
 
     type Dom = Interval<i32>;
@@ -170,6 +628,15 @@ mod test {
             }
         }
 
+        fn clone_test(&self) -> Self {
+            CumulativeTest::new(
+                self.starts.clone(),
+                self.durations.clone(),
+                self.resources.clone(),
+                self.capacity,
+            )
+        }
+
         fn new_assignment(
             starts: Vec<i32>,
             durations: Vec<i32>,
@@ -201,9 +668,8 @@ mod test {
             self,
             model: &mut Model,
             vstore: &mut VStoreFD,
-            cstore: &mut CStoreFD<VStoreFD>,
             constant: bool,
-        ) {
+        ) -> Cumulative<VStoreFD> {
             model.open_group("s");
             let starts = self
                 .starts
@@ -228,8 +694,7 @@ mod test {
             let capacity = Box::new(vstore.alloc(self.capacity));
             model.register_var(capacity.index(), String::from("c"));
 
-            let mut cumulative = Cumulative::new(starts, durations, resources, capacity);
-            cumulative.join(vstore, cstore);
+            Cumulative::new(starts, durations, resources, capacity)
         }
 
         // The boolean "constant" indicates if we transform the singleton domains into constant terms or not.
@@ -238,11 +703,46 @@ mod test {
             let mut vstore = VStoreFD::empty();
             let mut cstore = CStoreFD::empty();
             let mut model = Model::new();
-            self.instantiate(&mut model, &mut vstore, &mut cstore, constant);
+            let mut cumulative = self.instantiate(&mut model, &mut vstore, constant);
+            cumulative.join(&mut vstore, &mut cstore);
+            cstore.display(&(model, vstore.clone()));
+            assert_eq!(cstore.is_subsumed(&vstore), before);
+            assert_eq!(cstore.consistency(&mut vstore), after);
+            assert_eq!(cstore.is_subsumed(&vstore), after);
+        }
+
+        // Same as `test` but with the cumulative propagator instead of the decomposition.
+        // The starting dates after propagation are checked against `expected_starts` if provided.
+        fn test_propagator(
+            self,
+            test_num: usize,
+            before: SKleene,
+            after: SKleene,
+            energetic: bool,
+            expected_starts: Option<Vec<(i32, i32)>>,
+        ) {
+            println!("Test number {}", test_num);
+            let mut vstore = VStoreFD::empty();
+            let mut cstore = CStoreFD::empty();
+            let mut model = Model::new();
+            let num_tasks = self.starts.len();
+            let mut cumulative = self.instantiate(&mut model, &mut vstore, false);
+            if energetic {
+                cumulative = cumulative.with_energetic_reasoning();
+            }
+            cstore.alloc(Box::new(cumulative));
             cstore.display(&(model, vstore.clone()));
             assert_eq!(cstore.is_subsumed(&vstore), before);
             assert_eq!(cstore.consistency(&mut vstore), after);
             assert_eq!(cstore.is_subsumed(&vstore), after);
+            if let Some(expected_starts) = expected_starts {
+                let starts: Vec<_> = vstore
+                    .iter()
+                    .take(num_tasks)
+                    .map(|s| (s.lower(), s.upper()))
+                    .collect();
+                assert_eq!(starts, expected_starts);
+            }
         }
 
         fn test_assignment(self, test_num: usize, expected: SKleene, constant: bool) {
@@ -317,4 +817,193 @@ mod test {
         test.starts[2] = Interval::new(4, 5);
         test.test(3, Unknown, Unknown, constant);
     }
+
+    #[test]
+    fn propagator_assignment_test() {
+        let tests = vec![
+            (vec![0, 1, 4], vec![3, 4, 2], vec![1, 2, 2], 3, False),
+            (vec![0, 1, 5], vec![3, 4, 2], vec![1, 2, 2], 3, True),
+            (vec![0, 1, 4], vec![3, 4, 2], vec![1, 2, 1], 3, True),
+            (vec![0, 1, 4], vec![3, 4, 2], vec![1, 2, 2], 4, True),
+            (vec![0, 1, 4], vec![3, 3, 2], vec![1, 2, 2], 3, True),
+            (vec![0, 0], vec![0, 0], vec![1, 1], 1, True),
+            (vec![0], vec![1], vec![2], 1, False),
+        ];
+        for (i, (s, d, r, c, expected)) in tests.into_iter().enumerate() {
+            for &energetic in &[false, true] {
+                CumulativeTest::new_assignment(s.clone(), d.clone(), r.clone(), c).test_propagator(
+                    i + 1,
+                    expected,
+                    expected,
+                    energetic,
+                    None,
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn time_tabling_test() {
+        let mut test =
+            CumulativeTest::new_assignment(vec![0, 1, 4], vec![3, 4, 2], vec![1, 2, 2], 3);
+        test.starts[0] = Interval::new(0, 4);
+        test.test_propagator(1, False, False, false, None);
+
+        // The task 2 must start at 0 to end before the task 3.
+        let mut test =
+            CumulativeTest::new_assignment(vec![0, 1, 4], vec![3, 4, 2], vec![1, 2, 2], 3);
+        test.starts[1] = Interval::new(0, 1);
+        test.test_propagator(2, Unknown, True, false, Some(vec![(0, 0), (0, 0), (4, 4)]));
+
+        // The task 3 must start after the task 2.
+        let mut test =
+            CumulativeTest::new_assignment(vec![0, 1, 4], vec![3, 4, 2], vec![1, 2, 2], 3);
+        test.starts[2] = Interval::new(4, 5);
+        test.test_propagator(3, Unknown, True, false, Some(vec![(0, 0), (1, 1), (5, 5)]));
+
+        // The compulsory part [2..5) of the task 1 pushes the task 2 on both sides.
+        let mut test = CumulativeTest::new_assignment(vec![2, 0], vec![3, 2], vec![2, 2], 3);
+        test.starts[1] = Interval::new(1, 10);
+        test.test_propagator(4, Unknown, True, false, Some(vec![(2, 2), (5, 10)]));
+        let mut test = CumulativeTest::new_assignment(vec![2, 0], vec![3, 2], vec![2, 2], 3);
+        test.starts[1] = Interval::new(-5, 4);
+        test.test_propagator(5, Unknown, True, false, Some(vec![(2, 2), (-5, 0)]));
+
+        // The resource of the task 2 is bounded by the compulsory part of the task 1.
+        let mut test = CumulativeTest::new_assignment(vec![0, 1], vec![3, 1], vec![2, 0], 3);
+        test.resources[1] = Interval::new(0, 3);
+        test.test_propagator(6, Unknown, True, false, None);
+    }
+
+    #[test]
+    fn energetic_reasoning_test() {
+        // Three tasks of duration 2 in [0..4), time-tabling does not see the overload.
+        let mut test =
+            CumulativeTest::new_assignment(vec![0, 0, 0], vec![2, 2, 2], vec![1, 1, 1], 1);
+        test.starts = vec![Interval::new(0, 2); 3];
+        test.clone_test()
+            .test_propagator(1, Unknown, Unknown, false, None);
+        test.test_propagator(2, False, False, true, None);
+
+        // The two first tasks fill [0..4), so the third task must start at 4.
+        let mut test =
+            CumulativeTest::new_assignment(vec![0, 0, 0], vec![2, 2, 1], vec![1, 1, 1], 1);
+        test.starts = vec![
+            Interval::new(0, 2),
+            Interval::new(0, 2),
+            Interval::new(0, 10),
+        ];
+        test.clone_test().test_propagator(
+            3,
+            Unknown,
+            Unknown,
+            false,
+            Some(vec![(0, 2), (0, 2), (0, 10)]),
+        );
+        test.test_propagator(
+            4,
+            Unknown,
+            Unknown,
+            true,
+            Some(vec![(0, 2), (0, 2), (4, 10)]),
+        );
+
+        // Symmetric case, the third task must end before 0.
+        let mut test =
+            CumulativeTest::new_assignment(vec![0, 0, 0], vec![2, 2, 1], vec![1, 1, 1], 1);
+        test.starts = vec![
+            Interval::new(0, 2),
+            Interval::new(0, 2),
+            Interval::new(-10, 3),
+        ];
+        test.test_propagator(
+            5,
+            Unknown,
+            Unknown,
+            true,
+            Some(vec![(0, 2), (0, 2), (-10, -1)]),
+        );
+    }
+
+    #[derive(Clone, Copy, PartialEq)]
+    enum Approach {
+        Decomposition,
+        TimeTabling,
+        Energetic,
+        Negation,
+    }
+
+    fn count_solutions(
+        approach: Approach,
+        durations: &[(i32, i32)],
+        resources: &[i32],
+        capacity: i32,
+    ) -> usize {
+        let mut space = FDSpace::empty();
+        let num_tasks = durations.len();
+        let mut alloc_vars = |doms: Vec<(i32, i32)>| -> Vec<Var<VStore>> {
+            doms.into_iter()
+                .map(|d| Box::new(space.vstore.alloc(d.to_interval_set())) as Var<VStore>)
+                .collect()
+        };
+        let starts = alloc_vars(vec![(0, 4); num_tasks]);
+        let durations = alloc_vars(durations.to_vec());
+        let resources = alloc_vars(resources.iter().map(|&r| (r, r)).collect());
+        let capacity = Box::new(Constant::new(capacity));
+        let cumulative = Cumulative::new(starts, durations, resources, capacity);
+        match approach {
+            Approach::Decomposition => {
+                let mut cumulative = cumulative;
+                cumulative.join(&mut space.vstore, &mut space.cstore);
+            }
+            Approach::TimeTabling => {
+                space.cstore.alloc(Box::new(cumulative));
+            }
+            Approach::Energetic => {
+                space
+                    .cstore
+                    .alloc(Box::new(cumulative.with_energetic_reasoning()));
+            }
+            Approach::Negation => {
+                space.cstore.alloc(cumulative.not());
+            }
+        }
+        count_assignments(space, 2 * num_tasks)
+    }
+
+    #[test]
+    fn propagator_decomposition_solutions_test() {
+        // The expected number of solutions is obtained by enumerating every assignment.
+        let instances = vec![
+            (vec![(2, 2), (1, 1), (2, 2)], vec![1, 2, 1], 2, 52),
+            (vec![(2, 2), (1, 2), (3, 3)], vec![1, 1, 1], 1, 12),
+            (
+                vec![(1, 3), (2, 2), (1, 2), (2, 2)],
+                vec![2, 1, 1, 2],
+                3,
+                1261,
+            ),
+        ];
+        for (durations, resources, capacity, expected) in instances {
+            for &approach in &[
+                Approach::Decomposition,
+                Approach::TimeTabling,
+                Approach::Energetic,
+            ] {
+                assert_eq!(
+                    count_solutions(approach, &durations, &resources, capacity),
+                    expected
+                );
+            }
+            // Every start is in `[0..4]`.
+            let assignments: usize = durations
+                .iter()
+                .map(|&(l, u)| 5 * (u - l + 1) as usize)
+                .product();
+            assert_eq!(
+                count_solutions(Approach::Negation, &durations, &resources, capacity),
+                assignments - expected
+            );
+        }
+    }
 }
@@ -0,0 +1,718 @@
+// Copyright 2018 Pierre Talbot (IRCAM)
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+
+//     http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! The `Diffn` constraint ensures that the rectangles of origins `(x[i], y[i])`, widths `w[i]` and heights `h[i]` do not overlap.
+//! Similarly to `Disjunctive`, the rectangles with a width or height of zero can be placed anywhere, and we reason on the lower bounds of the sizes.
+//! The filtering is made of two rules applied until a fixpoint is reached:
+//!  * Pairwise: when only one relative position of two rectangles (left, right, below or above) is still possible, it is enforced.
+//!  * Sweep: the compulsory parts of the rectangles define forbidden regions for the origin of the other rectangles, each bound of an origin is moved to the first position not covered by these regions, as described in `Sweep as a generic pruning technique applied to the non-overlapping rectangles constraint`, Beldiceanu and Carlsson, 2001.
+
+use concept::*;
+use gcollections::*;
+use kernel::*;
+use logic::*;
+use model::*;
+use propagation::events::*;
+use propagation::*;
+use propagators::cmp::*;
+use std::cmp::max;
+use term::*;
+use trilean::SKleene;
+use trilean::SKleene::*;
+
+#[derive(Debug)]
+pub struct Diffn<VStore> {
+    /// The origins of the rectangles in each dimension: `[x, y]`.
+    origins: [Vec<Var<VStore>>; 2],
+    /// The sizes of the rectangles in each dimension: `[w, h]`.
+    sizes: [Vec<Var<VStore>>; 2],
+}
+
+impl<VStore> Diffn<VStore> {
+    pub fn new(
+        x: Vec<Var<VStore>>,
+        y: Vec<Var<VStore>>,
+        w: Vec<Var<VStore>>,
+        h: Vec<Var<VStore>>,
+    ) -> Self {
+        assert!(
+            x.len() == y.len() && x.len() == w.len() && x.len() == h.len(),
+            "Every rectangle in `Diffn` must have an origin, a width and a height."
+        );
+        Diffn {
+            origins: [x, y],
+            sizes: [w, h],
+        }
+    }
+}
+
+impl<VStore> Clone for Diffn<VStore>
+where
+    VStore: Collection,
+{
+    fn clone(&self) -> Self {
+        let bclone = |vars: &Vec<Var<VStore>>| vars.iter().map(|v| v.bclone()).collect();
+        Diffn {
+            origins: [bclone(&self.origins[0]), bclone(&self.origins[1])],
+            sizes: [bclone(&self.sizes[0]), bclone(&self.sizes[1])],
+        }
+    }
+}
+
+impl<VStore, Domain, Bound> NotFormula<VStore> for Diffn<VStore>
+where
+    VStore: VStoreConcept<Item = Domain> + 'static,
+    Domain: IntDomain<Item = Bound> + 'static,
+    Bound: IntBound + 'static,
+{
+    /// At least two rectangles with non-zero sizes overlap.
+    fn not(&self) -> Formula<VStore> {
+        let positive = |s: &Var<VStore>| {
+            Box::new(XLessY::new(
+                Box::new(Constant::new(Bound::zero())),
+                s.bclone(),
+            )) as Formula<VStore>
+        };
+        let mut overlaps = vec![];
+        for i in 0..self.len() {
+            for j in i + 1..self.len() {
+                let mut overlap = vec![];
+                for d in 0..2 {
+                    let (o, s) = (&self.origins[d], &self.sizes[d]);
+                    overlap.push(positive(&s[i]));
+                    overlap.push(positive(&s[j]));
+                    overlap.push(Box::new(XLessYPlusZ::new(
+                        o[i].bclone(),
+                        o[j].bclone(),
+                        s[j].bclone(),
+                    )) as Formula<VStore>);
+                    overlap.push(Box::new(XLessYPlusZ::new(
+                        o[j].bclone(),
+                        o[i].bclone(),
+                        s[i].bclone(),
+                    )));
+                }
+                overlaps.push(Box::new(Conjunction::new(overlap)) as Formula<VStore>);
+            }
+        }
+        Box::new(Disjunction::new(overlaps))
+    }
+}
+
+impl<VStore> DisplayStateful<Model> for Diffn<VStore> {
+    fn display(&self, model: &Model) {
+        print!("diffn(");
+        model.display_array(&self.origins[0]);
+        print!(", ");
+        model.display_array(&self.origins[1]);
+        print!(", ");
+        model.display_array(&self.sizes[0]);
+        print!(", ");
+        model.display_array(&self.sizes[1]);
+        print!(")");
+    }
+}
+
+impl<VStore> Diffn<VStore> {
+    fn len(&self) -> usize {
+        self.origins[0].len()
+    }
+}
+
+/// The bounds of the origin and the minimal size of a rectangle in each dimension.
+#[derive(Clone, Debug)]
+struct Rect<Bound> {
+    lower: [Bound; 2],
+    upper: [Bound; 2],
+    size: [Bound; 2],
+}
+
+impl<Bound> Rect<Bound>
+where
+    Bound: IntBound,
+{
+    /// The rectangles with a size of zero do not participate to the constraint.
+    fn is_empty(&self) -> bool {
+        self.size.iter().any(|s| *s <= Bound::zero())
+    }
+
+    /// `true` if this rectangle can be placed before `other` in the dimension `d`.
+    fn can_precede(&self, other: &Rect<Bound>, d: usize) -> bool {
+        self.lower[d].clone() + self.size[d].clone() <= other.upper[d]
+    }
+
+    /// The positions of the origin of `other` overlapping with the compulsory part of this rectangle in the dimension `d`, if any.
+    fn forbidden(&self, other: &Rect<Bound>, d: usize) -> Option<(Bound, Bound)> {
+        let start = self.upper[d].clone() - other.size[d].clone() + Bound::one();
+        let end = self.lower[d].clone() + self.size[d].clone() - Bound::one();
+        if self.upper[d] <= end {
+            Some((start, end))
+        } else {
+            None
+        }
+    }
+}
+
+/// A forbidden region for an origin: the positions in the sweep dimension and in the other dimension.
+type Region<Bound> = ((Bound, Bound), (Bound, Bound));
+
+/// `true` if the intervals cover `[lower..upper]`.
+fn covered<Bound: IntBound>(
+    lower: Bound,
+    upper: Bound,
+    mut intervals: Vec<(Bound, Bound)>,
+) -> bool {
+    intervals.sort();
+    let mut next = lower;
+    for (start, end) in intervals {
+        if start > next {
+            break;
+        }
+        next = max(next, end + Bound::one());
+    }
+    next > upper
+}
+
+/// The first position in `[lower..upper]` (in the sweep dimension) that is not covered by the forbidden `regions`.
+/// The origin in the other dimension is in `other`.
+fn sweep<Bound: IntBound>(
+    lower: Bound,
+    upper: Bound,
+    other: (Bound, Bound),
+    regions: &[Region<Bound>],
+) -> Option<Bound> {
+    let mut pos = lower;
+    while pos <= upper {
+        let active: Vec<_> = regions
+            .iter()
+            .filter(|(sweep, _)| sweep.0 <= pos && pos <= sweep.1)
+            .collect();
+        let intervals = active.iter().map(|(_, o)| o.clone()).collect();
+        if !covered(other.0.clone(), other.1.clone(), intervals) {
+            return Some(pos);
+        }
+        // The active regions cover the other dimension until one of them ends.
+        pos = active.iter().map(|(s, _)| s.1.clone()).min().unwrap() + Bound::one();
+    }
+    None
+}
+
+impl<VStore, Domain, Bound> Diffn<VStore>
+where
+    VStore: Collection<Item = Domain>,
+    Domain: IntDomain<Item = Bound>,
+    Bound: IntBound,
+{
+    fn rect(&self, vstore: &VStore, i: usize) -> Rect<Bound> {
+        let o: Vec<_> = (0..2).map(|d| self.origins[d][i].read(vstore)).collect();
+        Rect {
+            lower: [o[0].lower(), o[1].lower()],
+            upper: [o[0].upper(), o[1].upper()],
+            size: [
+                self.sizes[0][i].read(vstore).lower(),
+                self.sizes[1][i].read(vstore).lower(),
+            ],
+        }
+    }
+
+    fn rects(&self, vstore: &VStore) -> Vec<Rect<Bound>> {
+        (0..self.len()).map(|i| self.rect(vstore, i)).collect()
+    }
+
+    /// Restricts the origin of the rectangle `i` in the dimension `d` to `[lower..upper]`.
+    fn restrict(
+        &mut self,
+        vstore: &mut VStore,
+        i: usize,
+        d: usize,
+        lower: Bound,
+        upper: Bound,
+    ) -> bool {
+        let dom = self.origins[d][i]
+            .read(vstore)
+            .shrink_left(lower)
+            .shrink_right(upper);
+        self.origins[d][i].update(vstore, dom)
+    }
+
+    /// Enforces the relative position of two rectangles when it is the only possible one.
+    /// The rectangles are read once per pass, only the two rectangles modified are read again.
+    fn pairwise(&mut self, vstore: &mut VStore) -> bool {
+        let n = self.len();
+        let mut rects = self.rects(vstore);
+        for i in 0..n {
+            for j in i + 1..n {
+                let (ri, rj) = (&rects[i], &rects[j]);
+                if ri.is_empty() || rj.is_empty() {
+                    continue;
+                }
+                let mut positions = vec![];
+                for d in 0..2 {
+                    if ri.can_precede(rj, d) {
+                        positions.push((i, j, d));
+                    }
+                    if rj.can_precede(ri, d) {
+                        positions.push((j, i, d));
+                    }
+                }
+                match positions.len() {
+                    0 => return false,
+                    1 => {
+                        let (a, b, d) = positions[0];
+                        let (ra, rb) = (&rects[a], &rects[b]);
+                        let b_lower = ra.lower[d].clone() + ra.size[d].clone();
+                        let a_upper = rb.upper[d].clone() - ra.size[d].clone();
+                        if !self.restrict(vstore, b, d, b_lower, rb.upper[d].clone())
+                            || !self.restrict(vstore, a, d, ra.lower[d].clone(), a_upper)
+                        {
+                            return false;
+                        }
+                        rects[a] = self.rect(vstore, a);
+                        rects[b] = self.rect(vstore, b);
+                    }
+                    _ => (),
+                }
+            }
+        }
+        true
+    }
+
+    /// Moves each bound of the origins out of the forbidden regions.
+    fn sweep(&mut self, vstore: &mut VStore) -> bool {
+        let mut rects = self.rects(vstore);
+        for i in 0..self.len() {
+            for d in 0..2 {
+                let e = 1 - d;
+                let ri = &rects[i];
+                if ri.is_empty() {
+                    continue;
+                }
+                let regions: Vec<_> = rects
+                    .iter()
+                    .enumerate()
+                    .filter(|(j, _)| *j != i)
+                    .filter_map(|(_, rj)| match (rj.forbidden(ri, d), rj.forbidden(ri, e)) {
+                        (Some(fd), Some(fe)) => Some((fd, fe)),
+                        _ => None,
+                    })
+                    .collect();
+                if regions.is_empty() {
+                    continue;
+                }
+                let other = (ri.lower[e].clone(), ri.upper[e].clone());
+                let lower = sweep(
+                    ri.lower[d].clone(),
+                    ri.upper[d].clone(),
+                    other.clone(),
+                    &regions,
+                );
+                // The upper bound is the first free position in the mirrored dimension.
+                let mirror: Vec<_> = regions
+                    .iter()
+                    .map(|(fd, fe)| ((-fd.1.clone(), -fd.0.clone()), fe.clone()))
+                    .collect();
+                let upper = sweep(-ri.upper[d].clone(), -ri.lower[d].clone(), other, &mirror);
+                match (lower, upper) {
+                    (Some(lower), Some(upper)) => {
+                        if !self.restrict(vstore, i, d, lower, -upper) {
+                            return false;
+                        }
+                        rects[i] = self.rect(vstore, i);
+                    }
+                    _ => return false,
+                }
+            }
+        }
+        true
+    }
+
+    fn bounds(&self, vstore: &VStore) -> Vec<(Bound, Bound)> {
+        self.origins
+            .iter()
+            .flat_map(|o| o.iter())
+            .map(|v| {
+                let dom = v.read(vstore);
+                (dom.lower(), dom.upper())
+            })
+            .collect()
+    }
+}
+
+impl<VStore, Domain, Bound> Subsumption<VStore> for Diffn<VStore>
+where
+    VStore: Collection<Item = Domain>,
+    Domain: IntDomain<Item = Bound>,
+    Bound: IntBound,
+{
+    fn is_subsumed(&self, vstore: &VStore) -> SKleene {
+        // False: two non-empty rectangles cannot be placed apart.
+        // True: every pair of rectangles that can be non-empty is separated in a dimension, for all the values of the variables.
+        // Unknown: Everything else.
+        let rects = self.rects(vstore);
+        // The rectangles with the maximal sizes.
+        let mut largest = rects.clone();
+        for (i, rect) in largest.iter_mut().enumerate() {
+            for d in 0..2 {
+                rect.size[d] = self.sizes[d][i].read(vstore).upper();
+            }
+        }
+        let separated = |a: &Rect<Bound>, b: &Rect<Bound>, d: usize| {
+            a.upper[d].clone() + a.size[d].clone() <= b.lower[d]
+        };
+        let mut entailed = true;
+        for i in 0..rects.len() {
+            for j in i + 1..rects.len() {
+                let (ri, rj) = (&rects[i], &rects[j]);
+                if !ri.is_empty()
+                    && !rj.is_empty()
+                    && (0..2).all(|d| !ri.can_precede(rj, d) && !rj.can_precede(ri, d))
+                {
+                    return False;
+                }
+                let (li, lj) = (&largest[i], &largest[j]);
+                entailed = entailed
+                    && (li.is_empty()
+                        || lj.is_empty()
+                        || (0..2).any(|d| separated(li, lj, d) || separated(lj, li, d)));
+            }
+        }
+        if entailed {
+            True
+        } else {
+            Unknown
+        }
+    }
+}
+
+impl<VStore, Domain, Bound> Propagator<VStore> for Diffn<VStore>
+where
+    VStore: Collection<Item = Domain>,
+    Domain: IntDomain<Item = Bound>,
+    Bound: IntBound,
+{
+    fn propagate(&mut self, vstore: &mut VStore) -> bool {
+        let mut bounds = self.bounds(vstore);
+        loop {
+            if !self.pairwise(vstore) || !self.sweep(vstore) {
+                return false;
+            }
+            let new_bounds = self.bounds(vstore);
+            if new_bounds == bounds {
+                return true;
+            }
+            bounds = new_bounds;
+        }
+    }
+
+    fn cost(&self) -> PropagatorCost {
+        PropagatorCost::Quadratic
+    }
+}
+
+impl<VStore> PropagatorDependencies<FDEvent> for Diffn<VStore> {
+    fn dependencies(&self) -> Vec<(usize, FDEvent)> {
+        let mut deps: Vec<_> = self
+            .origins
+            .iter()
+            .chain(self.sizes.iter())
+            .flat_map(|vars| vars.iter())
+            .flat_map(|v| v.dependencies(FDEvent::Bound))
+            .collect();
+        deps.sort();
+        deps.dedup();
+        deps
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use gcollections::ops::*;
+    use interval::interval::*;
+    use interval::interval_set::*;
+    use search::test::*;
+    use variable::ops::*;
+    use variable::VStoreFD;
+
+    type Doms = Vec<(i32, i32)>;
+
+    /// Tests `Diffn` on the rectangles with origins `x` and `y` and sizes `w` and `h`, `expected` contains the expected `x` and `y` after propagation.
+    fn diffn_test_one(
+        test_num: u32,
+        (x, y, w, h): (Doms, Doms, Doms, Doms),
+        before: SKleene,
+        after: SKleene,
+        expected: Option<(Doms, Doms)>,
+    ) {
+        let mut vstore = VStoreFD::empty();
+        let mut alloc = |doms: Doms| -> Vec<Var<VStoreFD>> {
+            doms.into_iter()
+                .map(|d| Box::new(vstore.alloc(d.to_interval())) as Var<VStoreFD>)
+                .collect()
+        };
+        let (x, y, w, h) = (alloc(x), alloc(y), alloc(w), alloc(h));
+        let n = x.len();
+        let mut propagator = Diffn::new(x, y, w, h);
+        assert_eq!(
+            propagator.is_subsumed(&vstore),
+            before,
+            "Test {}.",
+            test_num
+        );
+        let success = propagator.propagate(&mut vstore);
+        assert_eq!(success, expected.is_some(), "Test {}.", test_num);
+        if let Some((x, y)) = expected {
+            assert_eq!(propagator.is_subsumed(&vstore), after, "Test {}.", test_num);
+            let origins: Vec<_> = vstore
+                .iter()
+                .take(2 * n)
+                .map(|o| (o.lower(), o.upper()))
+                .collect();
+            let expected: Vec<_> = x.into_iter().chain(y).collect();
+            assert_eq!(origins, expected, "Test {}.", test_num);
+        }
+    }
+
+    #[test]
+    fn diffn_test() {
+        let two = (2, 2);
+        diffn_test_one(
+            1,
+            (
+                vec![(0, 0), (2, 2)],
+                vec![(0, 0), (0, 0)],
+                vec![two, two],
+                vec![two, two],
+            ),
+            True,
+            True,
+            Some((vec![(0, 0), (2, 2)], vec![(0, 0), (0, 0)])),
+        );
+        diffn_test_one(
+            2,
+            (
+                vec![(0, 0), (1, 1)],
+                vec![(0, 0), (1, 1)],
+                vec![two, two],
+                vec![two, two],
+            ),
+            False,
+            False,
+            None,
+        );
+        // Rectangles with a width of zero can be placed anywhere.
+        diffn_test_one(
+            3,
+            (
+                vec![(0, 0), (1, 1)],
+                vec![(0, 0), (1, 1)],
+                vec![two, (0, 0)],
+                vec![two, two],
+            ),
+            True,
+            True,
+            Some((vec![(0, 0), (1, 1)], vec![(0, 0), (1, 1)])),
+        );
+        // Pairwise: the rectangle 1 cannot be above or below the rectangle 0, and not on its left.
+        diffn_test_one(
+            4,
+            (
+                vec![(0, 0), (0, 5)],
+                vec![(0, 0), (0, 1)],
+                vec![two, two],
+                vec![two, two],
+            ),
+            Unknown,
+            True,
+            Some((vec![(0, 0), (2, 5)], vec![(0, 0), (0, 1)])),
+        );
+        // Sweep: the rectangles 0 and 1 form a wall in `[2..4[` that the rectangle 2 cannot cross, it can still be above or on the right of each of them.
+        diffn_test_one(
+            5,
+            (
+                vec![(2, 2), (2, 2), (1, 6)],
+                vec![(0, 0), (2, 2), (0, 2)],
+                vec![two, two, two],
+                vec![two, two, two],
+            ),
+            Unknown,
+            True,
+            Some((vec![(2, 2), (2, 2), (4, 6)], vec![(0, 0), (2, 2), (0, 2)])),
+        );
+        // Sweep on the upper bound, the wall is in `[4..6[`.
+        diffn_test_one(
+            6,
+            (
+                vec![(4, 4), (4, 4), (0, 5)],
+                vec![(0, 0), (2, 2), (0, 2)],
+                vec![two, two, two],
+                vec![two, two, two],
+            ),
+            Unknown,
+            True,
+            Some((vec![(4, 4), (4, 4), (0, 2)], vec![(0, 0), (2, 2), (0, 2)])),
+        );
+        // The wall covers all the positions of the rectangle 2.
+        diffn_test_one(
+            7,
+            (
+                vec![(2, 2), (2, 2), (1, 3)],
+                vec![(0, 0), (2, 2), (0, 2)],
+                vec![two, two, two],
+                vec![two, two, two],
+            ),
+            Unknown,
+            Unknown,
+            None,
+        );
+        // The minimal size is used for the filtering and the maximal one for the subsumption.
+        diffn_test_one(
+            8,
+            (
+                vec![(0, 0), (0, 5)],
+                vec![(0, 0), (0, 1)],
+                vec![(2, 4), two],
+                vec![two, two],
+            ),
+            Unknown,
+            Unknown,
+            Some((vec![(0, 0), (2, 5)], vec![(0, 0), (0, 1)])),
+        );
+    }
+
+    /// Number of placements of rectangles of sizes `w` and `h` with their origins in `[0..max_x]` and `[0..max_y]`.
+    fn count_diffn(
+        max_x: i32,
+        max_y: i32,
+        w: &[(i32, i32)],
+        h: &[(i32, i32)],
+        decomposition: bool,
+    ) -> usize {
+        let mut space = FDSpace::empty();
+        let mut alloc = |doms: Vec<(i32, i32)>| -> Vec<Var<VStore>> {
+            doms.into_iter()
+                .map(|d| Box::new(space.vstore.alloc(d.to_interval_set())) as Var<VStore>)
+                .collect()
+        };
+        let n = w.len();
+        let x = alloc(vec![(0, max_x); n]);
+        let y = alloc(vec![(0, max_y); n]);
+        let w = alloc(w.to_vec());
+        let h = alloc(h.to_vec());
+        if decomposition {
+            // x[i] + w[i] <= x[j] \/ x[j] + w[j] <= x[i] \/ y[i] + h[i] <= y[j] \/ y[j] + h[j] <= y[i] \/ w[i] < 1 \/ ...
+            for i in 0..n {
+                for j in i + 1..n {
+                    let mut fs = vec![];
+                    for &(o, s) in &[(&x, &w), (&y, &h)] {
+                        for &(a, b) in &[(i, j), (j, i)] {
+                            fs.push(Box::new(x_geq_y_plus_z(
+                                o[b].bclone(),
+                                o[a].bclone(),
+                                s[a].bclone(),
+                            )) as Formula<VStore>);
+                        }
+                        for &k in &[i, j] {
+                            fs.push(Box::new(XLessY::new(
+                                s[k].bclone(),
+                                Box::new(Constant::new(1)),
+                            )));
+                        }
+                    }
+                    space.cstore.alloc(Box::new(Disjunction::new(fs)));
+                }
+            }
+        } else {
+            space.cstore.alloc(Box::new(Diffn::new(x, y, w, h)));
+        }
+        count_assignments(space, 4 * n)
+    }
+
+    /// Number of placements by enumerating every assignment of `[x, y, w, h]`.
+    fn brute_force(max_x: i32, max_y: i32, w: &[(i32, i32)], h: &[(i32, i32)]) -> usize {
+        let n = w.len();
+        let mut doms = vec![(0, max_x); n];
+        doms.extend(vec![(0, max_y); n]);
+        doms.extend(w.iter().cloned());
+        doms.extend(h.iter().cloned());
+        let mut values: Vec<_> = doms.iter().map(|d| d.0).collect();
+        let mut solutions = 0;
+        loop {
+            let rect = |i: usize| {
+                (
+                    values[i],
+                    values[n + i],
+                    values[2 * n + i],
+                    values[3 * n + i],
+                )
+            };
+            let overlap = (0..n).any(|i| {
+                (i + 1..n).any(|j| {
+                    let (xi, yi, wi, hi) = rect(i);
+                    let (xj, yj, wj, hj) = rect(j);
+                    wi > 0
+                        && hi > 0
+                        && wj > 0
+                        && hj > 0
+                        && xi < xj + wj
+                        && xj < xi + wi
+                        && yi < yj + hj
+                        && yj < yi + hi
+                })
+            });
+            if !overlap {
+                solutions += 1;
+            }
+            // Next assignment.
+            let mut k = 0;
+            while k < values.len() && values[k] == doms[k].1 {
+                values[k] = doms[k].0;
+                k += 1;
+            }
+            if k == values.len() {
+                return solutions;
+            }
+            values[k] += 1;
+        }
+    }
+
+    #[test]
+    fn diffn_solutions_test() {
+        let instances = vec![
+            (
+                2,
+                2,
+                vec![(2, 2), (1, 1), (2, 2)],
+                vec![(1, 1), (2, 2), (2, 2)],
+            ),
+            (
+                3,
+                1,
+                vec![(1, 2), (2, 2), (1, 1)],
+                vec![(2, 2), (1, 2), (0, 1)],
+            ),
+            (
+                2,
+                2,
+                vec![(1, 2), (1, 2), (1, 1), (1, 1)],
+                vec![(2, 2), (1, 1), (2, 2), (1, 1)],
+            ),
+        ];
+        for (max_x, max_y, w, h) in instances {
+            let expected = brute_force(max_x, max_y, &w, &h);
+            assert_eq!(count_diffn(max_x, max_y, &w, &h, false), expected);
+            assert_eq!(count_diffn(max_x, max_y, &w, &h, true), expected);
+        }
+    }
+}
@@ -0,0 +1,711 @@
+// Copyright 2018 Pierre Talbot (IRCAM)
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+
+//     http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! The `Disjunctive` constraint (also known as unary resource) ensures that the tasks do not overlap in time.
+//! Tasks with a duration of zero can be scheduled at any time, even in the middle of another task.
+//! The propagator implements the overload checking, detectable precedences, not-first/not-last and edge-finding rules over Θ-Λ-trees as described in `Filtering algorithms for the unary resource constraint`, Vilím, 2008.
+//! The durations are variables and we reason on their lower bounds.
+//! Every rule is implemented for the earliest starting times (or latest completion times for not-last) and applied on the mirrored tasks for the other bound.
+
+use concept::*;
+use gcollections::*;
+use kernel::*;
+use logic::*;
+use model::*;
+use propagation::events::*;
+use propagation::*;
+use propagators::cmp::*;
+use std::cmp::{max, min};
+use term::*;
+use trilean::SKleene;
+use trilean::SKleene::*;
+
+#[derive(Debug)]
+pub struct Disjunctive<VStore> {
+    starts: Vec<Var<VStore>>,
+    durations: Vec<Var<VStore>>,
+}
+
+impl<VStore> Disjunctive<VStore> {
+    pub fn new(starts: Vec<Var<VStore>>, durations: Vec<Var<VStore>>) -> Self {
+        assert_eq!(
+            starts.len(),
+            durations.len(),
+            "Every task in `Disjunctive` must have a start and a duration."
+        );
+        Disjunctive { starts, durations }
+    }
+}
+
+impl<VStore> Clone for Disjunctive<VStore>
+where
+    VStore: Collection,
+{
+    fn clone(&self) -> Self {
+        Disjunctive {
+            starts: self.starts.iter().map(|v| v.bclone()).collect(),
+            durations: self.durations.iter().map(|v| v.bclone()).collect(),
+        }
+    }
+}
+
+impl<VStore, Domain, Bound> NotFormula<VStore> for Disjunctive<VStore>
+where
+    VStore: VStoreConcept<Item = Domain> + 'static,
+    Domain: IntDomain<Item = Bound> + 'static,
+    Bound: IntBound + 'static,
+{
+    /// At least two tasks with a non-zero duration overlap.
+    fn not(&self) -> Formula<VStore> {
+        let positive = |d: &Var<VStore>| {
+            Box::new(XLessY::new(
+                Box::new(Constant::new(Bound::zero())),
+                d.bclone(),
+            )) as Formula<VStore>
+        };
+        let mut overlaps = vec![];
+        for i in 0..self.starts.len() {
+            for j in i + 1..self.starts.len() {
+                overlaps.push(Box::new(Conjunction::new(vec![
+                    positive(&self.durations[i]),
+                    positive(&self.durations[j]),
+                    Box::new(XLessYPlusZ::new(
+                        self.starts[i].bclone(),
+                        self.starts[j].bclone(),
+                        self.durations[j].bclone(),
+                    )),
+                    Box::new(XLessYPlusZ::new(
+                        self.starts[j].bclone(),
+                        self.starts[i].bclone(),
+                        self.durations[i].bclone(),
+                    )),
+                ])) as Formula<VStore>);
+            }
+        }
+        Box::new(Disjunction::new(overlaps))
+    }
+}
+
+impl<VStore> DisplayStateful<Model> for Disjunctive<VStore> {
+    fn display(&self, model: &Model) {
+        print!("disjunctive(");
+        model.display_array(&self.starts);
+        print!(", ");
+        model.display_array(&self.durations);
+        print!(")");
+    }
+}
+
+impl<VStore, Domain, Bound> Subsumption<VStore> for Disjunctive<VStore>
+where
+    VStore: Collection<Item = Domain>,
+    Domain: IntDomain<Item = Bound>,
+    Bound: IntBound,
+{
+    fn is_subsumed(&self, vstore: &VStore) -> SKleene {
+        // False: the tasks cannot be all executed in their time windows.
+        // True: the time windows of the tasks do not overlap.
+        // Unknown: Everything else.
+        if overload(&self.tasks(vstore)) {
+            return False;
+        }
+        let mut windows: Vec<_> = self
+            .starts
+            .iter()
+            .zip(self.durations.iter())
+            .map(|(s, d)| (s.read(vstore), d.read(vstore).upper()))
+            .filter(|(_, dmax)| *dmax > Bound::zero())
+            .map(|(s, dmax)| (s.lower(), s.upper() + dmax))
+            .collect();
+        windows.sort();
+        if windows.windows(2).all(|w| w[0].1 <= w[1].0) {
+            True
+        } else {
+            Unknown
+        }
+    }
+}
+
+impl<VStore, Domain, Bound> Disjunctive<VStore>
+where
+    VStore: Collection<Item = Domain>,
+    Domain: IntDomain<Item = Bound>,
+    Bound: IntBound,
+{
+    /// The tasks with a positive duration, the other ones do not participate to the constraint.
+    fn tasks(&self, vstore: &VStore) -> Vec<Task<Bound>> {
+        (0..self.starts.len())
+            .map(|i| {
+                let s = self.starts[i].read(vstore);
+                let p = self.durations[i].read(vstore).lower();
+                Task {
+                    index: i,
+                    est: s.lower(),
+                    lct: s.upper() + p.clone(),
+                    p,
+                }
+            })
+            .filter(|t| t.p > Bound::zero())
+            .collect()
+    }
+}
+
+impl<VStore, Domain, Bound> Propagator<VStore> for Disjunctive<VStore>
+where
+    VStore: Collection<Item = Domain>,
+    Domain: IntDomain<Item = Bound>,
+    Bound: IntBound,
+{
+    fn propagate(&mut self, vstore: &mut VStore) -> bool {
+        let tasks = self.tasks(vstore);
+        if tasks.len() < 2 {
+            return true;
+        }
+        let mirror: Vec<_> = tasks.iter().map(|t| t.mirror()).collect();
+        let (est_ef, lct_ef) = match (edge_finding(&tasks), edge_finding(&mirror)) {
+            (Some(est), Some(lct)) => (est, lct),
+            _ => return false,
+        };
+        let est_dp = detectable_precedences(&tasks);
+        let lct_dp = detectable_precedences(&mirror);
+        let lct_nl = not_last(&tasks);
+        let est_nf = not_last(&mirror);
+        for (k, task) in tasks.iter().enumerate() {
+            let est = max(
+                max(est_ef[k].clone(), est_dp[k].clone()),
+                -est_nf[k].clone(),
+            );
+            let lct = min(
+                min(-lct_ef[k].clone(), -lct_dp[k].clone()),
+                lct_nl[k].clone(),
+            );
+            let s = self.starts[task.index].read(vstore);
+            let lst = lct - task.p.clone();
+            if !self.starts[task.index].update(vstore, s.shrink_left(est).shrink_right(lst)) {
+                return false;
+            }
+        }
+        true
+    }
+
+    fn cost(&self) -> PropagatorCost {
+        PropagatorCost::Quadratic
+    }
+}
+
+impl<VStore> PropagatorDependencies<FDEvent> for Disjunctive<VStore> {
+    fn dependencies(&self) -> Vec<(usize, FDEvent)> {
+        let mut deps: Vec<_> = self
+            .starts
+            .iter()
+            .chain(self.durations.iter())
+            .flat_map(|v| v.dependencies(FDEvent::Bound))
+            .collect();
+        deps.sort();
+        deps.dedup();
+        deps
+    }
+}
+
+/// A task with a positive processing time `p`, the earliest starting time `est` and the latest completion time `lct`.
+/// `index` is the position of the task in the constraint.
+#[derive(Clone, Debug)]
+struct Task<Bound> {
+    index: usize,
+    est: Bound,
+    lct: Bound,
+    p: Bound,
+}
+
+impl<Bound> Task<Bound>
+where
+    Bound: IntBound,
+{
+    fn ect(&self) -> Bound {
+        self.est.clone() + self.p.clone()
+    }
+
+    fn lst(&self) -> Bound {
+        self.lct.clone() - self.p.clone()
+    }
+
+    /// The task in the reversed time, its `est` is the opposite of the `lct` and vice versa.
+    fn mirror(&self) -> Self {
+        Task {
+            index: self.index,
+            est: -self.lct.clone(),
+            lct: -self.est.clone(),
+            p: self.p.clone(),
+        }
+    }
+}
+
+/// Sort the indexes of `tasks` with the key `f`.
+fn sorted_by<Bound, F>(tasks: &[Task<Bound>], f: F) -> Vec<usize>
+where
+    Bound: IntBound,
+    F: Fn(&Task<Bound>) -> Bound,
+{
+    let mut order: Vec<usize> = (0..tasks.len()).collect();
+    order.sort_by_key(|&i| f(&tasks[i]));
+    order
+}
+
+/// `true` if the tasks cannot be all executed in their time windows.
+fn overload<Bound: IntBound>(tasks: &[Task<Bound>]) -> bool {
+    let mut tree = ThetaLambdaTree::new(tasks);
+    for j in sorted_by(tasks, |t| t.lct.clone()) {
+        tree.insert(j);
+        if tree.ect() > tasks[j].lct {
+            return true;
+        }
+    }
+    false
+}
+
+/// Returns the new earliest starting times, or `None` if the tasks are overloaded.
+fn edge_finding<Bound: IntBound>(tasks: &[Task<Bound>]) -> Option<Vec<Bound>> {
+    let mut est: Vec<Bound> = tasks.iter().map(|t| t.est.clone()).collect();
+    let mut tree = ThetaLambdaTree::new(tasks);
+    for i in 0..tasks.len() {
+        tree.insert(i);
+    }
+    let mut queue = sorted_by(tasks, |t| t.lct.clone());
+    let mut j = queue.pop().unwrap();
+    if tree.ect() > tasks[j].lct {
+        return None;
+    }
+    while let Some(next) = queue.pop() {
+        tree.insert_gray(j);
+        j = next;
+        if tree.ect() > tasks[j].lct {
+            return None;
+        }
+        while tree.ect_bar() > tasks[j].lct {
+            let i = tree
+                .responsible_ect_bar()
+                .expect("A gray task is responsible of `ect_bar > ect`.");
+            est[i] = max(est[i].clone(), tree.ect());
+            tree.remove(i);
+        }
+    }
+    Some(est)
+}
+
+/// Returns the new earliest starting times.
+fn detectable_precedences<Bound: IntBound>(tasks: &[Task<Bound>]) -> Vec<Bound> {
+    let mut est: Vec<Bound> = tasks.iter().map(|t| t.est.clone()).collect();
+    let mut tree = ThetaLambdaTree::new(tasks);
+    let by_lst = sorted_by(tasks, |t| t.lst());
+    let mut next = 0;
+    for i in sorted_by(tasks, |t| t.ect()) {
+        while next < by_lst.len() && tasks[i].ect() > tasks[by_lst[next]].lst() {
+            tree.insert(by_lst[next]);
+            next += 1;
+        }
+        est[i] = max(est[i].clone(), tree.ect_without(i));
+    }
+    est
+}
+
+/// Returns the new latest completion times.
+fn not_last<Bound: IntBound>(tasks: &[Task<Bound>]) -> Vec<Bound> {
+    let mut lct: Vec<Bound> = tasks.iter().map(|t| t.lct.clone()).collect();
+    let mut tree = ThetaLambdaTree::new(tasks);
+    let by_lst = sorted_by(tasks, |t| t.lst());
+    let mut next = 0;
+    for i in sorted_by(tasks, |t| t.lct.clone()) {
+        while next < by_lst.len() && tasks[i].lct > tasks[by_lst[next]].lst() {
+            tree.insert(by_lst[next]);
+            next += 1;
+        }
+        // The task with the largest `lst` inserted, other than `i`.
+        let last = by_lst[..next].iter().rev().find(|&&j| j != i);
+        if let Some(&j) = last {
+            if tree.ect_without(i) > tasks[i].lst() {
+                lct[i] = min(lct[i].clone(), tasks[j].lst());
+            }
+        }
+    }
+    lct
+}
+
+/// A node of the Θ-Λ-tree, the values with a `bar` are computed with at most one gray task (in Λ).
+/// `responsible_*` are the gray tasks responsible of the `bar` values, if any.
+#[derive(Clone)]
+struct Node<Bound> {
+    sum_p: Bound,
+    ect: Bound,
+    sum_p_bar: Bound,
+    ect_bar: Bound,
+    responsible_sum_p: Option<usize>,
+    responsible_ect: Option<usize>,
+}
+
+/// Balanced binary tree where the leaves are the tasks sorted by `est`.
+/// A task is either in Θ (white), in Λ (gray) or not in the tree.
+struct ThetaLambdaTree<'a, Bound: 'a> {
+    tasks: &'a [Task<Bound>],
+    nodes: Vec<Node<Bound>>,
+    /// Index of the leaf of each task.
+    leaves: Vec<usize>,
+    neg_inf: Bound,
+}
+
+impl<'a, Bound> ThetaLambdaTree<'a, Bound>
+where
+    Bound: IntBound,
+{
+    fn new(tasks: &'a [Task<Bound>]) -> Self {
+        let mut size = 1;
+        while size < tasks.len() {
+            size *= 2;
+        }
+        // Smaller than any `ect` reachable in the tree, even if we add every duration twice.
+        let total_p = tasks.iter().fold(Bound::zero(), |acc, t| acc + t.p.clone());
+        let min_est = tasks
+            .iter()
+            .map(|t| t.est.clone())
+            .min()
+            .unwrap_or_else(Bound::zero);
+        let two = Bound::one() + Bound::one();
+        let neg_inf = min_est - total_p * two - Bound::one();
+        let empty = Node {
+            sum_p: Bound::zero(),
+            ect: neg_inf.clone(),
+            sum_p_bar: Bound::zero(),
+            ect_bar: neg_inf.clone(),
+            responsible_sum_p: None,
+            responsible_ect: None,
+        };
+        let mut leaves = vec![0; tasks.len()];
+        for (pos, i) in sorted_by(tasks, |t| t.est.clone()).into_iter().enumerate() {
+            leaves[i] = size + pos;
+        }
+        ThetaLambdaTree {
+            tasks,
+            nodes: vec![empty; 2 * size],
+            leaves,
+            neg_inf,
+        }
+    }
+
+    fn ect(&self) -> Bound {
+        self.nodes[1].ect.clone()
+    }
+
+    fn ect_bar(&self) -> Bound {
+        self.nodes[1].ect_bar.clone()
+    }
+
+    fn responsible_ect_bar(&self) -> Option<usize> {
+        self.nodes[1].responsible_ect
+    }
+
+    fn insert(&mut self, i: usize) {
+        let t = &self.tasks[i];
+        let leaf = Node {
+            sum_p: t.p.clone(),
+            ect: t.ect(),
+            sum_p_bar: t.p.clone(),
+            ect_bar: t.ect(),
+            responsible_sum_p: None,
+            responsible_ect: None,
+        };
+        self.set_leaf(i, leaf);
+    }
+
+    fn insert_gray(&mut self, i: usize) {
+        let t = &self.tasks[i];
+        let leaf = Node {
+            sum_p: Bound::zero(),
+            ect: self.neg_inf.clone(),
+            sum_p_bar: t.p.clone(),
+            ect_bar: t.ect(),
+            responsible_sum_p: Some(i),
+            responsible_ect: Some(i),
+        };
+        self.set_leaf(i, leaf);
+    }
+
+    fn remove(&mut self, i: usize) {
+        let leaf = Node {
+            sum_p: Bound::zero(),
+            ect: self.neg_inf.clone(),
+            sum_p_bar: Bound::zero(),
+            ect_bar: self.neg_inf.clone(),
+            responsible_sum_p: None,
+            responsible_ect: None,
+        };
+        self.set_leaf(i, leaf);
+    }
+
+    /// The `ect` of Θ without the task `i`.
+    fn ect_without(&mut self, i: usize) -> Bound {
+        let leaf = self.nodes[self.leaves[i]].clone();
+        self.remove(i);
+        let ect = self.ect();
+        self.set_leaf(i, leaf);
+        ect
+    }
+
+    fn set_leaf(&mut self, i: usize, leaf: Node<Bound>) {
+        let mut node = self.leaves[i];
+        self.nodes[node] = leaf;
+        while node > 1 {
+            node /= 2;
+            self.nodes[node] = Self::combine(&self.nodes[2 * node], &self.nodes[2 * node + 1]);
+        }
+    }
+
+    /// Among values of equal magnitude, we prefer the one with a responsible gray task.
+    fn best(a: (Bound, Option<usize>), b: (Bound, Option<usize>)) -> (Bound, Option<usize>) {
+        if a.0 > b.0 || (a.0 == b.0 && a.1.is_some()) {
+            a
+        } else {
+            b
+        }
+    }
+
+    fn combine(l: &Node<Bound>, r: &Node<Bound>) -> Node<Bound> {
+        let (sum_p_bar, responsible_sum_p) = Self::best(
+            (l.sum_p_bar.clone() + r.sum_p.clone(), l.responsible_sum_p),
+            (l.sum_p.clone() + r.sum_p_bar.clone(), r.responsible_sum_p),
+        );
+        let (ect_bar, responsible_ect) = Self::best(
+            Self::best(
+                (r.ect_bar.clone(), r.responsible_ect),
+                (l.ect.clone() + r.sum_p_bar.clone(), r.responsible_sum_p),
+            ),
+            (l.ect_bar.clone() + r.sum_p.clone(), l.responsible_ect),
+        );
+        Node {
+            sum_p: l.sum_p.clone() + r.sum_p.clone(),
+            ect: max(r.ect.clone(), l.ect.clone() + r.sum_p.clone()),
+            sum_p_bar,
+            ect_bar,
+            responsible_sum_p,
+            responsible_ect,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use gcollections::ops::*;
+    use interval::interval::*;
+    use interval::interval_set::*;
+    use search::test::*;
+    use variable::ops::*;
+    use variable::VStoreFD;
+
+    fn task(index: usize, est: i32, lct: i32, p: i32) -> Task<i32> {
+        Task { index, est, lct, p }
+    }
+
+    #[test]
+    fn overload_test() {
+        assert!(overload(&[task(0, 0, 3, 2), task(1, 0, 3, 2)]));
+        assert!(!overload(&[task(0, 0, 4, 2), task(1, 0, 4, 2)]));
+        assert!(overload(&[
+            task(0, 0, 5, 2),
+            task(1, 1, 5, 2),
+            task(2, 1, 5, 2)
+        ]));
+        assert_eq!(edge_finding(&[task(0, 0, 3, 2), task(1, 0, 3, 2)]), None);
+    }
+
+    #[test]
+    fn edge_finding_test() {
+        // The task 0 must be executed after the tasks 1 and 2, which is not detected by the precedences.
+        let tasks = vec![task(0, 0, 20, 3), task(1, 1, 6, 2), task(2, 2, 6, 2)];
+        assert_eq!(edge_finding(&tasks), Some(vec![5, 1, 2]));
+        assert_eq!(detectable_precedences(&tasks), vec![0, 1, 2]);
+    }
+
+    #[test]
+    fn detectable_precedences_test() {
+        // The task 1 is necessarily before the task 0.
+        let tasks = vec![task(0, 0, 20, 5), task(1, 0, 4, 3)];
+        assert_eq!(detectable_precedences(&tasks), vec![3, 0]);
+        // The task 1 is before the task 2, and both are before the task 0.
+        let tasks = vec![task(0, 0, 20, 5), task(1, 0, 4, 3), task(2, 2, 6, 2)];
+        assert_eq!(detectable_precedences(&tasks), vec![5, 0, 3]);
+    }
+
+    #[test]
+    fn not_last_test() {
+        // The task 0 cannot be the last one, it must end before the latest start of the task 1.
+        let tasks = vec![task(0, 0, 10, 3), task(1, 5, 9, 4)];
+        assert_eq!(not_last(&tasks), vec![5, 9]);
+        // Not-first on the mirror: nothing to prune.
+        let mirror: Vec<_> = tasks.iter().map(|t| t.mirror()).collect();
+        assert_eq!(not_last(&mirror), vec![0, -5]);
+    }
+
+    fn disjunctive_test_one(
+        test_num: u32,
+        starts: Vec<(i32, i32)>,
+        durations: Vec<(i32, i32)>,
+        before: SKleene,
+        after: SKleene,
+        expected: Option<Vec<(i32, i32)>>,
+    ) {
+        let mut vstore = VStoreFD::empty();
+        let starts: Vec<Var<VStoreFD>> = starts
+            .into_iter()
+            .map(|s| Box::new(vstore.alloc(s.to_interval())) as Var<VStoreFD>)
+            .collect();
+        let durations: Vec<Var<VStoreFD>> = durations
+            .into_iter()
+            .map(|d| Box::new(vstore.alloc(d.to_interval())) as Var<VStoreFD>)
+            .collect();
+        let num_tasks = starts.len();
+        let mut propagator = Disjunctive::new(starts, durations);
+        assert_eq!(
+            propagator.is_subsumed(&vstore),
+            before,
+            "Test {}.",
+            test_num
+        );
+        let success = propagator.propagate(&mut vstore);
+        assert_eq!(success, expected.is_some(), "Test {}.", test_num);
+        assert_eq!(propagator.is_subsumed(&vstore), after, "Test {}.", test_num);
+        if let Some(expected) = expected {
+            let starts: Vec<_> = vstore
+                .iter()
+                .take(num_tasks)
+                .map(|s| (s.lower(), s.upper()))
+                .collect();
+            assert_eq!(starts, expected, "Test {}.", test_num);
+        }
+    }
+
+    #[test]
+    fn disjunctive_test() {
+        disjunctive_test_one(
+            1,
+            vec![(0, 0), (2, 2)],
+            vec![(2, 2), (3, 3)],
+            True,
+            True,
+            Some(vec![(0, 0), (2, 2)]),
+        );
+        disjunctive_test_one(
+            2,
+            vec![(0, 0), (1, 1)],
+            vec![(2, 2), (3, 3)],
+            False,
+            False,
+            None,
+        );
+        // Zero-duration tasks can be executed during other tasks.
+        disjunctive_test_one(
+            3,
+            vec![(0, 0), (1, 1)],
+            vec![(2, 2), (0, 0)],
+            True,
+            True,
+            Some(vec![(0, 0), (1, 1)]),
+        );
+        // The task 1 must be before the task 0, which is pushed to the right.
+        disjunctive_test_one(
+            4,
+            vec![(0, 15), (0, 1)],
+            vec![(5, 5), (3, 3)],
+            Unknown,
+            Unknown,
+            Some(vec![(3, 15), (0, 1)]),
+        );
+        // Both bounds are pruned: the task 0 is in the middle.
+        disjunctive_test_one(
+            5,
+            vec![(0, 10), (0, 0), (7, 7)],
+            vec![(2, 5), (3, 3), (5, 5)],
+            Unknown,
+            Unknown,
+            Some(vec![(3, 5), (0, 0), (7, 7)]),
+        );
+        // Edge-finding: the task 0 is after the tasks 1 and 2.
+        disjunctive_test_one(
+            6,
+            vec![(0, 17), (1, 4), (2, 4)],
+            vec![(3, 3), (2, 2), (2, 2)],
+            Unknown,
+            Unknown,
+            Some(vec![(5, 17), (1, 4), (2, 4)]),
+        );
+    }
+
+    fn count_disjunctive(durations: &[(i32, i32)], horizon: i32, decomposition: bool) -> usize {
+        let mut space = FDSpace::empty();
+        let starts: Vec<Var<VStore>> = durations
+            .iter()
+            .map(|_| Box::new(space.vstore.alloc((0, horizon).to_interval_set())) as Var<VStore>)
+            .collect();
+        let durations: Vec<Var<VStore>> = durations
+            .iter()
+            .map(|&d| Box::new(space.vstore.alloc(d.to_interval_set())) as Var<VStore>)
+            .collect();
+        let num_vars = starts.len() * 2;
+        if decomposition {
+            // s[i] + d[i] <= s[j] \/ s[j] + d[j] <= s[i] \/ d[i] < 1 \/ d[j] < 1
+            for i in 0..starts.len() {
+                for j in i + 1..starts.len() {
+                    space.cstore.alloc(Box::new(Disjunction::new(vec![
+                        Box::new(x_geq_y_plus_z(
+                            starts[j].bclone(),
+                            starts[i].bclone(),
+                            durations[i].bclone(),
+                        )),
+                        Box::new(x_geq_y_plus_z(
+                            starts[i].bclone(),
+                            starts[j].bclone(),
+                            durations[j].bclone(),
+                        )),
+                        Box::new(XLessY::new(
+                            durations[i].bclone(),
+                            Box::new(Constant::new(1)),
+                        )),
+                        Box::new(XLessY::new(
+                            durations[j].bclone(),
+                            Box::new(Constant::new(1)),
+                        )),
+                    ])));
+                }
+            }
+        } else {
+            space
+                .cstore
+                .alloc(Box::new(Disjunctive::new(starts, durations)));
+        }
+        count_assignments(space, num_vars)
+    }
+
+    #[test]
+    fn disjunctive_decomposition_solutions_test() {
+        let instances = vec![
+            (vec![(2, 2), (1, 1), (3, 3)], 6),
+            (vec![(1, 2), (2, 3), (0, 1)], 5),
+            (vec![(2, 2), (2, 2), (1, 1), (1, 1)], 6),
+        ];
+        for (durations, horizon) in instances {
+            assert_eq!(
+                count_disjunctive(&durations, horizon, false),
+                count_disjunctive(&durations, horizon, true)
+            );
+        }
+    }
+}
@@ -12,17 +12,24 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+//! The `Distinct` propagator (also known as `AllDifferent`) enforces either bounds consistency or domain consistency.
+//! The bounds consistency is obtained with the Hall intervals algorithm described in `A fast and simple algorithm for bounds consistency of the alldifferent constraint`, López-Ortiz and al., 2003.
+//! The domain consistency is obtained with the matching algorithm described in `A filtering algorithm for constraints of difference in CSPs`, Régin, 1994. It removes holes in the domains and is therefore mostly useful with domains such as `IntervalSet`.
+
 use concept::*;
+use gcollections::ops::*;
 use gcollections::*;
 use kernel::*;
 use logic::*;
 use model::*;
 use propagation::events::*;
 use propagation::*;
-use propagators::cmp::x_neq_y::*;
+use propagators::cmp::x_eq_y::*;
+use std::cmp::min;
 use trilean::SKleene;
+use trilean::SKleene::*;
 
-/// Precondition: `vars.len() > 1`.
+/// Precondition: `vars.len() > 0`.
 pub fn join_distinct<VStore, CStore, Domain, Bound>(
     _vstore: &mut VStore,
     cstore: &mut CStore,
@@ -33,56 +40,57 @@ pub fn join_distinct<VStore, CStore, Domain, Bound>(
     Bound: IntBound + 'static,
     CStore: IntCStore<VStore> + 'static,
 {
-    assert!(
-        vars.len() > 0,
-        "Variable array in `Distinct` must be non-empty."
-    );
-    for i in 0..vars.len() - 1 {
-        for j in i + 1..vars.len() {
-            cstore.alloc(Box::new(XNeqY::new(vars[i].bclone(), vars[j].bclone())));
-        }
-    }
+    cstore.alloc(Box::new(Distinct::new(vars)));
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DistinctConsistency {
+    /// Only the bounds of the domains are pruned (Hall intervals).
+    Bounds,
+    /// Every value that does not belong to a maximum matching is pruned (Régin).
+    Domain,
 }
 
 #[derive(Debug)]
 pub struct Distinct<VStore> {
-    conj: Conjunction<VStore>,
     vars: Vec<Var<VStore>>,
+    consistency: DistinctConsistency,
 }
 
-impl<VStore> NotFormula<VStore> for Distinct<VStore>
-where
-    VStore: Collection + 'static,
-{
-    fn not(&self) -> Formula<VStore> {
-        self.conj.not()
+impl<VStore> Distinct<VStore> {
+    /// Precondition: `vars.len() > 0`.
+    pub fn new(vars: Vec<Var<VStore>>) -> Self {
+        Distinct::with_consistency(vars, DistinctConsistency::Bounds)
+    }
+
+    /// Precondition: `vars.len() > 0`.
+    pub fn with_consistency(vars: Vec<Var<VStore>>, consistency: DistinctConsistency) -> Self {
+        assert!(
+            !vars.is_empty(),
+            "Variable array in `Distinct` must be non-empty."
+        );
+        Distinct { vars, consistency }
     }
 }
 
-impl<VStore, Domain, Bound> Distinct<VStore>
+impl<VStore, Domain, Bound> NotFormula<VStore> for Distinct<VStore>
 where
     VStore: VStoreConcept<Item = Domain> + 'static,
     Domain: IntDomain<Item = Bound> + 'static,
     Bound: IntBound + 'static,
 {
-    /// Precondition: `vars.len() > 1`.
-    pub fn new(vars: Vec<Var<VStore>>) -> Self {
-        assert!(
-            vars.len() > 0,
-            "Variable array in `Distinct` must be non-empty."
-        );
-        let mut props = vec![];
-        for i in 0..vars.len() - 1 {
-            for j in i + 1..vars.len() {
-                let i_neq_j =
-                    Box::new(XNeqY::new(vars[i].bclone(), vars[j].bclone())) as Formula<VStore>;
-                props.push(i_neq_j);
+    /// At least two variables are equal.
+    fn not(&self) -> Formula<VStore> {
+        let mut eqs = vec![];
+        for i in 0..self.vars.len() {
+            for j in i + 1..self.vars.len() {
+                eqs.push(
+                    Box::new(XEqY::new(self.vars[i].bclone(), self.vars[j].bclone()))
+                        as Formula<VStore>,
+                );
             }
         }
-        Distinct {
-            conj: Conjunction::new(props),
-            vars,
-        }
+        Box::new(Disjunction::new(eqs))
     }
 }
 
@@ -92,8 +100,8 @@ where
 {
     fn clone(&self) -> Self {
         Distinct {
-            conj: self.conj.clone(),
             vars: self.vars.iter().map(|v| v.bclone()).collect(),
+            consistency: self.consistency,
         }
     }
 }
@@ -104,34 +112,552 @@ impl<VStore> DisplayStateful<Model> for Distinct<VStore> {
     }
 }
 
-impl<VStore> Subsumption<VStore> for Distinct<VStore> {
+impl<VStore, Domain, Bound> Subsumption<VStore> for Distinct<VStore>
+where
+    VStore: Collection<Item = Domain>,
+    Domain: IntDomain<Item = Bound>,
+    Bound: IntBound,
+{
     fn is_subsumed(&self, vstore: &VStore) -> SKleene {
-        self.conj.is_subsumed(vstore)
+        // False: there are more variables than values in an interval.
+        // True: the domains do not overlap.
+        // Unknown: Everything else.
+        let doms = self.read_all(vstore);
+        if !HallIntervals::new(&doms).filter() {
+            False
+        } else if Self::pairwise_disjoint(&doms) {
+            True
+        } else {
+            Unknown
+        }
     }
 }
 
-impl<VStore> Propagator<VStore> for Distinct<VStore> {
+impl<VStore, Domain, Bound> Distinct<VStore>
+where
+    VStore: Collection<Item = Domain>,
+    Domain: IntDomain<Item = Bound>,
+    Bound: IntBound,
+{
+    fn read_all(&self, vstore: &VStore) -> Vec<Domain> {
+        self.vars.iter().map(|v| v.read(vstore)).collect()
+    }
+
+    fn pairwise_disjoint(doms: &[Domain]) -> bool {
+        let mut sorted: Vec<_> = doms.iter().collect();
+        sorted.sort_by_key(|d| d.lower());
+        sorted.windows(2).all(|w| w[0].upper() < w[1].lower())
+    }
+
+    fn propagate_doms(&mut self, vstore: &mut VStore, doms: Vec<Domain>) -> bool {
+        match self.consistency {
+            DistinctConsistency::Bounds => self.propagate_bounds(vstore, doms),
+            DistinctConsistency::Domain => self.propagate_domain(vstore, doms),
+        }
+    }
+
+    fn propagate_bounds(&mut self, vstore: &mut VStore, doms: Vec<Domain>) -> bool {
+        let mut hall = HallIntervals::new(&doms);
+        if !hall.filter() {
+            return false;
+        }
+        for (i, (var, dom)) in self.vars.iter_mut().zip(doms).enumerate() {
+            let (lb, ub) = hall.bounds_of(i);
+            if !var.update(vstore, dom.shrink_left(lb).shrink_right(ub)) {
+                return false;
+            }
+        }
+        true
+    }
+
+    fn propagate_domain(&mut self, vstore: &mut VStore, doms: Vec<Domain>) -> bool {
+        let mut graph = ValueGraph::new(&doms);
+        if !graph.maximum_matching() {
+            return false;
+        }
+        let inconsistent = graph.inconsistent_values();
+        for ((var, mut dom), values) in self.vars.iter_mut().zip(doms).zip(inconsistent) {
+            if !values.is_empty() {
+                for v in values {
+                    dom = dom.difference(&graph.values[v]);
+                }
+                if !var.update(vstore, dom) {
+                    return false;
+                }
+            }
+        }
+        true
+    }
+}
+
+impl<VStore, Domain, Bound> Propagator<VStore> for Distinct<VStore>
+where
+    VStore: Collection<Item = Domain>,
+    Domain: IntDomain<Item = Bound>,
+    Bound: IntBound,
+{
     fn propagate(&mut self, vstore: &mut VStore) -> bool {
-        self.conj.propagate(vstore)
+        let doms = self.read_all(vstore);
+        self.propagate_doms(vstore, doms)
+    }
+
+    /// The subsumption is checked on the domains read for the propagation, a failure is detected by the propagation.
+    fn propagate_status(&mut self, vstore: &mut VStore) -> PropagatorStatus {
+        let doms = self.read_all(vstore);
+        if Self::pairwise_disjoint(&doms) {
+            PropagatorStatus::Subsumed
+        } else if self.propagate_doms(vstore, doms) {
+            PropagatorStatus::NotSubsumed
+        } else {
+            PropagatorStatus::Failed
+        }
+    }
+
+    fn cost(&self) -> PropagatorCost {
+        match self.consistency {
+            DistinctConsistency::Bounds => PropagatorCost::Quadratic,
+            DistinctConsistency::Domain => PropagatorCost::Cubic,
+        }
     }
 }
 
 impl<VStore> PropagatorDependencies<FDEvent> for Distinct<VStore> {
     fn dependencies(&self) -> Vec<(usize, FDEvent)> {
+        let event = match self.consistency {
+            DistinctConsistency::Bounds => FDEvent::Bound,
+            DistinctConsistency::Domain => FDEvent::Inner,
+        };
         self.vars
             .iter()
-            .flat_map(|v| v.dependencies(FDEvent::Inner))
+            .flat_map(|v| v.dependencies(event))
             .collect()
     }
 }
 
+/// Variables are represented by the intervals `[mins[i]..maxs[i]-1]`, the upper bounds are exclusive.
+/// `bounds` contains the sorted and distinct lower and upper bounds of all the intervals plus two sentinels at its extremities, `minrank` and `maxrank` are the positions of the bounds of each interval inside `bounds`.
+struct HallIntervals<Bound> {
+    mins: Vec<Bound>,
+    maxs: Vec<Bound>,
+    minrank: Vec<usize>,
+    maxrank: Vec<usize>,
+    minsorted: Vec<usize>,
+    maxsorted: Vec<usize>,
+    bounds: Vec<Bound>,
+}
+
+impl<Bound> HallIntervals<Bound>
+where
+    Bound: IntBound,
+{
+    fn new<Domain>(doms: &[Domain]) -> Self
+    where
+        Domain: Bounded<Item = Bound>,
+    {
+        let n = doms.len();
+        let mut hall = HallIntervals {
+            mins: doms.iter().map(|d| d.lower()).collect(),
+            maxs: doms.iter().map(|d| d.upper() + Bound::one()).collect(),
+            minrank: vec![0; n],
+            maxrank: vec![0; n],
+            minsorted: (0..n).collect(),
+            maxsorted: (0..n).collect(),
+            bounds: vec![],
+        };
+        hall.sort();
+        hall
+    }
+
+    fn bounds_of(&self, var: usize) -> (Bound, Bound) {
+        (
+            self.mins[var].clone(),
+            self.maxs[var].clone() - Bound::one(),
+        )
+    }
+
+    fn sort(&mut self) {
+        let (mins, maxs) = (&self.mins, &self.maxs);
+        self.minsorted.sort_by(|&a, &b| mins[a].cmp(&mins[b]));
+        self.maxsorted.sort_by(|&a, &b| maxs[a].cmp(&maxs[b]));
+        let two = Bound::one() + Bound::one();
+        let n = self.mins.len();
+        let mut min = self.mins[self.minsorted[0]].clone();
+        let mut max = self.maxs[self.maxsorted[0]].clone();
+        let mut last = min.clone() - two.clone();
+        self.bounds = vec![last.clone()];
+        let (mut i, mut j) = (0, 0);
+        loop {
+            if i < n && min < max {
+                if min != last {
+                    last = min.clone();
+                    self.bounds.push(min.clone());
+                }
+                self.minrank[self.minsorted[i]] = self.bounds.len() - 1;
+                i += 1;
+                if i < n {
+                    min = self.mins[self.minsorted[i]].clone();
+                }
+            } else {
+                if max != last {
+                    last = max.clone();
+                    self.bounds.push(max.clone());
+                }
+                self.maxrank[self.maxsorted[j]] = self.bounds.len() - 1;
+                j += 1;
+                if j == n {
+                    break;
+                }
+                max = self.maxs[self.maxsorted[j]].clone();
+            }
+        }
+        let sentinel = last + two;
+        self.bounds.push(sentinel);
+    }
+
+    /// Returns `false` if there are more variables than values in an interval.
+    fn filter(&mut self) -> bool {
+        self.filter_lower() && self.filter_upper()
+    }
+
+    fn filter_lower(&mut self) -> bool {
+        let nb = self.bounds.len() - 2;
+        let mut t = vec![0; nb + 2];
+        let mut h = vec![0; nb + 2];
+        let mut d = vec![Bound::zero(); nb + 2];
+        for i in 1..nb + 2 {
+            t[i] = i - 1;
+            h[i] = i - 1;
+            d[i] = self.bounds[i].clone() - self.bounds[i - 1].clone();
+        }
+        for k in 0..self.maxsorted.len() {
+            let v = self.maxsorted[k];
+            let x = self.minrank[v];
+            let y = self.maxrank[v];
+            let mut z = path_max(&t, x + 1);
+            let j = t[z];
+            d[z] = d[z].clone() - Bound::one();
+            if d[z].is_zero() {
+                t[z] = z + 1;
+                z = path_max(&t, t[z]);
+                t[z] = j;
+            }
+            path_set(&mut t, x + 1, z, z);
+            let hall_size = self.bounds[z].clone() - self.bounds[y].clone();
+            if d[z] < hall_size {
+                return false;
+            }
+            if h[x] > x {
+                let w = path_max(&h, h[x]);
+                self.mins[v] = self.bounds[w].clone();
+                path_set(&mut h, x, w, w);
+            }
+            if d[z] == hall_size {
+                let hy = h[y];
+                path_set(&mut h, hy, j - 1, y);
+                h[y] = j - 1;
+            }
+        }
+        true
+    }
+
+    fn filter_upper(&mut self) -> bool {
+        let nb = self.bounds.len() - 2;
+        let mut t = vec![0; nb + 2];
+        let mut h = vec![0; nb + 2];
+        let mut d = vec![Bound::zero(); nb + 2];
+        for i in 0..nb + 1 {
+            t[i] = i + 1;
+            h[i] = i + 1;
+            d[i] = self.bounds[i + 1].clone() - self.bounds[i].clone();
+        }
+        for k in (0..self.minsorted.len()).rev() {
+            let v = self.minsorted[k];
+            let x = self.maxrank[v];
+            let y = self.minrank[v];
+            let mut z = path_min(&t, x - 1);
+            let j = t[z];
+            d[z] = d[z].clone() - Bound::one();
+            if d[z].is_zero() {
+                t[z] = z - 1;
+                z = path_min(&t, t[z]);
+                t[z] = j;
+            }
+            path_set(&mut t, x - 1, z, z);
+            let hall_size = self.bounds[y].clone() - self.bounds[z].clone();
+            if d[z] < hall_size {
+                return false;
+            }
+            if h[x] < x {
+                let w = path_min(&h, h[x]);
+                self.maxs[v] = self.bounds[w].clone();
+                path_set(&mut h, x, w, w);
+            }
+            if d[z] == hall_size {
+                let hy = h[y];
+                path_set(&mut h, hy, j + 1, y);
+                h[y] = j + 1;
+            }
+        }
+        true
+    }
+}
+
+fn path_set(t: &mut [usize], start: usize, end: usize, to: usize) {
+    let mut l = start;
+    while l != end {
+        let k = t[l];
+        t[l] = to;
+        l = k;
+    }
+}
+
+fn path_min(t: &[usize], mut i: usize) -> usize {
+    while t[i] < i {
+        i = t[i];
+    }
+    i
+}
+
+fn path_max(t: &[usize], mut i: usize) -> usize {
+    while t[i] > i {
+        i = t[i];
+    }
+    i
+}
+
+/// Bipartite graph between the variables and the values of their domains.
+/// `adj[x]` contains the indexes in `values` of the values in the domain of `x`.
+struct ValueGraph<Bound> {
+    values: Vec<Bound>,
+    adj: Vec<Vec<usize>>,
+    var_match: Vec<Option<usize>>,
+    val_match: Vec<Option<usize>>,
+}
+
+impl<Bound> ValueGraph<Bound>
+where
+    Bound: IntBound,
+{
+    fn new<Domain>(doms: &[Domain]) -> Self
+    where
+        Domain: IntDomain<Item = Bound>,
+    {
+        let doms_values: Vec<Vec<Bound>> = doms.iter().map(|d| Self::values_of(d)).collect();
+        let mut values: Vec<Bound> = doms_values.iter().flatten().cloned().collect();
+        values.sort();
+        values.dedup();
+        let adj = doms_values
+            .into_iter()
+            .map(|vs| {
+                vs.iter()
+                    .map(|v| values.binary_search(v).unwrap())
+                    .collect()
+            })
+            .collect();
+        ValueGraph {
+            var_match: vec![None; doms.len()],
+            val_match: vec![None; values.len()],
+            values,
+            adj,
+        }
+    }
+
+    fn values_of<Domain>(dom: &Domain) -> Vec<Bound>
+    where
+        Domain: IntDomain<Item = Bound>,
+    {
+        let mut values = vec![];
+        let mut rest = dom.clone();
+        // Iterate over the intervals of `dom`, the values missing in the range of `rest` give the end of its first interval.
+        while !rest.is_empty() {
+            let holes = Domain::new(rest.lower(), rest.upper()).difference(&rest);
+            let upper = if holes.is_empty() {
+                rest.upper()
+            } else {
+                holes.lower() - Bound::one()
+            };
+            let mut v = rest.lower();
+            while v <= upper {
+                values.push(v.clone());
+                v = v + Bound::one();
+            }
+            rest = rest.strict_shrink_left(upper);
+        }
+        values
+    }
+
+    /// Returns `true` if every variable is matched to a value.
+    fn maximum_matching(&mut self) -> bool {
+        for x in 0..self.adj.len() {
+            let mut visited = vec![false; self.values.len()];
+            if !self.augment(x, &mut visited) {
+                return false;
+            }
+        }
+        true
+    }
+
+    /// Search for an augmenting path starting from the variable `x` (Kuhn's algorithm).
+    fn augment(&mut self, x: usize, visited: &mut Vec<bool>) -> bool {
+        for i in 0..self.adj[x].len() {
+            let v = self.adj[x][i];
+            if !visited[v] {
+                visited[v] = true;
+                let free = match self.val_match[v] {
+                    None => true,
+                    Some(y) => self.augment(y, visited),
+                };
+                if free {
+                    self.var_match[x] = Some(v);
+                    self.val_match[v] = Some(x);
+                    return true;
+                }
+            }
+        }
+        false
+    }
+
+    /// The variables are the nodes `0..n` and the values the nodes `n..n+m`.
+    /// Edges of the matching go from the values to the variables and the other ones from the variables to the values.
+    fn oriented_graph(&self) -> Vec<Vec<usize>> {
+        let n = self.adj.len();
+        let mut graph = vec![vec![]; n + self.values.len()];
+        for (x, adj) in self.adj.iter().enumerate() {
+            for &v in adj {
+                if self.var_match[x] == Some(v) {
+                    graph[n + v].push(x);
+                } else {
+                    graph[x].push(n + v);
+                }
+            }
+        }
+        graph
+    }
+
+    /// Nodes from which a free value can be reached.
+    fn reach_free_values(&self, graph: &[Vec<usize>]) -> Vec<bool> {
+        let n = self.adj.len();
+        let mut reverse = vec![vec![]; graph.len()];
+        for (from, succs) in graph.iter().enumerate() {
+            for &to in succs {
+                reverse[to].push(from);
+            }
+        }
+        let mut reach = vec![false; graph.len()];
+        let mut stack: Vec<usize> = (0..self.values.len())
+            .filter(|&v| self.val_match[v].is_none())
+            .map(|v| n + v)
+            .collect();
+        while let Some(node) = stack.pop() {
+            if !reach[node] {
+                reach[node] = true;
+                stack.extend(reverse[node].iter().cloned().filter(|&p| !reach[p]));
+            }
+        }
+        reach
+    }
+
+    /// Precondition: the matching covers every variable.
+    /// An edge belongs to a maximum matching if it is in the current matching, in an even alternating cycle (its extremities are in the same strongly connected component) or in an even alternating path starting from a free value.
+    /// Returns, for each variable, the indexes of the values that do not belong to any maximum matching.
+    fn inconsistent_values(&self) -> Vec<Vec<usize>> {
+        let n = self.adj.len();
+        let graph = self.oriented_graph();
+        let scc = strongly_connected_components(&graph);
+        let reach = self.reach_free_values(&graph);
+        self.adj
+            .iter()
+            .enumerate()
+            .map(|(x, adj)| {
+                adj.iter()
+                    .cloned()
+                    .filter(|&v| {
+                        self.var_match[x] != Some(v) && scc[x] != scc[n + v] && !reach[n + v]
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+}
+
+/// Tarjan's algorithm, `graph[v]` contains the successors of the node `v`.
+/// Returns the component's number of each node.
+pub(crate) fn strongly_connected_components(graph: &[Vec<usize>]) -> Vec<usize> {
+    let n = graph.len();
+    let mut index = vec![None; n];
+    let mut lowlink = vec![0; n];
+    let mut on_stack = vec![false; n];
+    let mut stack = vec![];
+    let mut component = vec![0; n];
+    let mut next_index = 0;
+    let mut num_components = 0;
+    for root in 0..n {
+        if index[root].is_some() {
+            continue;
+        }
+        index[root] = Some(next_index);
+        lowlink[root] = next_index;
+        next_index += 1;
+        stack.push(root);
+        on_stack[root] = true;
+        // Each call frame is a node and the position of the next successor to visit.
+        let mut calls = vec![(root, 0)];
+        while let Some(&(v, pos)) = calls.last() {
+            if pos < graph[v].len() {
+                calls.last_mut().unwrap().1 += 1;
+                let w = graph[v][pos];
+                match index[w] {
+                    None => {
+                        index[w] = Some(next_index);
+                        lowlink[w] = next_index;
+                        next_index += 1;
+                        stack.push(w);
+                        on_stack[w] = true;
+                        calls.push((w, 0));
+                    }
+                    Some(w_index) if on_stack[w] => lowlink[v] = min(lowlink[v], w_index),
+                    _ => (),
+                }
+            } else {
+                calls.pop();
+                if let Some(&(u, _)) = calls.last() {
+                    lowlink[u] = min(lowlink[u], lowlink[v]);
+                }
+                if Some(lowlink[v]) == index[v] {
+                    loop {
+                        let w = stack.pop().unwrap();
+                        on_stack[w] = false;
+                        component[w] = num_components;
+                        if w == v {
+                            break;
+                        }
+                    }
+                    num_components += 1;
+                }
+            }
+        }
+    }
+    component
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
+    use gcollections::VectorStack;
     use interval::interval::*;
+    use interval::interval_set::*;
     use propagation::events::FDEvent::*;
     use propagators::test::*;
-    use trilean::SKleene::*;
+    use search::branching::*;
+    use search::engine::all_solution::*;
+    use search::engine::one_solution::*;
+    use search::monitor::*;
+    use search::propagation::*;
+    use search::statistics::*;
+    use search::test::*;
+    use search::*;
+    use variable::ops::*;
+    use variable::VStoreSet;
 
     #[test]
     fn distinct_test() {
@@ -160,7 +686,7 @@ mod test {
             vec![(2, Assignment)],
             true,
         );
-        distinct_test_one(5, vec![zero, one, dom0_1], Unknown, False, vec![], false);
+        distinct_test_one(5, vec![zero, one, dom0_1], False, False, vec![], false);
         distinct_test_one(
             6,
             vec![zero, dom0_3, dom0_3],
@@ -172,6 +698,62 @@ mod test {
         distinct_test_one(7, vec![dom0_3], True, True, vec![], true);
     }
 
+    #[test]
+    fn hall_interval_test() {
+        let dom1_2 = (1, 2).to_interval();
+        let dom1_3 = (1, 3).to_interval();
+        let dom1_4 = (1, 4).to_interval();
+        let dom2_3 = (2, 3).to_interval();
+        let dom2_4 = (2, 4).to_interval();
+
+        // [1..2] is a Hall interval.
+        distinct_test_one(
+            1,
+            vec![dom1_2, dom1_2, dom1_3],
+            Unknown,
+            Unknown,
+            vec![(2, Assignment)],
+            true,
+        );
+        distinct_test_one(
+            2,
+            vec![dom1_2, dom1_2, dom1_4],
+            Unknown,
+            Unknown,
+            vec![(2, Bound)],
+            true,
+        );
+        // [1..3] is a Hall interval.
+        distinct_test_one(
+            3,
+            vec![dom1_3, dom1_2, dom2_3, dom1_4],
+            Unknown,
+            Unknown,
+            vec![(3, Assignment)],
+            true,
+        );
+        // Upper bounds are also pruned: [2..4] is a Hall interval.
+        distinct_test_one(
+            4,
+            vec![dom1_4, dom2_4, dom2_3, dom2_4],
+            Unknown,
+            Unknown,
+            vec![(0, Assignment)],
+            true,
+        );
+        // Holes cannot be created, [2..3] is a Hall interval inside [1..4].
+        distinct_test_one(
+            5,
+            vec![dom2_3, dom2_3, dom1_4],
+            Unknown,
+            Unknown,
+            vec![],
+            true,
+        );
+        // Pigeonhole: three variables for two values.
+        distinct_test_one(6, vec![dom1_2, dom1_2, dom1_2], False, False, vec![], false);
+    }
+
     fn distinct_test_one(
         test_num: u32,
         doms: Vec<Interval<i32>>,
@@ -190,4 +772,129 @@ mod test {
             propagate_success,
         );
     }
+
+    #[test]
+    fn domain_consistency_test() {
+        let d1 = vec![(1, 1)];
+        let d13 = vec![(1, 1), (3, 3)];
+        let d123 = vec![(1, 3)];
+        let d1234 = vec![(1, 4)];
+        let d12 = vec![(1, 2)];
+        let d234 = vec![(2, 4)];
+        let d34 = vec![(3, 4)];
+        let d15 = vec![(1, 1), (5, 5)];
+
+        // A hole is created: 1 and 3 are taken by the two first variables.
+        domain_test_one(
+            1,
+            vec![d13.clone(), d13.clone(), d123.clone()],
+            Some(vec![d13.clone(), d13.clone(), vec![(2, 2)]]),
+        );
+        domain_test_one(
+            2,
+            vec![d1.clone(), d123.clone(), d13.clone()],
+            Some(vec![d1.clone(), vec![(2, 2)], vec![(3, 3)]]),
+        );
+        // {1,2} is a Hall set, the values of the other variables are still free.
+        domain_test_one(
+            3,
+            vec![d12.clone(), d12.clone(), d1234.clone(), d234.clone()],
+            Some(vec![d12.clone(), d12.clone(), d34.clone(), d34.clone()]),
+        );
+        // Values outside of any matching but reaching a free value are kept.
+        domain_test_one(4, vec![d1234.clone()], Some(vec![d1234.clone()]));
+        domain_test_one(5, vec![d15.clone(), d15.clone(), d15.clone()], None);
+        // Sparse domains over a wide range.
+        let sparse = vec![(0, 0), (1_000_000_000, 1_000_000_000)];
+        domain_test_one(
+            6,
+            vec![
+                sparse.clone(),
+                sparse.clone(),
+                vec![(0, 0), (5, 6), (1_000_000_000, 1_000_000_000)],
+            ],
+            Some(vec![sparse.clone(), sparse.clone(), vec![(5, 6)]]),
+        );
+    }
+
+    fn domain_test_one(
+        test_num: u32,
+        doms: Vec<Vec<(i32, i32)>>,
+        expected: Option<Vec<Vec<(i32, i32)>>>,
+    ) {
+        let mut vstore = VStoreSet::empty();
+        let vars: Vec<Var<VStoreSet>> = doms
+            .into_iter()
+            .map(|d| Box::new(vstore.alloc(d.to_interval_set())) as Var<VStoreSet>)
+            .collect();
+        let mut distinct = Distinct::with_consistency(vars, DistinctConsistency::Domain);
+        let success = distinct.propagate(&mut vstore);
+        match expected {
+            None => assert!(!success, "Test {}: propagation should fail.", test_num),
+            Some(expected) => {
+                assert!(success, "Test {}: propagation should succeed.", test_num);
+                let doms: Vec<_> = vstore.iter().cloned().collect();
+                let expected: Vec<_> = expected.into_iter().map(|d| d.to_interval_set()).collect();
+                assert_eq!(doms, expected, "Test {}: wrong domains.", test_num);
+            }
+        }
+    }
+
+    #[test]
+    fn permutation_count_test() {
+        for &consistency in &[DistinctConsistency::Bounds, DistinctConsistency::Domain] {
+            let mut space = FDSpace::empty();
+            let vars: Vec<Var<VStore>> = (0..4)
+                .map(|_| Box::new(space.vstore.alloc((1, 4).to_interval_set())) as Var<VStore>)
+                .collect();
+            space
+                .cstore
+                .alloc(Box::new(Distinct::with_consistency(vars, consistency)));
+            let mut statistics = Statistics::new();
+            {
+                let mut search: AllSolution<
+                    Monitor<Statistics, OneSolution<_, VectorStack<_>, FDSpace>>,
+                > = AllSolution::new(Monitor::new(
+                    &mut statistics,
+                    OneSolution::new(Propagation::new(Brancher::new(
+                        FirstSmallestVar,
+                        MiddleVal,
+                        BinarySplit,
+                    ))),
+                ));
+                search.start(&space);
+                let (_, status) = search.enter(space);
+                assert_eq!(status, Status::EndOfSearch);
+            }
+            assert_eq!(statistics.num_solution, 24);
+        }
+    }
+
+    #[test]
+    fn nqueens_solution_test() {
+        for n in 4..12 {
+            let mut space = FDSpace::empty();
+            nqueens(n, &mut space);
+            let mut search: OneSolution<_, VectorStack<_>, FDSpace> = OneSolution::new(
+                Propagation::new(Brancher::new(FirstSmallestVar, MiddleVal, BinarySplit)),
+            );
+            search.start(&space);
+            let (frozen_space, status) = search.enter(space);
+            assert_eq!(status, Status::Satisfiable);
+            let space = frozen_space.unfreeze();
+            let queens: Vec<i32> = space.vstore.iter().map(|q| q.lower()).collect();
+            for i in 0..n {
+                for j in i + 1..n {
+                    let (qi, qj) = (queens[i], queens[j]);
+                    let dist = (j - i) as i32;
+                    assert!(qi != qj, "{}-queens: two queens share a column.", n);
+                    assert!(
+                        qi + dist != qj && qi - dist != qj,
+                        "{}-queens: two queens share a diagonal.",
+                        n
+                    );
+                }
+            }
+        }
+    }
 }
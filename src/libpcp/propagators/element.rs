@@ -0,0 +1,337 @@
+// Copyright 2018 Pierre Talbot (IRCAM)
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+
+//     http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! The `Element` constraint `x = array[index]`, the term `array[index]` is given by `term::Element` which can also be used in any other constraint.
+//! The index is filtered with domain consistency. For arrays of constants, `x` is also filtered with domain consistency, otherwise the holes between the elements are removed from `x`.
+
+use concept::*;
+use gcollections::*;
+use kernel::*;
+use logic::*;
+use model::*;
+use propagation::events::*;
+use propagation::*;
+use propagators::cmp::*;
+use term::ops::*;
+use term::*;
+use trilean::SKleene;
+use trilean::SKleene::*;
+
+#[derive(Debug)]
+pub struct XEqElement<VStore> {
+    x: Var<VStore>,
+    element: Element<VStore>,
+}
+
+impl<VStore> XEqElement<VStore> {
+    pub fn new(x: Var<VStore>, element: Element<VStore>) -> Self {
+        XEqElement { x, element }
+    }
+}
+
+impl<VStore> Clone for XEqElement<VStore>
+where
+    VStore: Collection,
+{
+    fn clone(&self) -> Self {
+        XEqElement::new(self.x.bclone(), self.element.clone())
+    }
+}
+
+impl<VStore> DisplayStateful<Model> for XEqElement<VStore> {
+    fn display(&self, model: &Model) {
+        self.x.display(model);
+        print!(" = ");
+        self.element.display(model);
+    }
+}
+
+impl<VStore, Domain, Bound> NotFormula<VStore> for XEqElement<VStore>
+where
+    VStore: VStoreConcept<Item = Domain> + 'static,
+    Domain: IntDomain<Item = Bound> + 'static,
+    Bound: IntBound + 'static,
+{
+    fn not(&self) -> Formula<VStore> {
+        Box::new(XNeqY::new(self.x.bclone(), Box::new(self.element.clone())))
+    }
+}
+
+impl<VStore, Domain, Bound> Subsumption<VStore> for XEqElement<VStore>
+where
+    VStore: Collection<Item = Domain>,
+    Domain: IntDomain<Item = Bound>,
+    Bound: IntBound,
+{
+    fn is_subsumed(&self, vstore: &VStore) -> SKleene {
+        // False: `x` does not overlap any element at a possible position.
+        // True: `x` and every element at a possible position are equal to the same value.
+        // Unknown: Everything else.
+        let x = self.x.read(vstore);
+        if self.element.compatible(vstore, &x).is_empty() {
+            return False;
+        }
+        let element = self.element.read(vstore);
+        if x.is_singleton()
+            && element.is_singleton()
+            && x.lower() == element.lower()
+            && self.element.is_index_valid(vstore)
+        {
+            True
+        } else {
+            Unknown
+        }
+    }
+}
+
+impl<VStore, Domain, Bound> Propagator<VStore> for XEqElement<VStore>
+where
+    VStore: Collection<Item = Domain>,
+    Domain: IntDomain<Item = Bound>,
+    Bound: IntBound,
+{
+    fn propagate(&mut self, vstore: &mut VStore) -> bool {
+        let x = self.x.read(vstore);
+        if !self.element.update(vstore, x.clone()) {
+            return false;
+        }
+        let mut hulls: Vec<_> = self
+            .element
+            .compatible(vstore, &x)
+            .into_iter()
+            .map(|d| (d.lower(), d.upper()))
+            .collect();
+        hulls.sort();
+        let (lb, ub) = match (hulls.first(), hulls.iter().map(|h| h.1.clone()).max()) {
+            (Some(first), Some(ub)) => (first.0.clone(), ub),
+            _ => return false,
+        };
+        let mut x = x.shrink_left(lb).shrink_right(ub);
+        // Remove the values between the elements.
+        let mut covered = hulls[0].1.clone();
+        for (lb, ub) in hulls.into_iter().skip(1) {
+            if lb > covered.clone() + Bound::one() {
+                x = x.difference(&Domain::new(
+                    covered.clone() + Bound::one(),
+                    lb - Bound::one(),
+                ));
+            }
+            if ub > covered {
+                covered = ub;
+            }
+        }
+        self.x.update(vstore, x)
+    }
+}
+
+impl<VStore> PropagatorDependencies<FDEvent> for XEqElement<VStore> {
+    fn dependencies(&self) -> Vec<(usize, FDEvent)> {
+        let mut deps = self.x.dependencies(FDEvent::Inner);
+        deps.extend(self.element.dependencies(FDEvent::Inner));
+        deps.sort();
+        deps.dedup();
+        deps
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use gcollections::ops::*;
+    use interval::interval_set::*;
+    use search::test::*;
+    use variable::ops::*;
+    use variable::VStoreSet;
+
+    type Dom = IntervalSet<i32>;
+
+    enum Array {
+        Constant(Vec<i32>),
+        Variable(Vec<Dom>),
+    }
+
+    fn x_eq_element_test_one(
+        test_num: u32,
+        x: Dom,
+        array: Array,
+        index: Dom,
+        before: SKleene,
+        after: SKleene,
+        expected: Option<(Dom, Vec<Dom>, Dom)>,
+    ) {
+        let mut vstore = VStoreSet::empty();
+        let x_var = Box::new(vstore.alloc(x)) as Var<VStoreSet>;
+        let index_var = Box::new(vstore.alloc(index)) as Var<VStoreSet>;
+        let element = match array {
+            Array::Constant(array) => Element::constant(array, index_var),
+            Array::Variable(array) => {
+                let array = array
+                    .into_iter()
+                    .map(|d| Box::new(vstore.alloc(d)) as Var<VStoreSet>)
+                    .collect();
+                Element::new(array, index_var)
+            }
+        };
+        let mut propagator = XEqElement::new(x_var, element);
+        assert_eq!(
+            propagator.is_subsumed(&vstore),
+            before,
+            "Test {}.",
+            test_num
+        );
+        let success = propagator.propagate(&mut vstore);
+        assert_eq!(success, expected.is_some(), "Test {}.", test_num);
+        assert_eq!(propagator.is_subsumed(&vstore), after, "Test {}.", test_num);
+        if let Some((x, array, index)) = expected {
+            assert_eq!(vstore[0], x, "Test {}: x.", test_num);
+            assert_eq!(vstore[1], index, "Test {}: index.", test_num);
+            let elements: Vec<_> = vstore.iter().skip(2).cloned().collect();
+            assert_eq!(elements, array, "Test {}: array.", test_num);
+        }
+    }
+
+    fn single(v: i32) -> Dom {
+        (v, v).to_interval_set()
+    }
+
+    fn dom(lb: i32, ub: i32) -> Dom {
+        (lb, ub).to_interval_set()
+    }
+
+    #[test]
+    fn x_eq_element_constant_test() {
+        let array = || Array::Constant(vec![3, 7, 3, 9]);
+        x_eq_element_test_one(
+            1,
+            dom(0, 20),
+            array(),
+            dom(-5, 10),
+            Unknown,
+            Unknown,
+            Some((
+                vec![(3, 3), (7, 7), (9, 9)].to_interval_set(),
+                vec![],
+                dom(0, 3),
+            )),
+        );
+        x_eq_element_test_one(
+            2,
+            single(3),
+            array(),
+            dom(0, 3),
+            Unknown,
+            True,
+            Some((single(3), vec![], vec![(0, 0), (2, 2)].to_interval_set())),
+        );
+        x_eq_element_test_one(3, dom(4, 6), array(), dom(0, 3), False, False, None);
+        x_eq_element_test_one(
+            4,
+            dom(0, 8),
+            array(),
+            dom(1, 3),
+            Unknown,
+            Unknown,
+            Some((vec![(3, 3), (7, 7)].to_interval_set(), vec![], dom(1, 2))),
+        );
+        // The index is outside of the array.
+        x_eq_element_test_one(5, dom(0, 20), array(), dom(4, 6), False, False, None);
+    }
+
+    #[test]
+    fn x_eq_element_variable_test() {
+        let doms = vec![dom(0, 2), dom(5, 6), dom(10, 12)];
+        let array = || Array::Variable(doms.clone());
+        x_eq_element_test_one(
+            1,
+            dom(1, 11),
+            array(),
+            dom(0, 2),
+            Unknown,
+            Unknown,
+            Some((
+                vec![(1, 2), (5, 6), (10, 11)].to_interval_set(),
+                doms.clone(),
+                dom(0, 2),
+            )),
+        );
+        x_eq_element_test_one(
+            2,
+            dom(1, 5),
+            array(),
+            dom(0, 2),
+            Unknown,
+            Unknown,
+            Some((
+                vec![(1, 2), (5, 5)].to_interval_set(),
+                doms.clone(),
+                dom(0, 1),
+            )),
+        );
+        // The element at the assigned index is updated.
+        x_eq_element_test_one(
+            3,
+            dom(4, 20),
+            array(),
+            single(1),
+            Unknown,
+            Unknown,
+            Some((
+                dom(5, 6),
+                vec![dom(0, 2), dom(5, 6), dom(10, 12)],
+                single(1),
+            )),
+        );
+        x_eq_element_test_one(
+            4,
+            dom(6, 20),
+            array(),
+            single(1),
+            Unknown,
+            True,
+            Some((
+                single(6),
+                vec![dom(0, 2), single(6), dom(10, 12)],
+                single(1),
+            )),
+        );
+        x_eq_element_test_one(5, dom(3, 4), array(), dom(0, 2), False, False, None);
+    }
+
+    fn count_element(index: (i32, i32), view: bool) -> usize {
+        let mut space = FDSpace::empty();
+        let x = Box::new(space.vstore.alloc((0, 3).to_interval_set())) as Var<VStore>;
+        let index = Box::new(space.vstore.alloc(index.to_interval_set())) as Var<VStore>;
+        let array: Vec<Var<VStore>> = (0..3)
+            .map(|_| Box::new(space.vstore.alloc((0, 3).to_interval_set())) as Var<VStore>)
+            .collect();
+        let element = Element::new(array, index);
+        if view {
+            space
+                .cstore
+                .alloc(Box::new(XEqY::new(x, Box::new(element))));
+        } else {
+            space.cstore.alloc(Box::new(XEqElement::new(x, element)));
+        }
+        count_assignments(space, 5)
+    }
+
+    #[test]
+    fn x_eq_element_solutions_test() {
+        // `x` is determined by the index (3 values) and the array (4^3 values).
+        assert_eq!(count_element((0, 2), false), 192);
+        assert_eq!(count_element((-2, 4), false), 192);
+        assert_eq!(count_element((0, 2), true), 192);
+        assert_eq!(count_element((-2, 4), true), 192);
+    }
+}
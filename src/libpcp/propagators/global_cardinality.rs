@@ -0,0 +1,822 @@
+// Copyright 2018 Pierre Talbot (IRCAM)
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+
+//     http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! The `GlobalCardinality` constraint (GCC) counts the occurrences of some values in an array of variables: the value `values[j]` is taken by exactly `cards[j]` variables, the values not in `values` are not constrained.
+//! The domain consistency is obtained with the flow algorithm described in `Generalized arc consistency for global cardinality constraint`, Régin, 1996: a value is kept in the domain of a variable if there is a feasible flow assigning this value to this variable.
+//! The bounds consistency runs the same flow algorithm on the interval hulls of the domains and keeps the smallest and largest consistent values, in O(n*m) for `n` variables and `m` values. It prunes as much as `An efficient bounds consistency algorithm for the global cardinality constraint`, Quimper and al., 2003, but is not their linear-time algorithm based on Hall intervals.
+//! The cardinalities can be variables, they are pruned to the number of variables assigned to their value and the number of variables that can take it.
+
+use concept::*;
+use gcollections::*;
+use kernel::*;
+use logic::*;
+use model::*;
+use propagation::events::*;
+use propagation::*;
+use propagators::distinct::strongly_connected_components;
+use propagators::values::{naturals, restrict};
+use std::cmp::{max, min};
+use std::fmt::Debug;
+use term::Constant;
+use trilean::SKleene;
+use trilean::SKleene::*;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CardinalityConsistency {
+    /// Only the bounds of the domains are pruned, with the flow algorithm on the interval hulls.
+    Bounds,
+    /// Every value that does not belong to a feasible flow is pruned (Régin).
+    Domain,
+}
+
+#[derive(Debug)]
+pub struct GlobalCardinality<VStore, Bound> {
+    vars: Vec<Var<VStore>>,
+    /// The counted values in increasing order.
+    values: Vec<Bound>,
+    /// `cards[j]` is the number of occurrences of `values[j]`.
+    cards: Vec<Var<VStore>>,
+    consistency: CardinalityConsistency,
+    /// The negation of the constraint: at least one value does not occur `cards[j]` times.
+    negated: bool,
+}
+
+impl<VStore, Bound> GlobalCardinality<VStore, Bound>
+where
+    Bound: IntBound,
+{
+    /// Precondition: `values` are distinct and `values.len() == cards.len()`.
+    pub fn new(vars: Vec<Var<VStore>>, values: Vec<Bound>, cards: Vec<Var<VStore>>) -> Self {
+        GlobalCardinality::with_consistency(vars, values, cards, CardinalityConsistency::Bounds)
+    }
+
+    /// Precondition: `values` are distinct and `values.len() == cards.len()`.
+    pub fn with_consistency(
+        vars: Vec<Var<VStore>>,
+        values: Vec<Bound>,
+        cards: Vec<Var<VStore>>,
+        consistency: CardinalityConsistency,
+    ) -> Self {
+        assert_eq!(
+            values.len(),
+            cards.len(),
+            "`GlobalCardinality` must have one cardinality per value."
+        );
+        let mut counted: Vec<_> = values.into_iter().zip(cards).collect();
+        counted.sort_by(|a, b| a.0.cmp(&b.0));
+        assert!(
+            counted.windows(2).all(|w| w[0].0 < w[1].0),
+            "The values of `GlobalCardinality` must be distinct."
+        );
+        let (values, cards) = counted.into_iter().unzip();
+        GlobalCardinality {
+            vars,
+            values,
+            cards,
+            consistency,
+            negated: false,
+        }
+    }
+}
+
+impl<VStore, Domain, Bound> GlobalCardinality<VStore, Bound>
+where
+    VStore: VStoreConcept<Item = Domain> + 'static,
+    Domain: IntDomain<Item = Bound> + 'static,
+    Bound: IntBound + 'static,
+{
+    /// The value `values[j]` is taken by exactly `counts[j]` variables.
+    pub fn fixed(vars: Vec<Var<VStore>>, values: Vec<Bound>, counts: Vec<Bound>) -> Self {
+        let cards = counts
+            .into_iter()
+            .map(|c| Box::new(Constant::new(c)) as Var<VStore>)
+            .collect();
+        GlobalCardinality::new(vars, values, cards)
+    }
+}
+
+impl<VStore, Bound> Clone for GlobalCardinality<VStore, Bound>
+where
+    VStore: Collection,
+    Bound: Clone,
+{
+    fn clone(&self) -> Self {
+        GlobalCardinality {
+            vars: self.vars.iter().map(|v| v.bclone()).collect(),
+            values: self.values.clone(),
+            cards: self.cards.iter().map(|v| v.bclone()).collect(),
+            consistency: self.consistency,
+            negated: self.negated,
+        }
+    }
+}
+
+impl<VStore, Bound> DisplayStateful<Model> for GlobalCardinality<VStore, Bound>
+where
+    Bound: Debug,
+{
+    fn display(&self, model: &Model) {
+        if self.negated {
+            print!("not ");
+        }
+        print!("global_cardinality(");
+        model.display_array(&self.vars);
+        print!(", {:?}, ", self.values);
+        model.display_array(&self.cards);
+        print!(")");
+    }
+}
+
+impl<VStore, Domain, Bound> NotFormula<VStore> for GlobalCardinality<VStore, Bound>
+where
+    VStore: VStoreConcept<Item = Domain> + 'static,
+    Domain: IntDomain<Item = Bound> + 'static,
+    Bound: IntBound + 'static,
+{
+    fn not(&self) -> Formula<VStore> {
+        let mut not = self.clone();
+        not.negated = !self.negated;
+        Box::new(not)
+    }
+}
+
+impl<VStore, Domain, Bound> GlobalCardinality<VStore, Bound>
+where
+    VStore: Collection<Item = Domain>,
+    Domain: IntDomain<Item = Bound>,
+    Bound: IntBound,
+{
+    fn read_all(&self, vstore: &VStore) -> Vec<Domain> {
+        self.vars.iter().map(|v| v.read(vstore)).collect()
+    }
+
+    /// For each value, the number of variables assigned to it and the number of variables that can take it.
+    fn occurrences(&self, doms: &[Domain]) -> Vec<(usize, usize)> {
+        self.values
+            .iter()
+            .map(|v| {
+                doms.iter()
+                    .filter(|d| d.contains(v))
+                    .fold((0, 0), |(assigned, possible), d| {
+                        (assigned + usize::from(d.is_singleton()), possible + 1)
+                    })
+            })
+            .collect()
+    }
+
+    /// `true` if the number of occurrences of `values[j]` is known and is the only value of `cards[j]`.
+    fn is_satisfied(
+        &self,
+        vstore: &VStore,
+        j: usize,
+        occurrences: (usize, usize),
+        naturals: &[Bound],
+    ) -> bool {
+        let card = self.cards[j].read(vstore);
+        let (assigned, possible) = occurrences;
+        assigned == possible && card.is_singleton() && card.lower() == naturals[assigned]
+    }
+
+    /// The minimal and maximal number of occurrences of each value, the last pair is for the values that are not counted.
+    /// Returns `None` if a cardinality does not contain any integer between `0` and `vars.len()`.
+    fn capacities(&self, vstore: &VStore, naturals: &[Bound]) -> Option<Vec<(usize, usize)>> {
+        let mut capacities = vec![];
+        for card in &self.cards {
+            let card = card.read(vstore);
+            let lower = naturals.iter().position(|k| *k >= card.lower());
+            let upper = naturals.iter().rposition(|k| *k <= card.upper());
+            match (lower, upper) {
+                (Some(l), Some(u)) if l <= u => capacities.push((l, u)),
+                _ => return None,
+            }
+        }
+        capacities.push((0, self.vars.len()));
+        Some(capacities)
+    }
+
+    /// For each variable, `j` is an edge if `values[j]` is in its domain and `values.len()` is an edge if its domain contains a value that is not counted.
+    fn edges(&self, doms: &[Domain]) -> Vec<Vec<usize>> {
+        doms.iter()
+            .map(|dom| {
+                let mut rest = dom.clone();
+                let mut edges = vec![];
+                for (j, v) in self.values.iter().enumerate() {
+                    if dom.contains(v) {
+                        edges.push(j);
+                        rest = rest.difference(v);
+                    }
+                }
+                if !rest.is_empty() {
+                    edges.push(self.values.len());
+                }
+                edges
+            })
+            .collect()
+    }
+
+    fn feasible_flow(&self, vstore: &VStore, doms: &[Domain]) -> Option<CardinalityFlow> {
+        let naturals = naturals(self.vars.len());
+        let capacities = self.capacities(vstore, &naturals)?;
+        let mut flow = CardinalityFlow::new(self.edges(doms), capacities);
+        if flow.find() {
+            Some(flow)
+        } else {
+            None
+        }
+    }
+
+    /// Removes the values of `edges` that are not `consistent`.
+    fn prune_domain(&self, mut dom: Domain, edges: &[usize], consistent: &[usize]) -> Domain {
+        let m = self.values.len();
+        if consistent.last() != Some(&m) {
+            let values: Vec<_> = consistent.iter().map(|&j| self.values[j].clone()).collect();
+            restrict(dom, &values)
+        } else {
+            for &j in edges {
+                if j < m && !consistent.contains(&j) {
+                    dom = dom.difference(&self.values[j]);
+                }
+            }
+            dom
+        }
+    }
+
+    /// Shrinks `dom` to the smallest and largest `consistent` values of `hull`.
+    fn prune_bounds(&self, dom: Domain, hull: &Domain, consistent: &[usize]) -> Domain {
+        let m = self.values.len();
+        let counted: Vec<_> = consistent.iter().filter(|&&j| j < m).collect();
+        let (mut lb, mut ub) = match (counted.first(), counted.last()) {
+            (Some(&&l), Some(&&u)) => (self.values[l].clone(), self.values[u].clone()),
+            _ => (hull.upper(), hull.lower()),
+        };
+        if consistent.last() == Some(&m) {
+            let mut l = hull.lower();
+            while self.values.binary_search(&l).is_ok() {
+                l = l + Bound::one();
+            }
+            let mut u = hull.upper();
+            while self.values.binary_search(&u).is_ok() {
+                u = u - Bound::one();
+            }
+            lb = min(lb, l);
+            ub = max(ub, u);
+        }
+        dom.shrink_left(lb).shrink_right(ub)
+    }
+
+    fn propagate_vars(&mut self, vstore: &mut VStore) -> bool {
+        let doms = self.read_all(vstore);
+        let graph_doms: Vec<_> = match self.consistency {
+            CardinalityConsistency::Bounds => doms
+                .iter()
+                .map(|d| Domain::new(d.lower(), d.upper()))
+                .collect(),
+            CardinalityConsistency::Domain => doms.clone(),
+        };
+        let flow = match self.feasible_flow(vstore, &graph_doms) {
+            Some(flow) => flow,
+            None => return false,
+        };
+        let consistent = flow.consistent_edges();
+        for (x, dom) in doms.into_iter().enumerate() {
+            let dom = match self.consistency {
+                CardinalityConsistency::Bounds => {
+                    self.prune_bounds(dom, &graph_doms[x], &consistent[x])
+                }
+                CardinalityConsistency::Domain => {
+                    self.prune_domain(dom, &flow.adj[x], &consistent[x])
+                }
+            };
+            if !self.vars[x].update(vstore, dom) {
+                return false;
+            }
+        }
+        true
+    }
+
+    fn propagate_cards(&mut self, vstore: &mut VStore) -> bool {
+        let naturals: Vec<Bound> = naturals(self.vars.len());
+        let occurrences = self.occurrences(&self.read_all(vstore));
+        for (card, (assigned, possible)) in self.cards.iter_mut().zip(occurrences) {
+            let dom = card
+                .read(vstore)
+                .shrink_left(naturals[assigned].clone())
+                .shrink_right(naturals[possible].clone());
+            if !card.update(vstore, dom) {
+                return false;
+            }
+        }
+        true
+    }
+
+    /// The negation is only propagated when every value but one satisfies its cardinality and the number of occurrences of this last value is known.
+    fn propagate_negated(&mut self, vstore: &mut VStore) -> bool {
+        let naturals = naturals(self.vars.len());
+        let occurrences = self.occurrences(&self.read_all(vstore));
+        let violable: Vec<_> = (0..self.values.len())
+            .filter(|&j| !self.is_satisfied(vstore, j, occurrences[j], &naturals))
+            .collect();
+        match violable.len() {
+            0 => false,
+            1 => {
+                let j = violable[0];
+                let (assigned, possible) = occurrences[j];
+                if assigned == possible {
+                    let card = self.cards[j].read(vstore).difference(&naturals[assigned]);
+                    self.cards[j].update(vstore, card)
+                } else {
+                    true
+                }
+            }
+            _ => true,
+        }
+    }
+}
+
+impl<VStore, Domain, Bound> Subsumption<VStore> for GlobalCardinality<VStore, Bound>
+where
+    VStore: Collection<Item = Domain>,
+    Domain: IntDomain<Item = Bound>,
+    Bound: IntBound,
+{
+    fn is_subsumed(&self, vstore: &VStore) -> SKleene {
+        let doms = self.read_all(vstore);
+        let naturals = naturals(self.vars.len());
+        let occurrences = self.occurrences(&doms);
+        let satisfied =
+            (0..self.values.len()).all(|j| self.is_satisfied(vstore, j, occurrences[j], &naturals));
+        if self.negated {
+            // False: every value occurs as many times as its cardinality.
+            // True: the possible numbers of occurrences of a value are not in its cardinality.
+            // Unknown: Everything else.
+            let violated =
+                occurrences
+                    .iter()
+                    .zip(self.cards.iter())
+                    .any(|(&(assigned, possible), card)| {
+                        let counts =
+                            Domain::new(naturals[assigned].clone(), naturals[possible].clone());
+                        !card.read(vstore).overlap(&counts)
+                    });
+            if satisfied {
+                False
+            } else if violated {
+                True
+            } else {
+                Unknown
+            }
+        } else {
+            // False: there is no feasible flow, the values cannot occur as many times as required.
+            // True: every value occurs as many times as its cardinality.
+            // Unknown: Everything else.
+            if satisfied {
+                True
+            } else if self.feasible_flow(vstore, &doms).is_none() {
+                False
+            } else {
+                Unknown
+            }
+        }
+    }
+}
+
+impl<VStore, Domain, Bound> Propagator<VStore> for GlobalCardinality<VStore, Bound>
+where
+    VStore: Collection<Item = Domain>,
+    Domain: IntDomain<Item = Bound>,
+    Bound: IntBound,
+{
+    fn propagate(&mut self, vstore: &mut VStore) -> bool {
+        if self.negated {
+            self.propagate_negated(vstore)
+        } else {
+            self.propagate_vars(vstore) && self.propagate_cards(vstore)
+        }
+    }
+
+    fn cost(&self) -> PropagatorCost {
+        PropagatorCost::Quadratic
+    }
+}
+
+impl<VStore, Bound> PropagatorDependencies<FDEvent> for GlobalCardinality<VStore, Bound> {
+    fn dependencies(&self) -> Vec<(usize, FDEvent)> {
+        let event = match self.consistency {
+            CardinalityConsistency::Bounds if !self.negated => FDEvent::Bound,
+            _ => FDEvent::Inner,
+        };
+        let mut deps: Vec<_> = self
+            .vars
+            .iter()
+            .flat_map(|v| v.dependencies(event))
+            .chain(
+                self.cards
+                    .iter()
+                    .flat_map(|c| c.dependencies(FDEvent::Bound)),
+            )
+            .collect();
+        deps.sort();
+        deps.dedup();
+        deps
+    }
+}
+
+/// Flow in the bipartite graph between the variables and the values: each variable is matched to one value and each value `j` is matched to between `capacities[j].0` and `capacities[j].1` variables.
+struct CardinalityFlow {
+    /// `adj[x]` contains the values in the domain of `x` in increasing order.
+    adj: Vec<Vec<usize>>,
+    /// `vars_of[j]` contains the variables with `j` in their domain.
+    vars_of: Vec<Vec<usize>>,
+    capacities: Vec<(usize, usize)>,
+    flow: Vec<usize>,
+    var_match: Vec<Option<usize>>,
+}
+
+impl CardinalityFlow {
+    fn new(adj: Vec<Vec<usize>>, capacities: Vec<(usize, usize)>) -> Self {
+        let mut vars_of = vec![vec![]; capacities.len()];
+        for (x, values) in adj.iter().enumerate() {
+            for &j in values {
+                vars_of[j].push(x);
+            }
+        }
+        CardinalityFlow {
+            var_match: vec![None; adj.len()],
+            flow: vec![0; capacities.len()],
+            adj,
+            vars_of,
+            capacities,
+        }
+    }
+
+    fn assign(&mut self, x: usize, j: usize) {
+        if let Some(k) = self.var_match[x] {
+            self.flow[k] -= 1;
+        }
+        self.var_match[x] = Some(j);
+        self.flow[j] += 1;
+    }
+
+    /// The minimal capacities are reached first, the remaining variables are then matched without exceeding the maximal capacities.
+    /// Returns `true` if every variable is matched and every minimal capacity is reached.
+    fn find(&mut self) -> bool {
+        let required: usize = self.capacities.iter().map(|c| c.0).sum();
+        if required > self.adj.len() {
+            return false;
+        }
+        for j in 0..self.capacities.len() {
+            while self.flow[j] < self.capacities[j].0 {
+                let mut visited = vec![false; self.adj.len()];
+                if !self.augment_value(j, &mut visited) {
+                    return false;
+                }
+            }
+        }
+        for x in 0..self.adj.len() {
+            if self.var_match[x].is_none() {
+                let mut visited = vec![false; self.capacities.len()];
+                if !self.augment_var(x, &mut visited) {
+                    return false;
+                }
+            }
+        }
+        true
+    }
+
+    /// Search for an alternating path from the value `j` to an unmatched variable.
+    fn augment_value(&mut self, j: usize, visited: &mut [bool]) -> bool {
+        for i in 0..self.vars_of[j].len() {
+            let x = self.vars_of[j][i];
+            if !visited[x] && self.var_match[x] != Some(j) {
+                visited[x] = true;
+                let free = match self.var_match[x] {
+                    None => true,
+                    Some(k) => self.augment_value(k, visited),
+                };
+                if free {
+                    self.assign(x, j);
+                    return true;
+                }
+            }
+        }
+        false
+    }
+
+    /// Search for an alternating path from the variable `x` to a value under its maximal capacity.
+    fn augment_var(&mut self, x: usize, visited: &mut [bool]) -> bool {
+        for i in 0..self.adj[x].len() {
+            let j = self.adj[x][i];
+            if visited[j] || self.var_match[x] == Some(j) {
+                continue;
+            }
+            visited[j] = true;
+            if self.flow[j] < self.capacities[j].1 {
+                self.assign(x, j);
+                return true;
+            }
+            let matched: Vec<_> = self.vars_of[j]
+                .iter()
+                .cloned()
+                .filter(|&y| self.var_match[y] == Some(j))
+                .collect();
+            for y in matched {
+                if self.augment_var(y, visited) {
+                    self.assign(x, j);
+                    return true;
+                }
+            }
+        }
+        false
+    }
+
+    /// Precondition: `find()` succeeded.
+    /// The variables are the nodes `0..n`, the values the nodes `n..n+m` and the node `n+m` is the sink of the flow in the residual graph.
+    /// An edge belongs to a feasible flow if it is in the current flow or if its extremities are in the same strongly connected component.
+    /// Returns, for each variable, the values belonging to a feasible flow.
+    fn consistent_edges(&self) -> Vec<Vec<usize>> {
+        let n = self.adj.len();
+        let m = self.capacities.len();
+        let sink = n + m;
+        let mut graph = vec![vec![]; n + m + 1];
+        for (x, adj) in self.adj.iter().enumerate() {
+            for &j in adj {
+                if self.var_match[x] == Some(j) {
+                    graph[n + j].push(x);
+                } else {
+                    graph[x].push(n + j);
+                }
+            }
+        }
+        for (j, &(lower, upper)) in self.capacities.iter().enumerate() {
+            if self.flow[j] < upper {
+                graph[n + j].push(sink);
+            }
+            if self.flow[j] > lower {
+                graph[sink].push(n + j);
+            }
+        }
+        let scc = strongly_connected_components(&graph);
+        self.adj
+            .iter()
+            .enumerate()
+            .map(|(x, adj)| {
+                adj.iter()
+                    .cloned()
+                    .filter(|&j| self.var_match[x] == Some(j) || scc[x] == scc[n + j])
+                    .collect()
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use gcollections::ops::*;
+    use interval::interval_set::*;
+    use search::test::*;
+    use variable::ops::*;
+    use variable::VStoreSet;
+
+    type Doms = Vec<Vec<(i32, i32)>>;
+
+    fn gcc_test_one(
+        test_num: u32,
+        consistency: CardinalityConsistency,
+        (doms, values, cards): (Doms, Vec<i32>, Vec<(i32, i32)>),
+        expected: Option<(Doms, Vec<(i32, i32)>)>,
+    ) {
+        let mut vstore = VStoreSet::empty();
+        let vars: Vec<_> = doms
+            .into_iter()
+            .map(|d| Box::new(vstore.alloc(d.to_interval_set())) as Var<VStoreSet>)
+            .collect();
+        let cards: Vec<_> = cards
+            .into_iter()
+            .map(|c| Box::new(vstore.alloc(c.to_interval_set())) as Var<VStoreSet>)
+            .collect();
+        let mut gcc = GlobalCardinality::with_consistency(vars, values, cards, consistency);
+        let success = gcc.propagate(&mut vstore);
+        match expected {
+            None => assert!(!success, "Test {}: propagation should fail.", test_num),
+            Some((doms, cards)) => {
+                assert!(success, "Test {}: propagation should succeed.", test_num);
+                let expected: Vec<_> = doms
+                    .into_iter()
+                    .map(|d| d.to_interval_set())
+                    .chain(cards.into_iter().map(|c| c.to_interval_set()))
+                    .collect();
+                let doms: Vec<_> = vstore.iter().cloned().collect();
+                assert_eq!(doms, expected, "Test {}: wrong domains.", test_num);
+            }
+        }
+    }
+
+    #[test]
+    fn global_cardinality_test() {
+        use self::CardinalityConsistency::*;
+        let d1 = vec![(1, 1)];
+        let d2 = vec![(2, 2)];
+        let d3 = vec![(3, 3)];
+        let d12 = vec![(1, 2)];
+        let d13 = vec![(1, 1), (3, 3)];
+        let d123 = vec![(1, 3)];
+        let d15 = vec![(1, 1), (5, 5)];
+
+        for &consistency in &[Bounds, Domain] {
+            // Two 1 and one 2, the value 3 cannot be taken.
+            gcc_test_one(
+                1,
+                consistency,
+                (vec![d123.clone(); 3], vec![1, 2], vec![(2, 2), (1, 1)]),
+                Some((vec![d12.clone(); 3], vec![(2, 2), (1, 1)])),
+            );
+            // Too many occurrences of 1.
+            gcc_test_one(
+                2,
+                consistency,
+                (
+                    vec![d1.clone(), d1.clone(), d12.clone()],
+                    vec![1],
+                    vec![(0, 1)],
+                ),
+                None,
+            );
+            // The cardinalities are pruned to the number of assigned and possible variables.
+            gcc_test_one(
+                3,
+                consistency,
+                (
+                    vec![d1.clone(), d12.clone(), vec![(2, 3)]],
+                    vec![3, 1, 2],
+                    vec![(0, 3), (0, 3), (0, 3)],
+                ),
+                Some((
+                    vec![d1.clone(), d12.clone(), vec![(2, 3)]],
+                    vec![(0, 1), (1, 2), (0, 2)],
+                )),
+            );
+            // The values 1 and 2 must occur once and can only be taken by the two first variables.
+            gcc_test_one(
+                4,
+                consistency,
+                (
+                    vec![d123.clone(), d123.clone(), d3.clone()],
+                    vec![1, 2],
+                    vec![(1, 1), (1, 1)],
+                ),
+                Some((
+                    vec![d12.clone(), d12.clone(), d3.clone()],
+                    vec![(1, 1), (1, 1)],
+                )),
+            );
+            // The values that are not counted are removed when they cannot be taken.
+            gcc_test_one(
+                5,
+                consistency,
+                (vec![d15.clone(), d15.clone()], vec![1], vec![(2, 2)]),
+                Some((vec![d1.clone(), d1.clone()], vec![(2, 2)])),
+            );
+            // The values 1 and 2 occur at most once and are taken by the two first variables.
+            gcc_test_one(
+                6,
+                consistency,
+                (
+                    vec![d12.clone(), d12.clone(), d123.clone()],
+                    vec![1, 2],
+                    vec![(0, 1), (0, 1)],
+                ),
+                Some((
+                    vec![d12.clone(), d12.clone(), d3.clone()],
+                    vec![(0, 1), (0, 1)],
+                )),
+            );
+        }
+        // Only the domain consistency creates holes.
+        let doms = vec![d123.clone(), d2.clone()];
+        gcc_test_one(
+            7,
+            Domain,
+            (doms.clone(), vec![2], vec![(1, 1)]),
+            Some((vec![d13.clone(), d2.clone()], vec![(1, 1)])),
+        );
+        gcc_test_one(
+            8,
+            Bounds,
+            (doms.clone(), vec![2], vec![(1, 1)]),
+            Some((doms, vec![(1, 1)])),
+        );
+    }
+
+    /// `not(global_cardinality([1, x], [2], [card]))`
+    fn negated_gcc(
+        x: (i32, i32),
+        card: (i32, i32),
+    ) -> (VStoreSet, Formula<VStoreSet>, Var<VStoreSet>) {
+        let mut vstore = VStoreSet::empty();
+        let vars: Vec<_> = vec![(1, 1), x]
+            .into_iter()
+            .map(|d| Box::new(vstore.alloc(d.to_interval_set())) as Var<VStoreSet>)
+            .collect();
+        let x = vars[1].bclone();
+        let card = Box::new(vstore.alloc(card.to_interval_set())) as Var<VStoreSet>;
+        let gcc = GlobalCardinality::new(vars, vec![2], vec![card]);
+        (vstore, gcc.not(), x)
+    }
+
+    #[test]
+    fn negated_global_cardinality_test() {
+        let (mut vstore, mut not_gcc, mut x) = negated_gcc((1, 2), (0, 2));
+        assert_eq!(not_gcc.is_subsumed(&vstore), Unknown);
+        assert!(not_gcc.propagate(&mut vstore));
+        assert_eq!(vstore[2], (0, 2).to_interval_set());
+        // The value 2 occurs once.
+        assert!(x.update(&mut vstore, (2, 2).to_interval_set()));
+        assert!(not_gcc.propagate(&mut vstore));
+        assert_eq!(vstore[2], vec![(0, 0), (2, 2)].to_interval_set());
+        assert_eq!(not_gcc.is_subsumed(&vstore), True);
+
+        let (mut vstore, mut not_gcc, _) = negated_gcc((2, 2), (1, 1));
+        assert_eq!(not_gcc.is_subsumed(&vstore), False);
+        assert!(!not_gcc.propagate(&mut vstore));
+    }
+
+    /// Number of assignments of 4 variables in `[0..3]` such that the number of occurrences of `1` and `2` satisfy `accept`.
+    fn brute_force<F>(accept: F) -> usize
+    where
+        F: Fn(usize, usize) -> bool,
+    {
+        (0..256)
+            .filter(|a| {
+                let digits: Vec<_> = (0..4).map(|i| (a >> (2 * i)) & 3).collect();
+                let ones = digits.iter().filter(|&&d| d == 1).count();
+                let twos = digits.iter().filter(|&&d| d == 2).count();
+                accept(ones, twos)
+            })
+            .count()
+    }
+
+    fn count_gcc(
+        consistency: CardinalityConsistency,
+        cards: Option<Vec<(i32, i32)>>,
+        negated: bool,
+    ) -> usize {
+        let mut space = FDSpace::empty();
+        let vars: Vec<_> = (0..4)
+            .map(|_| Box::new(space.vstore.alloc((0, 3).to_interval_set())) as Var<VStore>)
+            .collect();
+        let gcc = match cards {
+            Some(cards) => {
+                let cards = cards
+                    .into_iter()
+                    .map(|c| Box::new(space.vstore.alloc(c.to_interval_set())) as Var<VStore>)
+                    .collect();
+                GlobalCardinality::with_consistency(vars, vec![2, 1], cards, consistency)
+            }
+            None => {
+                let mut gcc = GlobalCardinality::fixed(vars, vec![1, 2], vec![2, 1]);
+                gcc.consistency = consistency;
+                gcc
+            }
+        };
+        if negated {
+            space.cstore.alloc(gcc.not());
+        } else {
+            space.cstore.alloc(Box::new(gcc));
+        }
+        count_assignments(space, 4)
+    }
+
+    #[test]
+    fn global_cardinality_solutions_test() {
+        use self::CardinalityConsistency::*;
+        let fixed = brute_force(|ones, twos| ones == 2 && twos == 1);
+        let at_most = brute_force(|ones, twos| ones <= 1 && twos <= 2);
+        let between = brute_force(|ones, twos| ones >= 1 && twos == 1);
+        assert_eq!(fixed, 24);
+        for &consistency in &[Bounds, Domain] {
+            assert_eq!(count_gcc(consistency, None, false), fixed);
+            assert_eq!(count_gcc(consistency, None, true), 256 - fixed);
+            // The cardinalities are given for the values `[2, 1]`.
+            assert_eq!(
+                count_gcc(consistency, Some(vec![(0, 2), (0, 1)]), false),
+                at_most
+            );
+            assert_eq!(
+                count_gcc(consistency, Some(vec![(1, 1), (1, 4)]), false),
+                between
+            );
+        }
+    }
+}
@@ -0,0 +1,440 @@
+// Copyright 2018 Pierre Talbot (IRCAM)
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+
+//     http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! The `Inverse` constraint channels two arrays of variables `x` and `y` of the same length such that `x[i] = j <=> y[j] = i`, the indices are numbered from 0.
+//! Both arrays are then permutations of `0..n` and one is the inverse of the other.
+//! The filtering is domain consistent: a value `j` is removed from `x[i]` as soon as `i` is not in the domain of `y[j]`, and conversely.
+//! Since the channeling alone does not detect that two variables of `x` share a value, it is combined with a domain consistent `Distinct` on each array until a fixpoint is reached.
+
+use concept::*;
+use gcollections::ops::Cardinality;
+use gcollections::*;
+use kernel::*;
+use logic::*;
+use model::*;
+use propagation::events::*;
+use propagation::*;
+use propagators::distinct::*;
+use propagators::values::naturals;
+use trilean::SKleene;
+use trilean::SKleene::*;
+
+#[derive(Debug)]
+pub struct Inverse<VStore> {
+    x: Vec<Var<VStore>>,
+    y: Vec<Var<VStore>>,
+    distinct_x: Distinct<VStore>,
+    distinct_y: Distinct<VStore>,
+    negated: bool,
+}
+
+impl<VStore> Inverse<VStore>
+where
+    VStore: Collection,
+{
+    /// Precondition: `x.len() == y.len()` and `x.len() > 0`.
+    pub fn new(x: Vec<Var<VStore>>, y: Vec<Var<VStore>>) -> Self {
+        assert_eq!(
+            x.len(),
+            y.len(),
+            "The variable arrays in `Inverse` must have the same length."
+        );
+        assert!(
+            !x.is_empty(),
+            "The variable arrays in `Inverse` must be non-empty."
+        );
+        let distinct = |vars: &Vec<Var<VStore>>| {
+            Distinct::with_consistency(
+                vars.iter().map(|v| v.bclone()).collect(),
+                DistinctConsistency::Domain,
+            )
+        };
+        Inverse {
+            distinct_x: distinct(&x),
+            distinct_y: distinct(&y),
+            x,
+            y,
+            negated: false,
+        }
+    }
+}
+
+impl<VStore> Clone for Inverse<VStore>
+where
+    VStore: Collection,
+{
+    fn clone(&self) -> Self {
+        Inverse {
+            x: self.x.iter().map(|v| v.bclone()).collect(),
+            y: self.y.iter().map(|v| v.bclone()).collect(),
+            distinct_x: self.distinct_x.clone(),
+            distinct_y: self.distinct_y.clone(),
+            negated: self.negated,
+        }
+    }
+}
+
+impl<VStore> DisplayStateful<Model> for Inverse<VStore> {
+    fn display(&self, model: &Model) {
+        if self.negated {
+            print!("not ");
+        }
+        print!("inverse(");
+        model.display_array(&self.x);
+        print!(", ");
+        model.display_array(&self.y);
+        print!(")");
+    }
+}
+
+impl<VStore, Domain, Bound> NotFormula<VStore> for Inverse<VStore>
+where
+    VStore: VStoreConcept<Item = Domain> + 'static,
+    Domain: IntDomain<Item = Bound> + 'static,
+    Bound: IntBound + 'static,
+{
+    fn not(&self) -> Formula<VStore> {
+        let mut not = self.clone();
+        not.negated = !self.negated;
+        Box::new(not)
+    }
+}
+
+/// Removes from `x[i]` the values outside of `0..n` and the values `j` such that `i` is not in `y[j]`.
+fn channel<VStore, Domain, Bound>(
+    vstore: &mut VStore,
+    x: &mut [Var<VStore>],
+    y: &[Var<VStore>],
+    indices: &[Bound],
+) -> bool
+where
+    VStore: Collection<Item = Domain>,
+    Domain: IntDomain<Item = Bound>,
+    Bound: IntBound,
+{
+    let range = Domain::new(Bound::zero(), indices[indices.len() - 1].clone());
+    let ys: Vec<_> = y.iter().map(|v| v.read(vstore)).collect();
+    for (i, var) in x.iter_mut().enumerate() {
+        let mut dom = var.read(vstore).intersection(&range);
+        for (j, y) in ys.iter().enumerate() {
+            if dom.contains(&indices[j]) && !y.contains(&indices[i]) {
+                dom = dom.difference(&indices[j]);
+            }
+        }
+        if !var.update(vstore, dom) {
+            return false;
+        }
+    }
+    true
+}
+
+impl<VStore, Domain, Bound> Inverse<VStore>
+where
+    VStore: Collection<Item = Domain>,
+    Domain: IntDomain<Item = Bound>,
+    Bound: IntBound,
+{
+    /// The indices `0..n`.
+    fn indices(&self) -> Vec<Bound> {
+        let mut indices = naturals(self.x.len());
+        indices.pop();
+        indices
+    }
+
+    fn sizes(&self, vstore: &VStore) -> Vec<<Domain as Cardinality>::Size> {
+        self.x
+            .iter()
+            .chain(self.y.iter())
+            .map(|v| v.read(vstore).size())
+            .collect()
+    }
+
+    /// `true` if `x[i] = j` but `i` is not in `y[j]`.
+    fn inconsistent(x: &[Domain], y: &[Domain], indices: &[Bound]) -> bool {
+        x.iter().enumerate().any(|(i, dom)| {
+            dom.is_singleton()
+                && match indices.binary_search(&dom.lower()) {
+                    Ok(j) => !y[j].contains(&indices[i]),
+                    Err(_) => true,
+                }
+        })
+    }
+}
+
+impl<VStore, Domain, Bound> Subsumption<VStore> for Inverse<VStore>
+where
+    VStore: Collection<Item = Domain>,
+    Domain: IntDomain<Item = Bound>,
+    Bound: IntBound,
+{
+    fn is_subsumed(&self, vstore: &VStore) -> SKleene {
+        // False: an assigned variable is not channeled by the other array.
+        // True: `x` is assigned and `y[x[i]] = i` for every `i`, `y` is then assigned as well.
+        // Unknown: Everything else.
+        let indices = self.indices();
+        let x: Vec<_> = self.x.iter().map(|v| v.read(vstore)).collect();
+        let y: Vec<_> = self.y.iter().map(|v| v.read(vstore)).collect();
+        let status = if Self::inconsistent(&x, &y, &indices) || Self::inconsistent(&y, &x, &indices)
+        {
+            False
+        } else if x.iter().all(|dom| {
+            dom.is_singleton()
+                && indices
+                    .binary_search(&dom.lower())
+                    .is_ok_and(|j| y[j].is_singleton())
+        }) {
+            True
+        } else {
+            Unknown
+        };
+        if self.negated {
+            !status
+        } else {
+            status
+        }
+    }
+}
+
+impl<VStore, Domain, Bound> Propagator<VStore> for Inverse<VStore>
+where
+    VStore: Collection<Item = Domain>,
+    Domain: IntDomain<Item = Bound>,
+    Bound: IntBound,
+{
+    fn propagate(&mut self, vstore: &mut VStore) -> bool {
+        if self.negated {
+            return self.is_subsumed(vstore) != False;
+        }
+        let indices = self.indices();
+        let mut sizes = self.sizes(vstore);
+        loop {
+            if !(channel(vstore, &mut self.x, &self.y, &indices)
+                && channel(vstore, &mut self.y, &self.x, &indices)
+                && self.distinct_x.propagate(vstore)
+                && self.distinct_y.propagate(vstore))
+            {
+                return false;
+            }
+            let new_sizes = self.sizes(vstore);
+            if new_sizes == sizes {
+                return true;
+            }
+            sizes = new_sizes;
+        }
+    }
+
+    fn cost(&self) -> PropagatorCost {
+        PropagatorCost::Quadratic
+    }
+}
+
+impl<VStore> PropagatorDependencies<FDEvent> for Inverse<VStore> {
+    fn dependencies(&self) -> Vec<(usize, FDEvent)> {
+        let event = if self.negated {
+            FDEvent::Assignment
+        } else {
+            FDEvent::Inner
+        };
+        self.x
+            .iter()
+            .chain(self.y.iter())
+            .flat_map(|v| v.dependencies(event))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use gcollections::ops::*;
+    use interval::interval_set::*;
+    use search::test::*;
+    use variable::ops::*;
+    use variable::VStoreSet;
+
+    type Doms = Vec<Vec<(i32, i32)>>;
+
+    fn inverse_space(x: Doms, y: Doms) -> FDSpace {
+        let mut space = FDSpace::empty();
+        let mut alloc = |doms: Doms| -> Vec<Var<VStore>> {
+            doms.into_iter()
+                .map(|d| Box::new(space.vstore.alloc(d.to_interval_set())) as Var<VStore>)
+                .collect()
+        };
+        let x = alloc(x);
+        let y = alloc(y);
+        space.cstore.alloc(Box::new(Inverse::new(x, y)));
+        space
+    }
+
+    fn inverse_test_one(
+        test_num: u32,
+        (x, y): (Doms, Doms),
+        expected_status: SKleene,
+        expected: Doms,
+    ) {
+        let mut space = inverse_space(x, y);
+        let status = space.cstore.consistency(&mut space.vstore);
+        assert_eq!(status, expected_status, "Test {}: wrong status.", test_num);
+        if status != False {
+            let doms: Vec<_> = space.vstore.iter().cloned().collect();
+            let expected: Vec<_> = expected.into_iter().map(|d| d.to_interval_set()).collect();
+            assert_eq!(doms, expected, "Test {}: wrong domains.", test_num);
+        }
+    }
+
+    #[test]
+    fn inverse_test() {
+        let d0 = vec![(0, 0)];
+        let d1 = vec![(1, 1)];
+        let d2 = vec![(2, 2)];
+        let d02 = vec![(0, 0), (2, 2)];
+        let d0_2 = vec![(0, 2)];
+        let d1_2 = vec![(1, 2)];
+
+        inverse_test_one(
+            1,
+            (vec![d0_2.clone(); 3], vec![d0_2.clone(); 3]),
+            Unknown,
+            vec![d0_2.clone(); 6],
+        );
+        // The values outside of `0..3` are removed.
+        inverse_test_one(
+            2,
+            (vec![vec![(-1, 5)]; 3], vec![d0_2.clone(); 3]),
+            Unknown,
+            vec![d0_2.clone(); 6],
+        );
+        // x[1] = 1 gives y[1] = 1, and 1 is removed from the middle of the other domains (`Inner` event).
+        inverse_test_one(
+            3,
+            (
+                vec![d0_2.clone(), d1.clone(), d0_2.clone()],
+                vec![d0_2.clone(); 3],
+            ),
+            Unknown,
+            vec![
+                d02.clone(),
+                d1.clone(),
+                d02.clone(),
+                d02.clone(),
+                d1.clone(),
+                d02.clone(),
+            ],
+        );
+        // y[0] = 2 gives x[2] = 0, and x[0] must then be 1 or 2.
+        inverse_test_one(
+            4,
+            (
+                vec![d0_2.clone(); 3],
+                vec![d2.clone(), d0_2.clone(), d0_2.clone()],
+            ),
+            Unknown,
+            vec![
+                d1_2.clone(),
+                d1_2.clone(),
+                d0.clone(),
+                d2.clone(),
+                vec![(0, 1)],
+                vec![(0, 1)],
+            ],
+        );
+        inverse_test_one(
+            5,
+            (
+                vec![d1.clone(), d2.clone(), d0.clone()],
+                vec![d0_2.clone(); 3],
+            ),
+            True,
+            vec![
+                d1.clone(),
+                d2.clone(),
+                d0.clone(),
+                d2.clone(),
+                d0.clone(),
+                d1.clone(),
+            ],
+        );
+        // x[0] = 1 and y[1] = 2 are not inverse.
+        inverse_test_one(
+            6,
+            (
+                vec![d1.clone(), d0_2.clone(), d0_2.clone()],
+                vec![d0_2.clone(), d2.clone(), d0_2.clone()],
+            ),
+            False,
+            vec![],
+        );
+        // Two variables of `x` are equal.
+        inverse_test_one(
+            7,
+            (
+                vec![d1.clone(), d1.clone(), d0_2.clone()],
+                vec![d0_2.clone(); 3],
+            ),
+            False,
+            vec![],
+        );
+    }
+
+    #[test]
+    fn negated_inverse_test() {
+        // [1, 2, 0] is the inverse of [2, 0, 1] but not of [2, 1, 0].
+        for &(y1, expected) in &[(0, False), (1, True)] {
+            let mut vstore = VStoreSet::empty();
+            let mut vars: Vec<_> = (0..6)
+                .map(|_| Box::new(vstore.alloc((0, 2).to_interval_set())) as Var<VStoreSet>)
+                .collect();
+            let y = vars.split_off(3);
+            let mut not_inverse = Inverse::new(
+                vars.iter().map(|v| v.bclone()).collect(),
+                y.iter().map(|v| v.bclone()).collect(),
+            )
+            .not();
+            vars.extend(y);
+            for (i, v) in [1, 2, 0, 2].iter().enumerate() {
+                assert!(vars[i].update(&mut vstore, (*v, *v).to_interval_set()));
+            }
+            assert_eq!(not_inverse.is_subsumed(&vstore), Unknown);
+            assert!(not_inverse.propagate(&mut vstore));
+            assert!(vars[4].update(&mut vstore, (y1, y1).to_interval_set()));
+            assert!(vars[5].update(&mut vstore, (1 - y1, 1 - y1).to_interval_set()));
+            assert_eq!(not_inverse.is_subsumed(&vstore), expected);
+            assert_eq!(not_inverse.propagate(&mut vstore), expected == True);
+        }
+    }
+
+    #[test]
+    fn inverse_solutions_test() {
+        // Every permutation of `x` has exactly one inverse.
+        for n in 1..6 {
+            let doms = vec![vec![(0, n - 1)]; n as usize];
+            let space = inverse_space(doms.clone(), doms);
+            let permutations: usize = (1..(n as usize + 1)).product();
+            assert_eq!(count_assignments(space, 2 * n as usize), permutations);
+        }
+        // With holes in the domains: x[0] != 1 and y[2] != 0.
+        let space = inverse_space(
+            vec![
+                vec![(0, 0), (2, 3)],
+                vec![(0, 3)],
+                vec![(0, 3)],
+                vec![(0, 3)],
+            ],
+            vec![vec![(0, 3)], vec![(0, 3)], vec![(1, 3)], vec![(0, 3)]],
+        );
+        // Among the 24 permutations, 6 have x[0] = 1 and 6 have x[0] = 2, they are disjoint.
+        assert_eq!(count_assignments(space, 8), 12);
+    }
+}
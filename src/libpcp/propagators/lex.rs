@@ -0,0 +1,464 @@
+// Copyright 2018 Pierre Talbot (IRCAM)
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+
+//     http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! The lexicographic ordering constraints `x <lex y` and `x <=lex y` over two arrays of variables of the same length.
+//! They are filtered with the two pointers algorithm enforcing generalized arc consistency described in:
+//!   Frisch, A., Hnich, B., Kiziltan, Z., Miguel, I., & Walsh, T. (2002). Global constraints for lexicographic orderings. In International Conference on Principles and Practice of Constraint Programming (pp. 93-108). Springer.
+
+use concept::*;
+use gcollections::*;
+use kernel::*;
+use logic::*;
+use model::*;
+use propagation::events::*;
+use propagation::*;
+use trilean::SKleene;
+use trilean::SKleene::*;
+
+/// Posts `rows[i] <lex rows[i+1]` (or `rows[i] <=lex rows[i+1]` if `strict` is false) for every two consecutive rows of a matrix.
+/// It breaks the symmetry of a matrix model where the rows are interchangeable.
+pub fn join_lex_chain<VStore, CStore, Domain, Bound>(
+    cstore: &mut CStore,
+    rows: Vec<Vec<Var<VStore>>>,
+    strict: bool,
+) where
+    VStore: VStoreConcept<Item = Domain> + 'static,
+    Domain: IntDomain<Item = Bound> + 'static,
+    Bound: IntBound + 'static,
+    CStore: IntCStore<VStore> + 'static,
+{
+    for pair in rows.windows(2) {
+        let x = pair[0].iter().map(|v| v.bclone()).collect();
+        let y = pair[1].iter().map(|v| v.bclone()).collect();
+        cstore.alloc(Box::new(LexLess::with_strictness(x, y, strict)));
+    }
+}
+
+#[derive(Debug)]
+pub struct LexLess<VStore> {
+    x: Vec<Var<VStore>>,
+    y: Vec<Var<VStore>>,
+    strict: bool,
+}
+
+impl<VStore> LexLess<VStore> {
+    /// `x <lex y`
+    /// Precondition: `x.len() == y.len()`.
+    pub fn new(x: Vec<Var<VStore>>, y: Vec<Var<VStore>>) -> Self {
+        LexLess::with_strictness(x, y, true)
+    }
+
+    /// `x <=lex y`
+    /// Precondition: `x.len() == y.len()`.
+    pub fn less_eq(x: Vec<Var<VStore>>, y: Vec<Var<VStore>>) -> Self {
+        LexLess::with_strictness(x, y, false)
+    }
+
+    fn with_strictness(x: Vec<Var<VStore>>, y: Vec<Var<VStore>>, strict: bool) -> Self {
+        assert_eq!(
+            x.len(),
+            y.len(),
+            "Variable arrays in `LexLess` must have the same length."
+        );
+        LexLess { x, y, strict }
+    }
+}
+
+impl<VStore> Clone for LexLess<VStore>
+where
+    VStore: Collection,
+{
+    fn clone(&self) -> Self {
+        LexLess {
+            x: self.x.iter().map(|v| v.bclone()).collect(),
+            y: self.y.iter().map(|v| v.bclone()).collect(),
+            strict: self.strict,
+        }
+    }
+}
+
+impl<VStore> DisplayStateful<Model> for LexLess<VStore> {
+    fn display(&self, model: &Model) {
+        model.display_array(&self.x);
+        print!("{}", if self.strict { " <lex " } else { " <=lex " });
+        model.display_array(&self.y);
+    }
+}
+
+impl<VStore, Domain, Bound> NotFormula<VStore> for LexLess<VStore>
+where
+    VStore: VStoreConcept<Item = Domain> + 'static,
+    Domain: IntDomain<Item = Bound> + 'static,
+    Bound: IntBound + 'static,
+{
+    /// `not(x <lex y)` is `y <=lex x` and `not(x <=lex y)` is `y <lex x`.
+    fn not(&self) -> Formula<VStore> {
+        Box::new(LexLess {
+            x: self.y.iter().map(|v| v.bclone()).collect(),
+            y: self.x.iter().map(|v| v.bclone()).collect(),
+            strict: !self.strict,
+        })
+    }
+}
+
+impl<VStore, Domain, Bound> Subsumption<VStore> for LexLess<VStore>
+where
+    VStore: Collection<Item = Domain>,
+    Domain: IntDomain<Item = Bound>,
+    Bound: IntBound,
+{
+    fn is_subsumed(&self, vstore: &VStore) -> SKleene {
+        // True: the largest values of `x` are lexicographically smaller than the smallest values of `y`.
+        // False: the smallest values of `x` are lexicographically greater than the largest values of `y`.
+        // Unknown: Everything else.
+        let x_min: Vec<_> = self.x.iter().map(|v| v.read(vstore).lower()).collect();
+        let x_max: Vec<_> = self.x.iter().map(|v| v.read(vstore).upper()).collect();
+        let y_min: Vec<_> = self.y.iter().map(|v| v.read(vstore).lower()).collect();
+        let y_max: Vec<_> = self.y.iter().map(|v| v.read(vstore).upper()).collect();
+        if self.strict {
+            if x_max < y_min {
+                True
+            } else if x_min >= y_max {
+                False
+            } else {
+                Unknown
+            }
+        } else if x_max <= y_min {
+            True
+        } else if x_min > y_max {
+            False
+        } else {
+            Unknown
+        }
+    }
+}
+
+impl<VStore, Domain, Bound> LexLess<VStore>
+where
+    VStore: Collection<Item = Domain>,
+    Domain: IntDomain<Item = Bound>,
+    Bound: IntBound,
+{
+    /// The first index `alpha` from which `x[alpha]` and `y[alpha]` are not assigned to the same value.
+    fn alpha(&self, vstore: &VStore, from: usize) -> usize {
+        let mut alpha = from;
+        while alpha < self.x.len() {
+            let x = self.x[alpha].read(vstore);
+            let y = self.y[alpha].read(vstore);
+            if !(x.is_singleton() && y.is_singleton() && x.lower() == y.lower()) {
+                break;
+            }
+            alpha += 1;
+        }
+        alpha
+    }
+
+    /// The first index `beta` from which the suffix of `x` cannot be lexicographically smaller than (or equal to, if not strict) the suffix of `y`.
+    /// It is `n + 1` if there is no such index.
+    fn beta(&self, vstore: &VStore, alpha: usize) -> usize {
+        let n = self.x.len();
+        let mut beta = None;
+        let mut i = alpha;
+        while i < n {
+            let x = self.x[i].read(vstore);
+            let y = self.y[i].read(vstore);
+            if x.lower() > y.upper() {
+                return beta.unwrap_or(i);
+            } else if x.lower() == y.upper() {
+                beta = beta.or(Some(i));
+            } else {
+                beta = None;
+            }
+            i += 1;
+        }
+        // The suffix of `x` is necessarily greater or equal to the suffix of `y` from `beta`.
+        if self.strict {
+            beta.unwrap_or(n)
+        } else {
+            n + 1
+        }
+    }
+}
+
+impl<VStore, Domain, Bound> Propagator<VStore> for LexLess<VStore>
+where
+    VStore: Collection<Item = Domain>,
+    Domain: IntDomain<Item = Bound>,
+    Bound: IntBound,
+{
+    /// The filtering only happens at the position `alpha`: `x[alpha] <= y[alpha]`, or `x[alpha] < y[alpha]` if `beta == alpha + 1`.
+    /// When `x[alpha]` and `y[alpha]` become equal, `alpha` moves to the next position.
+    fn propagate(&mut self, vstore: &mut VStore) -> bool {
+        let n = self.x.len();
+        let mut alpha = 0;
+        loop {
+            alpha = self.alpha(vstore, alpha);
+            if alpha == n {
+                return !self.strict;
+            }
+            let beta = self.beta(vstore, alpha);
+            if beta <= alpha {
+                return false;
+            }
+            let x = self.x[alpha].read(vstore);
+            let y = self.y[alpha].read(vstore);
+            if x.upper() < y.lower() {
+                return true;
+            }
+            let (x, y) = if beta == alpha + 1 {
+                (
+                    x.shrink_right(y.upper() - Bound::one()),
+                    y.shrink_left(x.lower() + Bound::one()),
+                )
+            } else {
+                (x.shrink_right(y.upper()), y.shrink_left(x.lower()))
+            };
+            if !self.x[alpha].update(vstore, x.clone()) || !self.y[alpha].update(vstore, y.clone())
+            {
+                return false;
+            }
+            if !(x.is_singleton() && y.is_singleton() && x.lower() == y.lower()) {
+                return true;
+            }
+        }
+    }
+}
+
+impl<VStore> PropagatorDependencies<FDEvent> for LexLess<VStore> {
+    fn dependencies(&self) -> Vec<(usize, FDEvent)> {
+        self.x
+            .iter()
+            .chain(self.y.iter())
+            .flat_map(|v| v.dependencies(FDEvent::Bound))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use gcollections::ops::*;
+    use interval::interval::*;
+    use interval::interval_set::*;
+    use propagation::events::FDEvent::*;
+    use propagators::test::*;
+    use search::test::*;
+
+    #[test]
+    fn lex_less_test() {
+        let zero = (0, 0).to_interval();
+        let one = (1, 1).to_interval();
+        let two = (2, 2).to_interval();
+        let dom0_1 = (0, 1).to_interval();
+        let dom0_2 = (0, 2).to_interval();
+        let dom1_2 = (1, 2).to_interval();
+
+        // [x1, x2] <lex [y1, y2]
+        lex_test_one(
+            1,
+            true,
+            vec![dom0_1, dom0_1, dom0_1, dom0_1],
+            Unknown,
+            Unknown,
+            vec![],
+            true,
+        );
+        lex_test_one(
+            2,
+            true,
+            vec![dom0_2, dom0_2, one, dom0_2],
+            Unknown,
+            Unknown,
+            vec![(0, Bound)],
+            true,
+        );
+        // x1 = y1 is not possible since x2 >= y2.
+        lex_test_one(
+            3,
+            true,
+            vec![dom0_2, two, dom1_2, one],
+            Unknown,
+            Unknown,
+            vec![(0, Bound)],
+            true,
+        );
+        lex_test_one(
+            4,
+            true,
+            vec![one, dom0_2, one, dom0_1],
+            Unknown,
+            True,
+            vec![(1, Assignment), (3, Assignment)],
+            true,
+        );
+        lex_test_one(
+            5,
+            true,
+            vec![one, two, one, dom0_2],
+            False,
+            False,
+            vec![],
+            false,
+        );
+        lex_test_one(
+            6,
+            true,
+            vec![one, one, one, one],
+            False,
+            False,
+            vec![],
+            false,
+        );
+        lex_test_one(
+            7,
+            true,
+            vec![zero, two, one, zero],
+            True,
+            True,
+            vec![],
+            true,
+        );
+        lex_test_one(
+            8,
+            true,
+            vec![dom0_2, dom0_2, zero, dom0_2],
+            Unknown,
+            Unknown,
+            vec![(0, Assignment), (1, Bound), (3, Bound)],
+            true,
+        );
+    }
+
+    #[test]
+    fn lex_less_eq_test() {
+        let zero = (0, 0).to_interval();
+        let one = (1, 1).to_interval();
+        let two = (2, 2).to_interval();
+        let dom0_1 = (0, 1).to_interval();
+        let dom0_2 = (0, 2).to_interval();
+        let dom1_2 = (1, 2).to_interval();
+
+        // [x1, x2] <=lex [y1, y2]
+        lex_test_one(
+            1,
+            false,
+            vec![dom0_1, dom0_1, dom0_1, dom0_1],
+            Unknown,
+            Unknown,
+            vec![],
+            true,
+        );
+        lex_test_one(
+            2,
+            false,
+            vec![dom0_2, two, dom1_2, one],
+            Unknown,
+            Unknown,
+            vec![(0, Bound)],
+            true,
+        );
+        lex_test_one(
+            3,
+            false,
+            vec![one, dom0_2, one, dom0_1],
+            Unknown,
+            Unknown,
+            vec![(1, Bound)],
+            true,
+        );
+        lex_test_one(4, false, vec![one, one, one, one], True, True, vec![], true);
+        lex_test_one(
+            5,
+            false,
+            vec![one, two, one, dom0_1],
+            False,
+            False,
+            vec![],
+            false,
+        );
+        lex_test_one(
+            6,
+            false,
+            vec![dom0_2, zero, zero, dom0_2],
+            Unknown,
+            True,
+            vec![(0, Assignment)],
+            true,
+        );
+    }
+
+    fn lex_test_one(
+        test_num: u32,
+        strict: bool,
+        doms: Vec<Interval<i32>>,
+        before: SKleene,
+        after: SKleene,
+        delta_expected: Vec<(usize, FDEvent)>,
+        propagate_success: bool,
+    ) {
+        nary_propagator_test(
+            test_num,
+            |mut x: Vec<FDVar>| {
+                let y = x.split_off(x.len() / 2);
+                if strict {
+                    LexLess::new(x, y)
+                } else {
+                    LexLess::less_eq(x, y)
+                }
+            },
+            doms,
+            before,
+            after,
+            delta_expected,
+            propagate_success,
+        );
+    }
+
+    /// Number of assignments of a `rows x cols` matrix over `[0..2]` where the rows are lexicographically ordered.
+    fn count_lex_chain(rows: usize, cols: usize, strict: bool, negated: bool) -> usize {
+        let mut space = FDSpace::empty();
+        let matrix: Vec<Vec<_>> = (0..rows)
+            .map(|_| {
+                (0..cols)
+                    .map(|_| Box::new(space.vstore.alloc((0, 2).to_interval_set())) as Var<VStore>)
+                    .collect()
+            })
+            .collect();
+        if negated {
+            let lex = LexLess::with_strictness(
+                matrix[0].iter().map(|v| v.bclone()).collect(),
+                matrix[1].iter().map(|v| v.bclone()).collect(),
+                strict,
+            );
+            space.cstore.alloc(lex.not());
+        } else {
+            join_lex_chain(&mut space.cstore, matrix, strict);
+        }
+        count_assignments(space, rows * cols)
+    }
+
+    #[test]
+    fn lex_chain_solutions_test() {
+        // The 9 rows of length 2 over [0..2] are totally ordered.
+        // Non-decreasing sequences of 3 rows: C(9 + 2, 3) = 165.
+        assert_eq!(count_lex_chain(3, 2, false, false), 165);
+        // Increasing sequences of 3 rows: C(9, 3) = 84.
+        assert_eq!(count_lex_chain(3, 2, true, false), 84);
+        // Pairs of rows of length 3 over [0..2]: 27 * 27 = 729, 27 of them are equal.
+        assert_eq!(count_lex_chain(2, 3, true, false), (729 - 27) / 2);
+        assert_eq!(count_lex_chain(2, 3, false, false), (729 - 27) / 2 + 27);
+        assert_eq!(count_lex_chain(2, 3, true, true), (729 - 27) / 2 + 27);
+        assert_eq!(count_lex_chain(2, 3, false, true), (729 - 27) / 2);
+    }
+}
@@ -17,13 +17,40 @@
 //! We keep the propagator implementations generic over domains implementing specific operations (e.g. intersection or union). Propagators are also implemented to work on variable views, you can always obtain a view from a variable by using the `Identity` view.
 
 pub mod all_equal;
+pub mod bin_packing;
+pub mod circuit;
+pub mod clause;
 pub mod cmp;
+pub mod count;
 pub mod cumulative;
+pub mod diffn;
+pub mod disjunctive;
 pub mod distinct;
+pub mod element;
+pub mod global_cardinality;
+pub mod inverse;
+pub mod lex;
+pub mod pseudo_boolean;
+pub mod regular;
+pub mod table;
+mod values;
 
 pub use propagators::all_equal::*;
+pub use propagators::bin_packing::*;
+pub use propagators::circuit::*;
+pub use propagators::clause::*;
 pub use propagators::cmp::*;
+pub use propagators::count::*;
+pub use propagators::diffn::*;
+pub use propagators::disjunctive::*;
 pub use propagators::distinct::*;
+pub use propagators::element::*;
+pub use propagators::global_cardinality::*;
+pub use propagators::inverse::*;
+pub use propagators::lex::*;
+pub use propagators::pseudo_boolean::*;
+pub use propagators::regular::*;
+pub use propagators::table::*;
 
 #[cfg(test)]
 pub mod test {
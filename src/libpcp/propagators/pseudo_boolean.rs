@@ -0,0 +1,517 @@
+// Copyright 2018 Pierre Talbot (IRCAM)
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+
+//     http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! The `PseudoBoolean` constraint `sum(i in 0..n)(coefs[i] * vars[i]) >= k` over 0/1 variables.
+//! The negative coefficients are normalized with `a * b = a - (-a) * (not b)`, so that the constraint is a sum of positive coefficients over literals.
+//! The propagation watches a set of non-false literals whose coefficients sum to at least `k + max(coefs)`: setting any literal to false cannot make another literal necessary, so the propagator only checks the watched literals, as described in `A new advanced pseudo-Boolean solver`, Chai and Kuehlmann, 2003.
+//! When no more literal can be watched, the watched literals are all the non-false ones and those whose coefficient is greater than the slack are set to true.
+//! The propagator only subscribes to the watched literals and the constraint store moves its subscriptions with the watches (see `PropagatorDependencies::has_dynamic_dependencies`).
+//! The watches stay valid when backtracking, so the propagator is not stateful.
+
+use concept::*;
+use gcollections::*;
+use kernel::*;
+use logic::*;
+use model::*;
+use propagation::events::*;
+use propagation::*;
+use std::fmt::Debug;
+use trilean::SKleene;
+use trilean::SKleene::*;
+
+/// The value of the literal of the 0/1 variable with the domain `dom`, it is the negation of the variable if not `positive`.
+pub(crate) fn literal_value<Domain, Bound>(dom: &Domain, positive: bool) -> SKleene
+where
+    Domain: IntDomain<Item = Bound>,
+    Bound: IntBound,
+{
+    let value = if dom.lower() >= Bound::one() {
+        True
+    } else if dom.upper() <= Bound::zero() {
+        False
+    } else {
+        Unknown
+    };
+    if positive {
+        value
+    } else {
+        !value
+    }
+}
+
+/// Sets the literal of the 0/1 variable `var` to true.
+pub(crate) fn literal_update<VStore, Domain, Bound>(
+    vstore: &mut VStore,
+    var: &mut Var<VStore>,
+    positive: bool,
+) -> bool
+where
+    VStore: Collection<Item = Domain>,
+    Domain: IntDomain<Item = Bound>,
+    Bound: IntBound,
+{
+    let dom = var.read(vstore);
+    let dom = if positive {
+        dom.shrink_left(Bound::one())
+    } else {
+        dom.shrink_right(Bound::zero())
+    };
+    var.update(vstore, dom)
+}
+
+#[derive(Debug)]
+pub struct PseudoBoolean<VStore, Bound> {
+    /// The positive coefficients in decreasing order.
+    coefs: Vec<Bound>,
+    vars: Vec<Var<VStore>>,
+    /// `positive[i]` is `false` if the literal `i` is the negation of `vars[i]`.
+    positive: Vec<bool>,
+    k: Bound,
+    /// The indices of the watched literals.
+    watches: Vec<usize>,
+    watched: Vec<bool>,
+    /// The sum of the coefficients of the watched literals.
+    watched_sum: Bound,
+}
+
+impl<VStore, Bound> PseudoBoolean<VStore, Bound>
+where
+    Bound: IntBound,
+{
+    /// Precondition: `coefs.len() == vars.len()` and the variables are 0/1 variables.
+    pub fn new(coefs: Vec<Bound>, vars: Vec<Var<VStore>>, k: Bound) -> Self {
+        let positive = vec![true; vars.len()];
+        PseudoBoolean::with_literals(coefs, vars, positive, k)
+    }
+
+    /// `sum(i in 0..n)(coefs[i] * l[i]) >= k` where `l[i]` is `vars[i]` if `positive[i]`, and `not vars[i]` otherwise.
+    pub(crate) fn with_literals(
+        coefs: Vec<Bound>,
+        vars: Vec<Var<VStore>>,
+        positive: Vec<bool>,
+        mut k: Bound,
+    ) -> Self {
+        assert_eq!(
+            coefs.len(),
+            vars.len(),
+            "Every variable in `PseudoBoolean` must have a coefficient."
+        );
+        let mut literals: Vec<_> = coefs
+            .into_iter()
+            .zip(vars.into_iter().zip(positive))
+            .filter(|(a, _)| *a != Bound::zero())
+            .map(|(a, (var, positive))| {
+                if a < Bound::zero() {
+                    k = k.clone() - a.clone();
+                    (-a, (var, !positive))
+                } else {
+                    (a, (var, positive))
+                }
+            })
+            .collect();
+        literals.sort_by(|a, b| b.0.cmp(&a.0));
+        let n = literals.len();
+        let (coefs, literals): (Vec<_>, Vec<_>) = literals.into_iter().unzip();
+        let (vars, positive) = literals.into_iter().unzip();
+        PseudoBoolean {
+            coefs,
+            vars,
+            positive,
+            k,
+            watches: vec![],
+            watched: vec![false; n],
+            watched_sum: Bound::zero(),
+        }
+    }
+
+    fn max_coef(&self) -> Bound {
+        self.coefs.first().cloned().unwrap_or_else(Bound::zero)
+    }
+
+    fn total(&self) -> Bound {
+        self.coefs
+            .iter()
+            .fold(Bound::zero(), |acc, a| acc + a.clone())
+    }
+}
+
+/// `sum(i in 0..n)(coefs[i] * vars[i]) <= k`
+pub fn pseudo_boolean_leq<VStore, Bound>(
+    coefs: Vec<Bound>,
+    vars: Vec<Var<VStore>>,
+    k: Bound,
+) -> PseudoBoolean<VStore, Bound>
+where
+    Bound: IntBound,
+{
+    PseudoBoolean::new(coefs.into_iter().map(|a| -a).collect(), vars, -k)
+}
+
+impl<VStore, Bound> Clone for PseudoBoolean<VStore, Bound>
+where
+    VStore: Collection,
+    Bound: Clone,
+{
+    fn clone(&self) -> Self {
+        PseudoBoolean {
+            coefs: self.coefs.clone(),
+            vars: self.vars.iter().map(|v| v.bclone()).collect(),
+            positive: self.positive.clone(),
+            k: self.k.clone(),
+            watches: self.watches.clone(),
+            watched: self.watched.clone(),
+            watched_sum: self.watched_sum.clone(),
+        }
+    }
+}
+
+impl<VStore, Bound> DisplayStateful<Model> for PseudoBoolean<VStore, Bound>
+where
+    Bound: Debug,
+{
+    fn display(&self, model: &Model) {
+        if self.vars.is_empty() {
+            print!("0");
+        }
+        for (i, var) in self.vars.iter().enumerate() {
+            if i > 0 {
+                print!(" + ");
+            }
+            print!("{:?}*", self.coefs[i]);
+            if !self.positive[i] {
+                print!("not ");
+            }
+            var.display(model);
+        }
+        print!(" >= {:?}", self.k);
+    }
+}
+
+impl<VStore, Domain, Bound> NotFormula<VStore> for PseudoBoolean<VStore, Bound>
+where
+    VStore: VStoreConcept<Item = Domain> + 'static,
+    Domain: IntDomain<Item = Bound> + 'static,
+    Bound: IntBound + 'static,
+{
+    /// `sum(a[i] * l[i]) < k` is `sum(a[i] * not l[i]) >= sum(a[i]) - k + 1`.
+    fn not(&self) -> Formula<VStore> {
+        Box::new(PseudoBoolean::with_literals(
+            self.coefs.clone(),
+            self.vars.iter().map(|v| v.bclone()).collect(),
+            self.positive.iter().map(|p| !p).collect(),
+            self.total() - self.k.clone() + Bound::one(),
+        ))
+    }
+}
+
+impl<VStore, Domain, Bound> PseudoBoolean<VStore, Bound>
+where
+    VStore: Collection<Item = Domain>,
+    Domain: IntDomain<Item = Bound>,
+    Bound: IntBound,
+{
+    fn value(&self, vstore: &VStore, i: usize) -> SKleene {
+        literal_value(&self.vars[i].read(vstore), self.positive[i])
+    }
+
+    /// The sums of the coefficients of the true and non-false literals in `literals`.
+    fn sums<'a, I>(&self, vstore: &VStore, literals: I) -> (Bound, Bound)
+    where
+        I: Iterator<Item = &'a usize>,
+    {
+        let mut sum_true = Bound::zero();
+        let mut sum_non_false = Bound::zero();
+        for &i in literals {
+            match self.value(vstore, i) {
+                True => {
+                    sum_true = sum_true + self.coefs[i].clone();
+                    sum_non_false = sum_non_false + self.coefs[i].clone();
+                }
+                Unknown => sum_non_false = sum_non_false + self.coefs[i].clone(),
+                False => (),
+            }
+        }
+        (sum_true, sum_non_false)
+    }
+}
+
+impl<VStore, Domain, Bound> Subsumption<VStore> for PseudoBoolean<VStore, Bound>
+where
+    VStore: Collection<Item = Domain>,
+    Domain: IntDomain<Item = Bound>,
+    Bound: IntBound,
+{
+    fn is_subsumed(&self, vstore: &VStore) -> SKleene {
+        // The watched literals are sufficient to decide the entailment, or that the constraint is not yet decided if they sum to at least `k + max(coefs)`.
+        let (sum_true, sum_non_false) = self.sums(vstore, self.watches.iter());
+        if sum_true >= self.k {
+            True
+        } else if sum_non_false >= self.k.clone() + self.max_coef() {
+            Unknown
+        } else {
+            let all: Vec<_> = (0..self.vars.len()).collect();
+            let (sum_true, sum_non_false) = self.sums(vstore, all.iter());
+            if sum_true >= self.k {
+                True
+            } else if sum_non_false < self.k {
+                False
+            } else {
+                Unknown
+            }
+        }
+    }
+}
+
+impl<VStore, Domain, Bound> Propagator<VStore> for PseudoBoolean<VStore, Bound>
+where
+    VStore: Collection<Item = Domain>,
+    Domain: IntDomain<Item = Bound>,
+    Bound: IntBound,
+{
+    fn propagate(&mut self, vstore: &mut VStore) -> bool {
+        let mut w = 0;
+        while w < self.watches.len() {
+            let i = self.watches[w];
+            if self.value(vstore, i) == False {
+                self.watched[i] = false;
+                self.watched_sum = self.watched_sum.clone() - self.coefs[i].clone();
+                self.watches.swap_remove(w);
+            } else {
+                w += 1;
+            }
+        }
+        let target = self.k.clone() + self.max_coef();
+        let mut i = 0;
+        while self.watched_sum < target && i < self.vars.len() {
+            if !self.watched[i] && self.value(vstore, i) != False {
+                self.watched[i] = true;
+                self.watched_sum = self.watched_sum.clone() + self.coefs[i].clone();
+                self.watches.push(i);
+            }
+            i += 1;
+        }
+        if self.watched_sum >= target {
+            return true;
+        }
+        // All the non-false literals are watched.
+        if self.watched_sum < self.k {
+            return false;
+        }
+        let slack = self.watched_sum.clone() - self.k.clone();
+        for &i in &self.watches {
+            if self.coefs[i] > slack
+                && self.value(vstore, i) == Unknown
+                && !literal_update(vstore, &mut self.vars[i], self.positive[i])
+            {
+                return false;
+            }
+        }
+        true
+    }
+}
+
+impl<VStore, Bound> PropagatorDependencies<FDEvent> for PseudoBoolean<VStore, Bound>
+where
+    Bound: IntBound,
+{
+    /// Only the watched literals are subscribed while they sum to at least `k + max(coefs)`, otherwise all the literals are subscribed since a literal made false but not watched on backtracking can make the other literals necessary.
+    fn dependencies(&self) -> Vec<(usize, FDEvent)> {
+        let mut deps: Vec<_> = if self.watched_sum >= self.k.clone() + self.max_coef() {
+            self.watches
+                .iter()
+                .flat_map(|&i| self.vars[i].dependencies(FDEvent::Assignment))
+                .collect()
+        } else {
+            self.vars
+                .iter()
+                .flat_map(|v| v.dependencies(FDEvent::Assignment))
+                .collect()
+        };
+        deps.sort();
+        deps.dedup();
+        deps
+    }
+
+    fn has_dynamic_dependencies(&self) -> bool {
+        true
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use gcollections::ops::*;
+    use interval::interval::*;
+    use interval::interval_set::*;
+    use propagation::events::FDEvent::*;
+    use propagators::test::*;
+    use search::test::*;
+    use variable::VStoreFD;
+
+    #[test]
+    fn pseudo_boolean_test() {
+        let zero = (0, 0).to_interval();
+        let one = (1, 1).to_interval();
+        let dom0_1 = (0, 1).to_interval();
+
+        // 3*x0 + 2*x1 + x2 >= 4
+        let geq4 = |vars| PseudoBoolean::new(vec![3, 2, 1], vars, 4);
+        pseudo_boolean_test_one(
+            1,
+            geq4,
+            vec![dom0_1, dom0_1, dom0_1],
+            Unknown,
+            Unknown,
+            vec![(0, Assignment)],
+            true,
+        );
+        pseudo_boolean_test_one(
+            2,
+            geq4,
+            vec![zero, dom0_1, dom0_1],
+            False,
+            False,
+            vec![],
+            false,
+        );
+        pseudo_boolean_test_one(
+            3,
+            geq4,
+            vec![dom0_1, zero, dom0_1],
+            Unknown,
+            True,
+            vec![(0, Assignment), (2, Assignment)],
+            true,
+        );
+        pseudo_boolean_test_one(4, geq4, vec![one, one, dom0_1], True, True, vec![], true);
+        // 2*x0 - x1 >= 1 is 2*x0 + not x1 >= 2.
+        let neg = |vars| PseudoBoolean::new(vec![2, -1], vars, 1);
+        pseudo_boolean_test_one(
+            5,
+            neg,
+            vec![dom0_1, dom0_1],
+            Unknown,
+            True,
+            vec![(0, Assignment)],
+            true,
+        );
+        pseudo_boolean_test_one(
+            6,
+            neg,
+            vec![dom0_1, zero],
+            Unknown,
+            True,
+            vec![(0, Assignment)],
+            true,
+        );
+        // x0 + x1 + x2 <= 1
+        let at_most_one = |vars| pseudo_boolean_leq(vec![1, 1, 1], vars, 1);
+        pseudo_boolean_test_one(
+            7,
+            at_most_one,
+            vec![dom0_1, dom0_1, dom0_1],
+            Unknown,
+            Unknown,
+            vec![],
+            true,
+        );
+        pseudo_boolean_test_one(
+            8,
+            at_most_one,
+            vec![dom0_1, one, dom0_1],
+            Unknown,
+            True,
+            vec![(0, Assignment), (2, Assignment)],
+            true,
+        );
+        pseudo_boolean_test_one(
+            9,
+            at_most_one,
+            vec![one, one, dom0_1],
+            False,
+            False,
+            vec![],
+            false,
+        );
+    }
+
+    fn pseudo_boolean_test_one<F>(
+        test_num: u32,
+        make_prop: F,
+        doms: Vec<Interval<i32>>,
+        before: SKleene,
+        after: SKleene,
+        delta_expected: Vec<(usize, FDEvent)>,
+        propagate_success: bool,
+    ) where
+        F: FnOnce(Vec<FDVar>) -> PseudoBoolean<VStoreFD, i32>,
+    {
+        nary_propagator_test(
+            test_num,
+            make_prop,
+            doms,
+            before,
+            after,
+            delta_expected,
+            propagate_success,
+        );
+    }
+
+    fn count_pseudo_boolean(coefs: &[i32], k: i32, negated: bool) -> usize {
+        let mut space = FDSpace::empty();
+        let vars: Vec<_> = coefs
+            .iter()
+            .map(|_| Box::new(space.vstore.alloc((0, 1).to_interval_set())) as Var<VStore>)
+            .collect();
+        let pb = PseudoBoolean::new(coefs.to_vec(), vars, k);
+        if negated {
+            space.cstore.alloc(pb.not());
+        } else {
+            space.cstore.alloc(Box::new(pb));
+        }
+        count_assignments(space, coefs.len())
+    }
+
+    fn brute_force(coefs: &[i32], k: i32) -> usize {
+        let n = coefs.len();
+        (0..(1 << n))
+            .filter(|&a: &usize| {
+                let sum: i32 = (0..n)
+                    .filter(|&i| a & (1 << i) != 0)
+                    .map(|i| coefs[i])
+                    .sum();
+                sum >= k
+            })
+            .count()
+    }
+
+    #[test]
+    fn pseudo_boolean_solutions_test() {
+        let instances = vec![
+            (vec![3, 2, 1], 4),
+            (vec![5, 3, 3, 2, 1, 1], 8),
+            (vec![4, -3, 2, -2, 1], 1),
+            (vec![1, 1, 1, 1, 1], 3),
+            (vec![2, 0, -1], -1),
+        ];
+        for (coefs, k) in instances {
+            let expected = brute_force(&coefs, k);
+            assert_eq!(count_pseudo_boolean(&coefs, k, false), expected);
+            assert_eq!(
+                count_pseudo_boolean(&coefs, k, true),
+                (1 << coefs.len()) - expected
+            );
+        }
+    }
+}
@@ -0,0 +1,522 @@
+// Copyright 2018 Pierre Talbot (IRCAM)
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+
+//     http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! The `Regular` constraint forces a sequence of variables to be a word accepted by a deterministic finite automaton.
+//! The filtering algorithm reaches domain consistency on the layered graph of the automaton, as described in:
+//!   Pesant, G. (2004). A regular language membership constraint for finite sequences of variables. In International Conference on Principles and Practice of Constraint Programming (pp. 482-495). Springer.
+//! The layered graph is the internal state of the propagator: the states removed from a layer are never visited again in the sub-tree, it is restored by the constraint store on backtracking (see `Propagator::is_stateful`).
+
+use concept::*;
+use gcollections::*;
+use kernel::*;
+use logic::*;
+use model::*;
+use propagation::events::*;
+use propagation::*;
+use propagators::values::restrict;
+use std::rc::Rc;
+use trilean::SKleene;
+use trilean::SKleene::*;
+
+/// A deterministic finite automaton where the states are numbered from `0` to `num_states - 1`.
+/// A state can have a default transition taken on every value without an explicit transition from this state.
+#[derive(Clone, Debug)]
+pub struct Automaton<Bound> {
+    initial: usize,
+    accepting: Vec<bool>,
+    /// `transitions[q]` are the explicit transitions `(v, q')` from `q` sorted by values.
+    transitions: Vec<Vec<(Bound, usize)>>,
+    otherwise: Vec<Option<usize>>,
+}
+
+impl<Bound> Automaton<Bound> {
+    pub fn num_states(&self) -> usize {
+        self.accepting.len()
+    }
+}
+
+impl<Bound> Automaton<Bound>
+where
+    Bound: IntBound,
+{
+    /// `transitions` contains the triples `(q, v, q')` meaning that we go from `q` to `q'` when reading `v`.
+    pub fn new(
+        num_states: usize,
+        initial: usize,
+        accepting: Vec<usize>,
+        transitions: Vec<(usize, Bound, usize)>,
+    ) -> Self {
+        assert!(initial < num_states, "The initial state does not exist.");
+        let mut is_accepting = vec![false; num_states];
+        for q in accepting {
+            is_accepting[q] = true;
+        }
+        let mut delta = vec![vec![]; num_states];
+        for (from, v, to) in transitions {
+            assert!(to < num_states, "The state {} does not exist.", to);
+            delta[from].push((v, to));
+        }
+        for ts in &mut delta {
+            ts.sort();
+            assert!(
+                ts.windows(2).all(|w| w[0].0 != w[1].0),
+                "The automaton must be deterministic."
+            );
+        }
+        Automaton {
+            initial,
+            accepting: is_accepting,
+            transitions: delta,
+            otherwise: vec![None; num_states],
+        }
+    }
+
+    /// Adds a transition from `from` to `to` for every value without an explicit transition from `from`.
+    pub fn with_default_transition(mut self, from: usize, to: usize) -> Self {
+        assert!(to < self.num_states(), "The state {} does not exist.", to);
+        self.otherwise[from] = Some(to);
+        self
+    }
+
+    /// The automaton accepting exactly the words rejected by `self`.
+    /// A sink state, taken on every value without a transition in `self`, completes the transition function.
+    pub fn complement(&self) -> Self {
+        let sink = self.num_states();
+        let mut automaton = self.clone();
+        automaton.transitions.push(vec![]);
+        automaton.otherwise = self
+            .otherwise
+            .iter()
+            .map(|d| Some(d.unwrap_or(sink)))
+            .chain(Some(Some(sink)))
+            .collect();
+        automaton.accepting = self
+            .accepting
+            .iter()
+            .map(|a| !a)
+            .chain(Some(true))
+            .collect();
+        automaton
+    }
+
+    fn labels(&self, q: usize) -> impl Iterator<Item = &Bound> {
+        self.transitions[q].iter().map(|(v, _)| v)
+    }
+}
+
+#[derive(Debug)]
+pub struct Regular<VStore, Bound> {
+    vars: Vec<Var<VStore>>,
+    automaton: Rc<Automaton<Bound>>,
+    /// `alive[i][q]` is `false` if there is no accepting path going through the state `q` of the layer `i`.
+    alive: Vec<Vec<bool>>,
+}
+
+impl<VStore, Bound> Regular<VStore, Bound>
+where
+    Bound: IntBound,
+{
+    pub fn new(vars: Vec<Var<VStore>>, automaton: Automaton<Bound>) -> Self {
+        Regular::with_automaton(vars, Rc::new(automaton))
+    }
+
+    fn with_automaton(vars: Vec<Var<VStore>>, automaton: Rc<Automaton<Bound>>) -> Self {
+        let alive = vec![vec![true; automaton.num_states()]; vars.len() + 1];
+        Regular {
+            vars,
+            automaton,
+            alive,
+        }
+    }
+}
+
+impl<VStore, Bound> Clone for Regular<VStore, Bound>
+where
+    VStore: Collection,
+{
+    fn clone(&self) -> Self {
+        Regular {
+            vars: self.vars.iter().map(|v| v.bclone()).collect(),
+            automaton: self.automaton.clone(),
+            alive: self.alive.clone(),
+        }
+    }
+}
+
+impl<VStore, Bound> DisplayStateful<Model> for Regular<VStore, Bound> {
+    fn display(&self, model: &Model) {
+        print!("regular(");
+        model.display_array(&self.vars);
+        print!(", {} states)", self.automaton.num_states());
+    }
+}
+
+impl<VStore, Domain, Bound> NotFormula<VStore> for Regular<VStore, Bound>
+where
+    VStore: VStoreConcept<Item = Domain> + 'static,
+    Domain: IntDomain<Item = Bound> + 'static,
+    Bound: IntBound + 'static,
+{
+    fn not(&self) -> Formula<VStore> {
+        let vars = self.vars.iter().map(|v| v.bclone()).collect();
+        Box::new(Regular::new(vars, self.automaton.complement()))
+    }
+}
+
+impl<VStore, Domain, Bound> Regular<VStore, Bound>
+where
+    VStore: Collection<Item = Domain>,
+    Domain: IntDomain<Item = Bound>,
+    Bound: IntBound,
+{
+    /// `true` if `dom` contains a value without an explicit transition from `q`.
+    fn has_default_value(&self, q: usize, dom: &Domain) -> bool {
+        let mut rest = dom.clone();
+        for v in self.automaton.labels(q) {
+            if rest.contains(v) {
+                rest = rest.difference(v);
+            }
+        }
+        !rest.is_empty()
+    }
+
+    /// The states of each layer reachable from the initial state.
+    fn forward(&self, doms: &[Domain]) -> Vec<Vec<bool>> {
+        let n = self.automaton.num_states();
+        let mut reached = vec![vec![false; n]; doms.len() + 1];
+        reached[0][self.automaton.initial] = self.alive[0][self.automaton.initial];
+        for (i, dom) in doms.iter().enumerate() {
+            for q in 0..n {
+                if !reached[i][q] {
+                    continue;
+                }
+                for (v, to) in &self.automaton.transitions[q] {
+                    if self.alive[i + 1][*to] && dom.contains(v) {
+                        reached[i + 1][*to] = true;
+                    }
+                }
+                if let Some(to) = self.automaton.otherwise[q] {
+                    if self.alive[i + 1][to] && self.has_default_value(q, dom) {
+                        reached[i + 1][to] = true;
+                    }
+                }
+            }
+        }
+        reached
+    }
+
+    fn accepts(&self, reached: &[Vec<bool>]) -> bool {
+        reached[reached.len() - 1]
+            .iter()
+            .zip(self.automaton.accepting.iter())
+            .any(|(r, a)| *r && *a)
+    }
+
+    /// Removes the values of `dom` that do not label an arc on an accepting path, the states `q` with `defaults[q]` have a default arc on such a path.
+    fn filter_domain(
+        &self,
+        dom: Domain,
+        mut supported: Vec<Bound>,
+        defaults: Vec<usize>,
+    ) -> Domain {
+        supported.sort();
+        supported.dedup();
+        if defaults.is_empty() {
+            return restrict(dom, &supported);
+        }
+        // A value is not supported if it has an explicit transition from every state with a default arc.
+        let mut dom = dom;
+        for v in self.automaton.labels(defaults[0]) {
+            let explicit_everywhere = defaults[1..].iter().all(|&q| {
+                self.automaton.transitions[q]
+                    .binary_search_by(|(w, _)| w.cmp(v))
+                    .is_ok()
+            });
+            if explicit_everywhere && supported.binary_search(v).is_err() && dom.contains(v) {
+                dom = dom.difference(v);
+            }
+        }
+        dom
+    }
+}
+
+impl<VStore, Domain, Bound> Subsumption<VStore> for Regular<VStore, Bound>
+where
+    VStore: Collection<Item = Domain>,
+    Domain: IntDomain<Item = Bound>,
+    Bound: IntBound,
+{
+    fn is_subsumed(&self, vstore: &VStore) -> SKleene {
+        // False: No word accepted by the automaton is in the domains.
+        // True: The variables are assigned to an accepted word.
+        // Unknown: Everything else.
+        let doms: Vec<_> = self.vars.iter().map(|v| v.read(vstore)).collect();
+        let reached = self.forward(&doms);
+        if !self.accepts(&reached) {
+            False
+        } else if doms.iter().all(|d| d.is_singleton()) {
+            True
+        } else {
+            Unknown
+        }
+    }
+}
+
+impl<VStore, Domain, Bound> Propagator<VStore> for Regular<VStore, Bound>
+where
+    VStore: Collection<Item = Domain>,
+    Domain: IntDomain<Item = Bound>,
+    Bound: IntBound,
+{
+    fn propagate(&mut self, vstore: &mut VStore) -> bool {
+        let doms: Vec<_> = self.vars.iter().map(|v| v.read(vstore)).collect();
+        let reached = self.forward(&doms);
+        let n = self.automaton.num_states();
+        let last = doms.len();
+        let mut alive = vec![vec![false; n]; last + 1];
+        for q in 0..n {
+            alive[last][q] = reached[last][q] && self.automaton.accepting[q];
+        }
+        let mut new_doms = Vec::with_capacity(last);
+        for (i, dom) in doms.into_iter().enumerate().rev() {
+            let mut supported = vec![];
+            let mut defaults = vec![];
+            for q in 0..n {
+                if !reached[i][q] {
+                    continue;
+                }
+                for (v, to) in &self.automaton.transitions[q] {
+                    if alive[i + 1][*to] && dom.contains(v) {
+                        alive[i][q] = true;
+                        supported.push(v.clone());
+                    }
+                }
+                if let Some(to) = self.automaton.otherwise[q] {
+                    if alive[i + 1][to] && self.has_default_value(q, &dom) {
+                        alive[i][q] = true;
+                        defaults.push(q);
+                    }
+                }
+            }
+            new_doms.push(self.filter_domain(dom, supported, defaults));
+        }
+        self.alive = alive;
+        new_doms.reverse();
+        for (x, dom) in self.vars.iter_mut().zip(new_doms) {
+            if !x.update(vstore, dom) {
+                return false;
+            }
+        }
+        self.alive[0][self.automaton.initial]
+    }
+
+    fn is_stateful(&self) -> bool {
+        true
+    }
+}
+
+impl<VStore, Bound> PropagatorDependencies<FDEvent> for Regular<VStore, Bound> {
+    fn dependencies(&self) -> Vec<(usize, FDEvent)> {
+        let mut deps: Vec<_> = self
+            .vars
+            .iter()
+            .flat_map(|v| v.dependencies(FDEvent::Inner))
+            .collect();
+        deps.sort();
+        deps.dedup();
+        deps
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use gcollections::ops::*;
+    use interval::interval_set::*;
+    use search::test::*;
+    use variable::ops::*;
+    use variable::VStoreSet;
+
+    type Dom = IntervalSet<i32>;
+
+    /// Words over `{0, 1}` without `max + 1` consecutive `1`.
+    fn max_consecutive_ones(max: usize) -> Automaton<i32> {
+        let mut transitions = vec![];
+        for q in 0..max + 1 {
+            transitions.push((q, 0, 0));
+            if q < max {
+                transitions.push((q, 1, q + 1));
+            }
+        }
+        Automaton::new(max + 1, 0, (0..max + 1).collect(), transitions)
+    }
+
+    /// Words where `2` is never directly followed by `2`, the state `2` is a dead state.
+    fn no_double_two() -> Automaton<i32> {
+        Automaton::new(3, 0, vec![0, 1], vec![(0, 2, 1), (1, 2, 2)])
+            .with_default_transition(0, 0)
+            .with_default_transition(1, 0)
+    }
+
+    fn regular_test_one(
+        test_num: u32,
+        doms: Vec<Dom>,
+        automaton: Automaton<i32>,
+        before: SKleene,
+        after: SKleene,
+        expected: Option<Vec<Dom>>,
+    ) {
+        let mut vstore = VStoreSet::empty();
+        let vars: Vec<_> = doms
+            .into_iter()
+            .map(|d| Box::new(vstore.alloc(d)) as Var<VStoreSet>)
+            .collect();
+        let mut propagator = Regular::new(vars, automaton);
+        assert_eq!(
+            propagator.is_subsumed(&vstore),
+            before,
+            "Test {}.",
+            test_num
+        );
+        let success = propagator.propagate(&mut vstore);
+        assert_eq!(success, expected.is_some(), "Test {}.", test_num);
+        assert_eq!(propagator.is_subsumed(&vstore), after, "Test {}.", test_num);
+        if let Some(expected) = expected {
+            let doms: Vec<_> = vstore.iter().cloned().collect();
+            assert_eq!(doms, expected, "Test {}.", test_num);
+        }
+    }
+
+    fn dom(lb: i32, ub: i32) -> Dom {
+        (lb, ub).to_interval_set()
+    }
+
+    #[test]
+    fn regular_test() {
+        let one = dom(1, 1);
+        let bin = dom(0, 1);
+        regular_test_one(
+            1,
+            vec![dom(-5, 5), dom(0, 5), dom(0, 5)],
+            max_consecutive_ones(2),
+            Unknown,
+            Unknown,
+            Some(vec![bin.clone(), bin.clone(), bin.clone()]),
+        );
+        regular_test_one(
+            2,
+            vec![one.clone(), one.clone(), bin.clone(), one.clone()],
+            max_consecutive_ones(2),
+            Unknown,
+            True,
+            Some(vec![one.clone(), one.clone(), dom(0, 0), one.clone()]),
+        );
+        regular_test_one(
+            3,
+            vec![bin.clone(), one.clone(), one.clone(), one.clone()],
+            max_consecutive_ones(2),
+            False,
+            False,
+            None,
+        );
+        regular_test_one(
+            4,
+            vec![one.clone(), bin.clone(), one.clone()],
+            max_consecutive_ones(1),
+            Unknown,
+            True,
+            Some(vec![one.clone(), dom(0, 0), one.clone()]),
+        );
+        // The default transitions keep the values not appearing in the automaton.
+        regular_test_one(
+            5,
+            vec![dom(2, 2), dom(0, 5), dom(2, 2)],
+            no_double_two(),
+            Unknown,
+            Unknown,
+            Some(vec![
+                dom(2, 2),
+                vec![(0, 1), (3, 5)].to_interval_set(),
+                dom(2, 2),
+            ]),
+        );
+        regular_test_one(
+            6,
+            vec![dom(2, 2), dom(2, 2)],
+            no_double_two(),
+            False,
+            False,
+            None,
+        );
+    }
+
+    fn count_regular(n: usize, max: i32, automaton: Automaton<i32>, negate: bool) -> usize {
+        let mut space = FDSpace::empty();
+        let vars: Vec<_> = (0..n)
+            .map(|_| Box::new(space.vstore.alloc(dom(0, max))) as Var<VStore>)
+            .collect();
+        let regular = Regular::new(vars, automaton);
+        if negate {
+            space.cstore.alloc(regular.not());
+        } else {
+            space.cstore.alloc(Box::new(regular));
+        }
+        count_assignments(space, n)
+    }
+
+    fn brute_force<F>(n: usize, max: i32, accept: F) -> usize
+    where
+        F: Fn(&[i32]) -> bool,
+    {
+        let mut count = 0;
+        let mut word = vec![0; n];
+        loop {
+            if accept(&word) {
+                count += 1;
+            }
+            let mut i = 0;
+            while i < n && word[i] == max {
+                word[i] = 0;
+                i += 1;
+            }
+            if i == n {
+                return count;
+            }
+            word[i] += 1;
+        }
+    }
+
+    #[test]
+    fn regular_solutions_test() {
+        // At most 3 night shifts (the value 1) in a row.
+        let nights = |w: &[i32]| !w.windows(4).any(|w| w.iter().all(|&x| x == 1));
+        assert_eq!(
+            count_regular(7, 1, max_consecutive_ones(3), false),
+            brute_force(7, 1, nights)
+        );
+        assert_eq!(
+            count_regular(7, 1, max_consecutive_ones(3), true),
+            brute_force(7, 1, |w| !nights(w))
+        );
+        let no_two = |w: &[i32]| !w.windows(2).any(|w| w[0] == 2 && w[1] == 2);
+        assert_eq!(
+            count_regular(4, 3, no_double_two(), false),
+            brute_force(4, 3, no_two)
+        );
+        assert_eq!(
+            count_regular(4, 3, no_double_two(), true),
+            brute_force(4, 3, |w| !no_two(w))
+        );
+    }
+}
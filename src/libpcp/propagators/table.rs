@@ -0,0 +1,899 @@
+// Copyright 2018 Pierre Talbot (IRCAM)
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+
+//     http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! The extensional constraint `Table` restricts a sequence of variables to a list of allowed (or forbidden) tuples.
+//! The allowed tuples are filtered with the Compact-Table algorithm described in:
+//!   Demeulenaere, J., Hartert, R., Lecoutre, C., Perez, G., Perron, L., Régin, J. C., & Schaus, P. (2016). Compact-table: efficiently filtering table constraints with reversible sparse bit-sets. In International Conference on Principles and Practice of Constraint Programming (pp. 207-223). Springer.
+//! The forbidden tuples are filtered by counting the valid tuples supporting each value, as in:
+//!   Verhaeghe, H., Lecoutre, C., & Schaus, P. (2017). Extending Compact-Table to negative and short tables. In Thirty-First AAAI Conference on Artificial Intelligence.
+//! The current table (the set of valid tuples) is the internal state of the propagator, the modified words of its bitset are trailed and restored by the constraint store on backtracking (see `Propagator::is_trailed`).
+
+use concept::*;
+use gcollections::*;
+use kernel::*;
+use logic::*;
+use model::*;
+use propagation::events::*;
+use propagation::*;
+use propagators::values::restrict;
+use std::rc::Rc;
+use trilean::SKleene;
+use trilean::SKleene::*;
+
+#[derive(Debug)]
+struct TableData<Bound> {
+    num_tuples: usize,
+    /// `values[x]` are the values of the column `x` in increasing order.
+    values: Vec<Vec<Bound>>,
+    /// `supports[x][k]` is the set of tuples `t` such that `t[x] == values[x][k]`.
+    supports: Vec<Vec<Vec<u64>>>,
+}
+
+impl<Bound> TableData<Bound>
+where
+    Bound: IntBound,
+{
+    fn new(arity: usize, mut tuples: Vec<Vec<Bound>>) -> Self {
+        // Duplicated tuples would be counted twice in the support counts.
+        tuples.sort();
+        tuples.dedup();
+        let num_words = tuples.len().div_ceil(64);
+        let mut values = vec![];
+        let mut supports = vec![];
+        for x in 0..arity {
+            let mut column: Vec<_> = tuples.iter().map(|t| t[x].clone()).collect();
+            column.sort();
+            column.dedup();
+            let mut column_supports = vec![vec![0u64; num_words]; column.len()];
+            for (t, tuple) in tuples.iter().enumerate() {
+                let k = column.binary_search(&tuple[x]).unwrap();
+                column_supports[k][t / 64] |= 1 << (t % 64);
+            }
+            values.push(column);
+            supports.push(column_supports);
+        }
+        TableData {
+            num_tuples: tuples.len(),
+            values,
+            supports,
+        }
+    }
+}
+
+/// A set of tuples represented by a reversible sparse bitset: the non-zero words are indexed in `index[0..limit]` and the modifications of the words and of the limit are trailed.
+#[derive(Clone, Debug)]
+struct SparseBitSet {
+    words: Vec<u64>,
+    index: Vec<usize>,
+    limit: usize,
+    /// The mask intersected with the words, it is kept between the updates to avoid an allocation.
+    mask: Vec<u64>,
+    /// The previous values of the words and of the limit modified since the marks.
+    trail: Vec<TrailedValue>,
+    /// The words are trailed at most once between two marks: `stamps[offset]` is the time at which the word `offset` was last trailed, and `stamps[words.len()]` is the one of the limit.
+    stamps: Vec<usize>,
+    /// Incremented on each mark and undo.
+    time: usize,
+}
+
+#[derive(Clone, Debug)]
+enum TrailedValue {
+    Word(usize, u64),
+    Limit(usize),
+}
+
+impl SparseBitSet {
+    fn full(n: usize) -> Self {
+        let num_words = n.div_ceil(64);
+        let mut words = vec![!0u64; num_words];
+        let tail = n % 64;
+        if tail != 0 {
+            words[num_words - 1] = (1 << tail) - 1;
+        }
+        SparseBitSet {
+            words,
+            index: (0..num_words).collect(),
+            limit: num_words,
+            mask: vec![0u64; num_words],
+            trail: vec![],
+            stamps: vec![0; num_words + 1],
+            time: 0,
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.limit == 0
+    }
+
+    fn non_zero(&self) -> &[usize] {
+        &self.index[..self.limit]
+    }
+
+    fn mark(&mut self) -> usize {
+        self.time += 1;
+        self.trail.len()
+    }
+
+    fn undo(&mut self, mark: usize) {
+        while self.trail.len() > mark {
+            match self.trail.pop().unwrap() {
+                TrailedValue::Word(offset, w) => self.words[offset] = w,
+                TrailedValue::Limit(limit) => self.limit = limit,
+            }
+        }
+        self.time += 1;
+    }
+
+    fn set_word(&mut self, offset: usize, w: u64) {
+        if self.stamps[offset] != self.time {
+            self.stamps[offset] = self.time;
+            self.trail
+                .push(TrailedValue::Word(offset, self.words[offset]));
+        }
+        self.words[offset] = w;
+    }
+
+    fn set_limit(&mut self, limit: usize) {
+        let stamp = self.words.len();
+        if self.stamps[stamp] != self.time {
+            self.stamps[stamp] = self.time;
+            self.trail.push(TrailedValue::Limit(self.limit));
+        }
+        self.limit = limit;
+    }
+
+    fn clear_mask(&mut self) {
+        for i in 0..self.limit {
+            self.mask[self.index[i]] = 0;
+        }
+    }
+
+    fn add_to_mask(&mut self, words: &[u64]) {
+        for i in 0..self.limit {
+            let offset = self.index[i];
+            self.mask[offset] |= words[offset];
+        }
+    }
+
+    fn reverse_mask(&mut self) {
+        for i in 0..self.limit {
+            let offset = self.index[i];
+            self.mask[offset] = !self.mask[offset];
+        }
+    }
+
+    fn intersect_with_mask(&mut self) {
+        for i in (0..self.limit).rev() {
+            let offset = self.index[i];
+            let w = self.words[offset] & self.mask[offset];
+            if w != self.words[offset] {
+                self.set_word(offset, w);
+                if w == 0 {
+                    self.index.swap(i, self.limit - 1);
+                    let limit = self.limit - 1;
+                    self.set_limit(limit);
+                }
+            }
+        }
+    }
+
+    /// The index of a word overlapping with `mask`.
+    fn intersect_index(&self, mask: &[u64]) -> Option<usize> {
+        self.non_zero()
+            .iter()
+            .cloned()
+            .find(|&offset| self.words[offset] & mask[offset] != 0)
+    }
+
+    fn count(&self) -> usize {
+        self.non_zero()
+            .iter()
+            .map(|&offset| self.words[offset].count_ones() as usize)
+            .sum()
+    }
+
+    fn count_with(&self, mask: &[u64]) -> usize {
+        self.non_zero()
+            .iter()
+            .map(|&offset| (self.words[offset] & mask[offset]).count_ones() as usize)
+            .sum()
+    }
+}
+
+#[derive(Debug)]
+pub struct Table<VStore, Bound> {
+    vars: Vec<Var<VStore>>,
+    data: Rc<TableData<Bound>>,
+    allowed: bool,
+    current: SparseBitSet,
+    residues: Vec<Vec<usize>>,
+    /// `true` if the variables do not share a dependency.
+    distinct_vars: bool,
+}
+
+impl<VStore, Bound> Table<VStore, Bound>
+where
+    Bound: IntBound,
+{
+    /// The variables `vars` must be equal to one of the `tuples`.
+    pub fn new(vars: Vec<Var<VStore>>, tuples: Vec<Vec<Bound>>) -> Self {
+        Table::with_tuples(vars, tuples, true)
+    }
+
+    /// The variables `vars` must be different from every tuple in `tuples`.
+    pub fn forbidden(vars: Vec<Var<VStore>>, tuples: Vec<Vec<Bound>>) -> Self {
+        Table::with_tuples(vars, tuples, false)
+    }
+
+    fn with_tuples(vars: Vec<Var<VStore>>, tuples: Vec<Vec<Bound>>, allowed: bool) -> Self {
+        assert!(
+            tuples.iter().all(|t| t.len() == vars.len()),
+            "The tuples of a table must have the same length as the sequence of variables."
+        );
+        let data = Rc::new(TableData::new(vars.len(), tuples));
+        Table::with_data(vars, data, allowed)
+    }
+
+    fn with_data(vars: Vec<Var<VStore>>, data: Rc<TableData<Bound>>, allowed: bool) -> Self {
+        let residues = data.values.iter().map(|col| vec![0; col.len()]).collect();
+        let current = SparseBitSet::full(data.num_tuples);
+        let mut deps: Vec<_> = vars
+            .iter()
+            .flat_map(|v| v.dependencies(FDEvent::Inner))
+            .map(|(v, _)| v)
+            .collect();
+        let num_deps = deps.len();
+        deps.sort();
+        deps.dedup();
+        Table {
+            vars,
+            data,
+            allowed,
+            current,
+            residues,
+            distinct_vars: deps.len() == num_deps,
+        }
+    }
+}
+
+impl<VStore, Bound> Clone for Table<VStore, Bound>
+where
+    VStore: Collection,
+{
+    fn clone(&self) -> Self {
+        Table {
+            vars: self.vars.iter().map(|v| v.bclone()).collect(),
+            data: self.data.clone(),
+            allowed: self.allowed,
+            current: self.current.clone(),
+            residues: self.residues.clone(),
+            distinct_vars: self.distinct_vars,
+        }
+    }
+}
+
+impl<VStore, Bound> DisplayStateful<Model> for Table<VStore, Bound> {
+    fn display(&self, model: &Model) {
+        if self.allowed {
+            print!("table(");
+        } else {
+            print!("forbidden_table(");
+        }
+        model.display_array(&self.vars);
+        print!(", {} tuples)", self.data.num_tuples);
+    }
+}
+
+impl<VStore, Domain, Bound> NotFormula<VStore> for Table<VStore, Bound>
+where
+    VStore: VStoreConcept<Item = Domain> + 'static,
+    Domain: IntDomain<Item = Bound> + 'static,
+    Bound: IntBound + 'static,
+{
+    fn not(&self) -> Formula<VStore> {
+        let vars = self.vars.iter().map(|v| v.bclone()).collect();
+        Box::new(Table::with_data(vars, self.data.clone(), !self.allowed))
+    }
+}
+
+impl<VStore, Domain, Bound> Table<VStore, Bound>
+where
+    VStore: Collection<Item = Domain>,
+    Domain: IntDomain<Item = Bound>,
+    Bound: IntBound,
+{
+    fn read_all(&self, vstore: &VStore) -> Vec<Domain> {
+        self.vars.iter().map(|v| v.read(vstore)).collect()
+    }
+
+    /// Removes from the current table the tuples that are not valid anymore w.r.t. the domains `doms`.
+    fn update_table(&mut self, doms: &[Domain]) {
+        for (x, dom) in doms.iter().enumerate() {
+            self.update_column(x, dom, None);
+        }
+    }
+
+    /// Removes from the current table the tuples whose value in the column `x` is not in `dom`.
+    /// With the domain `old` of the column at the last update, only the supports of the values removed since are removed if they are fewer than the values left (the reset-based update of Compact-Table).
+    fn update_column(&mut self, x: usize, dom: &Domain, old: Option<&Domain>) {
+        let mut kept = vec![];
+        let mut removed = vec![];
+        for (k, v) in self.data.values[x].iter().enumerate() {
+            if dom.contains(v) {
+                kept.push(k);
+            } else if old.is_some_and(|old| old.contains(v)) {
+                removed.push(k);
+            }
+        }
+        self.current.clear_mask();
+        if old.is_some() && removed.len() < kept.len() {
+            for k in removed {
+                self.current.add_to_mask(&self.data.supports[x][k]);
+            }
+            self.current.reverse_mask();
+        } else {
+            for k in kept {
+                self.current.add_to_mask(&self.data.supports[x][k]);
+            }
+        }
+        self.current.intersect_with_mask();
+    }
+
+    /// The number of tuples of the current table that are valid w.r.t. the domains `doms`, the current table is not modified.
+    fn count_valid(&self, doms: &[Domain]) -> usize {
+        let mut words = self.current.words.clone();
+        for (x, dom) in doms.iter().enumerate() {
+            let mut mask = vec![0u64; words.len()];
+            for (k, v) in self.data.values[x].iter().enumerate() {
+                if dom.contains(v) {
+                    for &offset in self.current.non_zero() {
+                        mask[offset] |= self.data.supports[x][k][offset];
+                    }
+                }
+            }
+            for &offset in self.current.non_zero() {
+                words[offset] &= mask[offset];
+            }
+        }
+        self.current
+            .non_zero()
+            .iter()
+            .map(|&offset| words[offset].count_ones() as usize)
+            .sum()
+    }
+
+    /// The filtering of the allowed tuples is idempotent when the variables are distinct: a value removed from a domain is not supported by any valid tuple, hence the valid tuples stay the same.
+    fn status(&self, vstore: &VStore, consistent: bool) -> PropagatorStatus {
+        if !consistent {
+            PropagatorStatus::Failed
+        } else if !self.allowed || !self.distinct_vars {
+            PropagatorStatus::NotFixpoint
+        } else {
+            let doms = self.read_all(vstore);
+            let num_valid = self.current.count();
+            if self.product_size(&doms, None, num_valid) == Some(num_valid) {
+                PropagatorStatus::Subsumed
+            } else {
+                PropagatorStatus::Fixpoint
+            }
+        }
+    }
+
+    /// Filters the domains with the current table, already updated.
+    fn filter(&mut self, vstore: &mut VStore, doms: Vec<Domain>) -> bool {
+        if self.allowed {
+            !self.current.is_empty() && self.propagate_allowed(vstore)
+        } else {
+            self.current.is_empty() || self.propagate_forbidden(vstore, doms)
+        }
+    }
+
+    /// The number of values of `dom`, if all of them appear in the column `x`.
+    /// Otherwise, `dom` has a value not appearing in any tuple and we return `None`.
+    fn column_size(&self, x: usize, dom: &Domain) -> Option<usize> {
+        let mut rest = dom.clone();
+        let mut size = 0;
+        for v in &self.data.values[x] {
+            if rest.contains(v) {
+                size += 1;
+                rest = rest.difference(v);
+            }
+        }
+        if rest.is_empty() {
+            Some(size)
+        } else {
+            None
+        }
+    }
+
+    /// The number of tuples in the Cartesian product of the domains of `vars` (except `x`), if it is not greater than `max`.
+    fn product_size(&self, doms: &[Domain], except: Option<usize>, max: usize) -> Option<usize> {
+        let mut product: usize = 1;
+        for (x, dom) in doms.iter().enumerate() {
+            if Some(x) != except {
+                product = product.checked_mul(self.column_size(x, dom)?)?;
+                if product > max {
+                    return None;
+                }
+            }
+        }
+        Some(product)
+    }
+
+    fn supported_values(&mut self, x: usize, dom: &Domain) -> Vec<Bound> {
+        let mut supported = vec![];
+        for (k, v) in self.data.values[x].iter().enumerate() {
+            if dom.contains(v) {
+                let support = &self.data.supports[x][k];
+                let residue = self.residues[x][k];
+                if self.current.words[residue] & support[residue] != 0 {
+                    supported.push(v.clone());
+                } else if let Some(offset) = self.current.intersect_index(support) {
+                    self.residues[x][k] = offset;
+                    supported.push(v.clone());
+                }
+            }
+        }
+        supported
+    }
+
+    fn propagate_allowed(&mut self, vstore: &mut VStore) -> bool {
+        for x in 0..self.vars.len() {
+            let dom = self.vars[x].read(vstore);
+            let supported = self.supported_values(x, &dom);
+            if !self.vars[x].update(vstore, restrict(dom, &supported)) {
+                return false;
+            }
+        }
+        true
+    }
+
+    fn propagate_forbidden(&mut self, vstore: &mut VStore, doms: Vec<Domain>) -> bool {
+        let num_valid = self.current.count();
+        for x in 0..self.vars.len() {
+            // A value is not supported if the valid forbidden tuples cover all the assignments of the other variables.
+            if let Some(product) = self.product_size(&doms, Some(x), num_valid) {
+                let mut dom = self.vars[x].read(vstore);
+                for (k, v) in self.data.values[x].iter().enumerate() {
+                    if dom.contains(v)
+                        && self.current.count_with(&self.data.supports[x][k]) == product
+                    {
+                        dom = dom.difference(v);
+                    }
+                }
+                if !self.vars[x].update(vstore, dom) {
+                    return false;
+                }
+            }
+        }
+        true
+    }
+}
+
+impl<VStore, Domain, Bound> Subsumption<VStore> for Table<VStore, Bound>
+where
+    VStore: Collection<Item = Domain>,
+    Domain: IntDomain<Item = Bound>,
+    Bound: IntBound,
+{
+    fn is_subsumed(&self, vstore: &VStore) -> SKleene {
+        // Allowed tuples:
+        //   False: No tuple is valid.
+        //   True: Every assignment of the variables is a valid tuple.
+        // Forbidden tuples:
+        //   False: Every assignment of the variables is a valid tuple.
+        //   True: No tuple is valid.
+        // Unknown: Everything else.
+        let doms = self.read_all(vstore);
+        let num_valid = self.count_valid(&doms);
+        let all_valid =
+            num_valid > 0 && self.product_size(&doms, None, num_valid) == Some(num_valid);
+        match (num_valid == 0, all_valid) {
+            (true, _) if self.allowed => False,
+            (true, _) => True,
+            (_, true) if self.allowed => True,
+            (_, true) => False,
+            _ => Unknown,
+        }
+    }
+}
+
+impl<VStore, Domain, Bound> Propagator<VStore> for Table<VStore, Bound>
+where
+    VStore: Collection<Item = Domain>,
+    Domain: IntDomain<Item = Bound>,
+    Bound: IntBound,
+{
+    fn propagate(&mut self, vstore: &mut VStore) -> bool {
+        let doms = self.read_all(vstore);
+        self.update_table(&doms);
+        self.filter(vstore, doms)
+    }
+
+    fn propagate_status(&mut self, vstore: &mut VStore) -> PropagatorStatus {
+        let consistent = self.propagate(vstore);
+        self.status(vstore, consistent)
+    }
+
+    fn is_stateful(&self) -> bool {
+        true
+    }
+
+    fn is_trailed(&self) -> bool {
+        true
+    }
+
+    fn mark(&mut self) -> usize {
+        self.current.mark()
+    }
+
+    fn undo(&mut self, mark: usize) {
+        self.current.undo(mark);
+    }
+
+    fn is_incremental(&self) -> bool {
+        true
+    }
+
+    /// Only the columns depending on a modified variable are updated, from the old domain of the variable unless the column is a view.
+    fn propagate_delta(
+        &mut self,
+        vstore: &mut VStore,
+        delta: &[VarDelta<Domain>],
+    ) -> PropagatorStatus {
+        let doms = self.read_all(vstore);
+        for (x, dom) in doms.iter().enumerate() {
+            let modified = self.vars[x]
+                .dependencies(FDEvent::Inner)
+                .iter()
+                .any(|&(v, _)| delta.iter().any(|d| d.var == v));
+            if modified {
+                let old = self.vars[x]
+                    .variable_index()
+                    .and_then(|v| delta.iter().find(|d| d.var == v))
+                    .map(|d| &d.old);
+                self.update_column(x, dom, old);
+            }
+        }
+        let consistent = self.filter(vstore, doms);
+        self.status(vstore, consistent)
+    }
+}
+
+impl<VStore, Bound> PropagatorDependencies<FDEvent> for Table<VStore, Bound> {
+    fn dependencies(&self) -> Vec<(usize, FDEvent)> {
+        let mut deps: Vec<_> = self
+            .vars
+            .iter()
+            .flat_map(|v| v.dependencies(FDEvent::Inner))
+            .collect();
+        deps.sort();
+        deps.dedup();
+        deps
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use gcollections::ops::*;
+    use interval::interval_set::*;
+    use propagators::cmp::*;
+    use search::test::*;
+    use search::*;
+    use term::*;
+    use variable::ops::*;
+    use variable::{VStoreCopy, VStoreSet};
+
+    type Dom = IntervalSet<i32>;
+
+    fn table_test_one(
+        test_num: u32,
+        doms: Vec<Dom>,
+        tuples: Vec<Vec<i32>>,
+        allowed: bool,
+        before: SKleene,
+        after: SKleene,
+        expected: Option<Vec<Dom>>,
+    ) {
+        let mut vstore = VStoreSet::empty();
+        let vars: Vec<_> = doms
+            .into_iter()
+            .map(|d| Box::new(vstore.alloc(d)) as Var<VStoreSet>)
+            .collect();
+        let mut propagator = if allowed {
+            Table::new(vars, tuples)
+        } else {
+            Table::forbidden(vars, tuples)
+        };
+        assert_eq!(
+            propagator.is_subsumed(&vstore),
+            before,
+            "Test {}.",
+            test_num
+        );
+        let success = propagator.propagate(&mut vstore);
+        assert_eq!(success, expected.is_some(), "Test {}.", test_num);
+        assert_eq!(propagator.is_subsumed(&vstore), after, "Test {}.", test_num);
+        if let Some(expected) = expected {
+            let doms: Vec<_> = vstore.iter().cloned().collect();
+            assert_eq!(doms, expected, "Test {}.", test_num);
+        }
+    }
+
+    fn dom(lb: i32, ub: i32) -> Dom {
+        (lb, ub).to_interval_set()
+    }
+
+    fn set(values: Vec<i32>) -> Dom {
+        values
+            .into_iter()
+            .map(|v| (v, v))
+            .collect::<Vec<_>>()
+            .to_interval_set()
+    }
+
+    fn tuples() -> Vec<Vec<i32>> {
+        vec![
+            vec![0, 0, 1],
+            vec![0, 2, 2],
+            vec![1, 1, 0],
+            vec![3, 0, 0],
+            vec![3, 2, 1],
+        ]
+    }
+
+    #[test]
+    fn allowed_table_test() {
+        table_test_one(
+            1,
+            vec![dom(-5, 5), dom(0, 10), dom(0, 10)],
+            tuples(),
+            true,
+            Unknown,
+            Unknown,
+            Some(vec![set(vec![0, 1, 3]), dom(0, 2), dom(0, 2)]),
+        );
+        table_test_one(
+            2,
+            vec![dom(0, 3), dom(1, 2), dom(0, 3)],
+            tuples(),
+            true,
+            Unknown,
+            Unknown,
+            Some(vec![set(vec![0, 1, 3]), dom(1, 2), set(vec![0, 1, 2])]),
+        );
+        table_test_one(
+            3,
+            vec![dom(3, 3), dom(0, 1), dom(0, 3)],
+            tuples(),
+            true,
+            Unknown,
+            True,
+            Some(vec![dom(3, 3), dom(0, 0), dom(0, 0)]),
+        );
+        table_test_one(
+            4,
+            vec![dom(1, 2), dom(0, 0), dom(0, 3)],
+            tuples(),
+            true,
+            False,
+            False,
+            None,
+        );
+        table_test_one(
+            5,
+            vec![dom(0, 0), dom(0, 2), dom(0, 3)],
+            vec![vec![0, 0, 0], vec![0, 1, 0], vec![0, 2, 0], vec![0, 0, 0]],
+            true,
+            Unknown,
+            True,
+            Some(vec![dom(0, 0), dom(0, 2), dom(0, 0)]),
+        );
+        table_test_one(6, vec![dom(0, 3)], vec![], true, False, False, None);
+    }
+
+    #[test]
+    fn forbidden_table_test() {
+        table_test_one(
+            1,
+            vec![dom(0, 3), dom(0, 3), dom(0, 3)],
+            tuples(),
+            false,
+            Unknown,
+            Unknown,
+            Some(vec![dom(0, 3), dom(0, 3), dom(0, 3)]),
+        );
+        // With `x = 3` and `y = 0`, the value 0 of `z` is forbidden.
+        table_test_one(
+            2,
+            vec![dom(3, 3), dom(0, 0), dom(0, 1)],
+            tuples(),
+            false,
+            Unknown,
+            True,
+            Some(vec![dom(3, 3), dom(0, 0), dom(1, 1)]),
+        );
+        // With `y = 0` and `z = 0`, the value 3 of `x` is forbidden.
+        table_test_one(
+            3,
+            vec![dom(0, 3), dom(0, 0), dom(0, 0)],
+            tuples(),
+            false,
+            Unknown,
+            True,
+            Some(vec![dom(0, 2), dom(0, 0), dom(0, 0)]),
+        );
+        table_test_one(
+            4,
+            vec![dom(0, 1), dom(0, 1)],
+            vec![vec![0, 0], vec![0, 1], vec![1, 0], vec![1, 1]],
+            false,
+            False,
+            False,
+            None,
+        );
+        table_test_one(
+            5,
+            vec![dom(4, 5), dom(0, 1)],
+            vec![vec![0, 0], vec![1, 1]],
+            false,
+            True,
+            True,
+            Some(vec![dom(4, 5), dom(0, 1)]),
+        );
+    }
+
+    #[test]
+    fn negation_test() {
+        let mut vstore = VStoreSet::empty();
+        let vars: Vec<_> = (0..3)
+            .map(|_| Box::new(vstore.alloc(dom(0, 3))) as Var<VStoreSet>)
+            .collect();
+        let mut x = vars[0].bclone();
+        let mut y = vars[1].bclone();
+        let table = Table::new(vars, tuples());
+        let mut not_table = table.not();
+        assert!(not_table.propagate(&mut vstore));
+        assert_eq!(not_table.is_subsumed(&vstore), Unknown);
+        assert!(x.update(&mut vstore, dom(3, 3)));
+        assert!(y.update(&mut vstore, dom(0, 0)));
+        assert!(not_table.propagate(&mut vstore));
+        assert_eq!(vstore[2], set(vec![1, 2, 3]));
+    }
+
+    fn count_table(allowed: bool) -> usize {
+        let mut space = FDSpace::empty();
+        let vars: Vec<_> = (0..3)
+            .map(|_| Box::new(space.vstore.alloc(dom(0, 3))) as Var<VStore>)
+            .collect();
+        space.cstore.alloc(Box::new(XLessY::new(
+            vars[1].bclone(),
+            Box::new(Addition::new(vars[0].bclone(), 1)),
+        )));
+        if allowed {
+            space.cstore.alloc(Box::new(Table::new(vars, tuples())));
+        } else {
+            space
+                .cstore
+                .alloc(Box::new(Table::forbidden(vars, tuples())));
+        }
+        count_assignments(space, 3)
+    }
+
+    #[test]
+    fn table_solutions_test() {
+        // The tuples satisfying `y <= x` are (0,0,1), (1,1,0), (3,0,0) and (3,2,1).
+        assert_eq!(count_table(true), 4);
+        // There are 4 * (1 + 2 + 3 + 4) = 40 assignments satisfying `y <= x`.
+        assert_eq!(count_table(false), 36);
+    }
+
+    /// `x`, `y` and `z` in `[0..3]` with the allowed `tuples()`, `x` is restricted to `x_values` after the first propagation.
+    fn delta_test_one(test_num: u32, x_values: Vec<i32>, expected: Vec<Dom>) {
+        let mut vstore = VStoreSet::empty();
+        let vars: Vec<_> = (0..3)
+            .map(|_| Box::new(vstore.alloc(dom(0, 3))) as Var<VStoreSet>)
+            .collect();
+        let mut x = vars[0].bclone();
+        let mut propagator = Table::new(vars, tuples());
+        assert!(propagator.propagate(&mut vstore), "Test {}.", test_num);
+        let old = x.read(&vstore);
+        assert!(x.update(&mut vstore, set(x_values)), "Test {}.", test_num);
+        let event = FDEvent::new(&x.read(&vstore), &old).unwrap();
+        let delta = vec![VarDelta { var: 0, event, old }];
+        assert_ne!(
+            propagator.propagate_delta(&mut vstore, &delta),
+            PropagatorStatus::Failed,
+            "Test {}.",
+            test_num
+        );
+        let doms: Vec<_> = vstore.iter().cloned().collect();
+        assert_eq!(doms, expected, "Test {}.", test_num);
+    }
+
+    #[test]
+    fn table_delta_test() {
+        // The supports of the removed value `1` are removed.
+        delta_test_one(
+            1,
+            vec![0, 3],
+            vec![set(vec![0, 3]), set(vec![0, 2]), dom(0, 2)],
+        );
+        // The supports of the remaining value `3` are kept.
+        delta_test_one(2, vec![3], vec![dom(3, 3), set(vec![0, 2]), dom(0, 1)]);
+    }
+
+    #[test]
+    fn sparse_bit_set_trail_test() {
+        let mut set = SparseBitSet::full(130);
+        let intersect = |set: &mut SparseBitSet, mask: Vec<u64>| {
+            set.clear_mask();
+            set.add_to_mask(&mask);
+            set.intersect_with_mask();
+        };
+        let root = set.mark();
+        intersect(&mut set, vec![0b1010, 0, !0]);
+        assert_eq!(set.count(), 4);
+        let child = set.mark();
+        intersect(&mut set, vec![0b10, 0, 0]);
+        intersect(&mut set, vec![0, 0, 0]);
+        assert!(set.is_empty());
+        set.undo(child);
+        assert_eq!(set.count(), 4);
+        assert_eq!(set.non_zero().len(), 2);
+        set.undo(root);
+        assert_eq!(set.count(), 130);
+        assert_eq!(set.non_zero().len(), 3);
+    }
+
+    fn restoration_test<VStore>()
+    where
+        VStore: VStoreConcept<Item = Dom> + Freeze + Empty + Cardinality<Size = usize>,
+        VStore: DrainDelta<FDEvent> + AdviseDelta + Iterable + 'static,
+    {
+        type CStore<VStore> = CStoreFD<VStore>;
+        type Space<VStore> =
+            ::search::space::Space<VStore, CStore<VStore>, NoRecomputation<VStore, CStore<VStore>>>;
+        let mut space: Space<VStore> = Space::empty();
+        let vars: Vec<_> = (0..2)
+            .map(|_| Box::new(space.vstore.alloc(dom(0, 2))) as Var<VStore>)
+            .collect();
+        let x = vars[0].bclone();
+        let tuples = vec![vec![0, 0], vec![1, 2], vec![2, 1]];
+        space.cstore.alloc(Box::new(Table::new(vars, tuples)));
+        assert_eq!(space.consistency(), Unknown);
+        let mut frozen = space.freeze();
+        let label_1 = frozen.label();
+        let label_2 = frozen.label();
+        let expected = vec![(1, 2), (0, 0)];
+        for (label, (value, y)) in vec![label_1, label_2].into_iter().zip(expected) {
+            let mut space = frozen.restore(label);
+            space.cstore.alloc(Box::new(XEqY::new(
+                x.bclone(),
+                Box::new(Constant::new(value)),
+            )));
+            assert_eq!(space.consistency(), True);
+            let doms: Vec<_> = space.vstore.iter().cloned().collect();
+            assert_eq!(doms, vec![dom(value, value), dom(y, y)]);
+            frozen = space.freeze();
+        }
+    }
+
+    #[test]
+    fn table_restoration_test() {
+        restoration_test::<VStoreSet>();
+        restoration_test::<VStoreCopy<Dom>>();
+    }
+}
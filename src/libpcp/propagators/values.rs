@@ -0,0 +1,50 @@
+// Copyright 2018 Pierre Talbot (IRCAM)
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+
+//     http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Operations on sets of values shared by several propagators.
+
+use concept::*;
+
+/// Restricts `dom` to the values in `values` (sorted in increasing order).
+pub(crate) fn restrict<Domain, Bound>(dom: Domain, values: &[Bound]) -> Domain
+where
+    Domain: IntDomain<Item = Bound>,
+    Bound: IntBound,
+{
+    if values.is_empty() {
+        return Domain::empty();
+    }
+    let mut dom = dom
+        .shrink_left(values[0].clone())
+        .shrink_right(values[values.len() - 1].clone());
+    for w in values.windows(2) {
+        if w[1] > w[0].clone() + Bound::one() {
+            dom = dom.difference(&Domain::new(
+                w[0].clone() + Bound::one(),
+                w[1].clone() - Bound::one(),
+            ));
+        }
+    }
+    dom
+}
+
+/// The integers `0..=n`.
+pub(crate) fn naturals<Bound: IntBound>(n: usize) -> Vec<Bound> {
+    let mut naturals = vec![Bound::zero()];
+    for i in 0..n {
+        let next = naturals[i].clone() + Bound::one();
+        naturals.push(next);
+    }
+    naturals
+}
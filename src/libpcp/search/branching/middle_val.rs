@@ -23,6 +23,6 @@ where
     Bound: IntBound,
 {
     fn select(&mut self, dom: Domain) -> Bound {
-        (dom.lower() + dom.upper()) / (Bound::one() + Bound::one())
+        (dom.lower() + dom.upper()).div_floor(&(Bound::one() + Bound::one()))
     }
 }
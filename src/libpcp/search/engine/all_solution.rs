@@ -53,6 +53,8 @@ mod test {
     use super::*;
     use gcollections::ops::*;
     use gcollections::VectorStack;
+    use propagation::schedulers::*;
+    use propagation::Scheduler;
     use search::branching::binary_split::*;
     use search::branching::brancher::*;
     use search::branching::first_smallest_var::*;
@@ -62,7 +64,7 @@ mod test {
     use search::propagation::*;
     use search::statistics::*;
     use search::test::*;
-    use search::FDSpace;
+    use search::FDSpaceWith;
 
     #[test]
     fn example_nqueens() {
@@ -70,18 +72,22 @@ mod test {
         let nqueens_solution = vec![1, 0, 0, 2, 10, 4, 40, 92, 352];
 
         for (n, sol) in nqueens_solution.into_iter().enumerate() {
-            test_nqueens(n + 1, sol, EndOfSearch);
+            test_nqueens::<RelaxedFifo>(n + 1, sol, EndOfSearch);
+            test_nqueens::<CostQueues>(n + 1, sol, EndOfSearch);
         }
     }
 
-    fn test_nqueens(n: usize, sol_expected: usize, expect: Status<FDSpace>) {
-        let mut space = FDSpace::empty();
+    fn test_nqueens<S>(n: usize, sol_expected: usize, expect: Status<FDSpaceWith<S>>)
+    where
+        S: Scheduler + 'static,
+    {
+        let mut space = FDSpaceWith::<S>::empty();
         nqueens(n, &mut space);
 
         let mut statistics = Statistics::new();
         {
             let mut search: AllSolution<
-                Monitor<Statistics, OneSolution<_, VectorStack<_>, FDSpace>>,
+                Monitor<Statistics, OneSolution<_, VectorStack<_>, FDSpaceWith<S>>>,
             > = AllSolution::new(Monitor::new(
                 &mut statistics,
                 OneSolution::new(Propagation::new(Brancher::new(
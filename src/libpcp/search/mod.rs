@@ -32,36 +32,48 @@ pub use search::search_tree_visitor::*;
 pub use search::space::*;
 
 use gcollections::VectorStack;
-use propagation::CStoreFD;
+use propagation::schedulers::RelaxedFifo;
+use propagation::CStoreFDWith;
+use propagation::Scheduler;
 use search::branching::*;
 use search::engine::one_solution::*;
 use search::propagation::*;
 use variable::VStoreSet;
 
 pub type VStore = VStoreSet;
-type CStore = CStoreFD<VStore>;
-pub type FDSpace = Space<VStore, CStore, NoRecomputation<VStore, CStore>>;
+type CStore<S> = CStoreFDWith<VStore, S>;
+/// A space whose constraints store uses the scheduler `S`.
+pub type FDSpaceWith<S> = Space<VStore, CStore<S>, NoRecomputation<VStore, CStore<S>>>;
+pub type FDSpace = FDSpaceWith<RelaxedFifo>;
 
 pub fn one_solution_engine() -> Box<dyn SearchTreeVisitor<FDSpace>> {
-    let search = OneSolution::<_, VectorStack<_>, FDSpace>::new(Propagation::new(Brancher::new(
-        FirstSmallestVar,
-        MiddleVal,
-        BinarySplit,
-    )));
+    one_solution_engine_with()
+}
+
+pub fn one_solution_engine_with<S>() -> Box<dyn SearchTreeVisitor<FDSpaceWith<S>>>
+where
+    S: Scheduler + 'static,
+{
+    let search = OneSolution::<_, VectorStack<_>, FDSpaceWith<S>>::new(Propagation::new(
+        Brancher::new(FirstSmallestVar, MiddleVal, BinarySplit),
+    ));
     Box::new(search)
 }
 
 #[cfg(test)]
-mod test {
+pub(crate) mod test {
     pub use super::*;
     use concept::*;
     use gcollections::ops::*;
     use interval::interval_set::*;
     use propagators::cmp::*;
     use propagators::distinct::*;
+    use search::engine::all_solution::*;
+    use search::monitor::*;
     use term::*;
+    use variable::ops::*;
 
-    pub fn nqueens(n: usize, space: &mut FDSpace) {
+    pub fn nqueens<S: Scheduler>(n: usize, space: &mut FDSpaceWith<S>) {
         let mut queens: Vec<Var<VStore>> = vec![];
         // 2 queens can't share the same line.
         for _ in 0..n {
@@ -89,4 +101,43 @@ mod test {
         // 2 queens can't share the same column.
         space.cstore.alloc(Box::new(Distinct::new(queens)));
     }
+
+    /// A solution node is not necessarily fully assigned, so we count the assignments of the first `num_vars` variables it contains.
+    struct AssignmentCounter {
+        num_vars: usize,
+        count: usize,
+    }
+
+    impl SearchMonitor<FDSpace> for AssignmentCounter {
+        fn on_solution(&mut self, space: &FDSpace) {
+            self.count += space
+                .vstore
+                .iter()
+                .take(self.num_vars)
+                .map(|d| d.size() as usize)
+                .product::<usize>();
+        }
+    }
+
+    /// Number of assignments of the `num_vars` first variables satisfying the constraints of `space`.
+    /// The other variables must be functionally defined by these first variables.
+    pub fn count_assignments(space: FDSpace, num_vars: usize) -> usize {
+        let mut counter = AssignmentCounter { num_vars, count: 0 };
+        {
+            let mut search: AllSolution<
+                Monitor<AssignmentCounter, OneSolution<_, VectorStack<_>, FDSpace>>,
+            > = AllSolution::new(Monitor::new(
+                &mut counter,
+                OneSolution::new(Propagation::new(Brancher::new(
+                    FirstSmallestVar,
+                    MiddleVal,
+                    BinarySplit,
+                ))),
+            ));
+            search.start(&space);
+            let (_, status) = search.enter(space);
+            assert_eq!(status, Status::EndOfSearch);
+        }
+        counter.count
+    }
 }
@@ -0,0 +1,255 @@
+// Copyright 2018 Pierre Talbot (IRCAM)
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+
+//     http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! The term `array[index]` where `index` is a variable and `array` contains variables or constants.
+//! The array is indexed from 0 and the values of `index` outside the array are not valid.
+
+use concept::*;
+use gcollections::*;
+use kernel::*;
+use model::*;
+use propagation::events::*;
+use term::ops::*;
+use term::Constant;
+
+#[derive(Debug)]
+pub struct Element<VStore> {
+    array: Vec<Var<VStore>>,
+    index: Var<VStore>,
+}
+
+impl<VStore> Element<VStore> {
+    pub fn new(array: Vec<Var<VStore>>, index: Var<VStore>) -> Self {
+        Element { array, index }
+    }
+}
+
+impl<VStore, Domain, Bound> Element<VStore>
+where
+    VStore: VStoreConcept<Item = Domain> + 'static,
+    Domain: IntDomain<Item = Bound> + 'static,
+    Bound: IntBound + 'static,
+{
+    pub fn constant(array: Vec<Bound>, index: Var<VStore>) -> Self {
+        Element::new(
+            array
+                .into_iter()
+                .map(|v| Box::new(Constant::new(v)) as Var<VStore>)
+                .collect(),
+            index,
+        )
+    }
+}
+
+impl<VStore, Domain, Bound> Element<VStore>
+where
+    VStore: Collection<Item = Domain>,
+    Domain: IntDomain<Item = Bound>,
+    Bound: IntBound,
+{
+    /// The domain of `index` restricted to the positions of the array.
+    fn index_in_range(&self, store: &VStore) -> Domain {
+        let len = self
+            .array
+            .iter()
+            .fold(Bound::zero(), |len, _| len + Bound::one());
+        self.index
+            .read(store)
+            .shrink_left(Bound::zero())
+            .shrink_right(len - Bound::one())
+    }
+
+    /// The positions `i` in the domain of `index` with the value `i` of `index`.
+    fn positions(&self, index: &Domain) -> Vec<(usize, Bound)> {
+        let mut positions = vec![];
+        let mut value = Bound::zero();
+        for i in 0..self.array.len() {
+            if index.contains(&value) {
+                positions.push((i, value.clone()));
+            }
+            value = value + Bound::one();
+        }
+        positions
+    }
+
+    /// `true` if every value of `index` is a position of the array.
+    pub fn is_index_valid(&self, store: &VStore) -> bool {
+        self.index
+            .read(store)
+            .is_subset(&self.index_in_range(store))
+    }
+
+    /// The domains `array[i] ∩ value` that are not empty for every possible position `i`.
+    pub fn compatible(&self, store: &VStore, value: &Domain) -> Vec<Domain> {
+        let index = self.index_in_range(store);
+        self.positions(&index)
+            .into_iter()
+            .map(|(i, _)| self.array[i].read(store).intersection(value))
+            .filter(|d| !d.is_empty())
+            .collect()
+    }
+}
+
+impl<VStore> Clone for Element<VStore>
+where
+    VStore: Collection,
+{
+    fn clone(&self) -> Self {
+        Element::new(
+            self.array.iter().map(|v| v.bclone()).collect(),
+            self.index.bclone(),
+        )
+    }
+}
+
+impl<VStore> DisplayStateful<Model> for Element<VStore> {
+    fn display(&self, model: &Model) {
+        model.display_array(&self.array);
+        print!("[");
+        self.index.display(model);
+        print!("]");
+    }
+}
+
+impl<VStore, Domain, Bound> StoreMonotonicUpdate<VStore> for Element<VStore>
+where
+    VStore: Collection<Item = Domain>,
+    Domain: IntDomain<Item = Bound>,
+    Bound: IntBound,
+{
+    /// Remove from `index` the positions of the elements not overlapping with `value`.
+    /// If `index` is assigned, the corresponding element is updated.
+    fn update(&mut self, store: &mut VStore, value: Domain) -> bool {
+        let mut index = self.index_in_range(store);
+        for (i, v) in self.positions(&index) {
+            if !self.array[i].read(store).overlap(&value) {
+                index = index.difference(&v);
+            }
+        }
+        if !self.index.update(store, index.clone()) {
+            return false;
+        }
+        if index.is_singleton() {
+            let (i, _) = self.positions(&index)[0];
+            let element = self.array[i].read(store).intersection(&value);
+            self.array[i].update(store, element)
+        } else {
+            true
+        }
+    }
+}
+
+impl<VStore, Domain, Bound> StoreRead<VStore> for Element<VStore>
+where
+    VStore: Collection<Item = Domain>,
+    Domain: IntDomain<Item = Bound>,
+    Bound: IntBound,
+{
+    /// The smallest interval containing every element at a possible position.
+    fn read(&self, store: &VStore) -> Domain {
+        let index = self.index_in_range(store);
+        let elements: Vec<_> = self
+            .positions(&index)
+            .into_iter()
+            .map(|(i, _)| self.array[i].read(store))
+            .filter(|d| !d.is_empty())
+            .collect();
+        let lb = elements.iter().map(|d| d.lower()).min();
+        let ub = elements.iter().map(|d| d.upper()).max();
+        match (lb, ub) {
+            (Some(lb), Some(ub)) => Domain::new(lb, ub),
+            _ => Domain::empty(),
+        }
+    }
+}
+
+impl<VStore> ViewDependencies<FDEvent> for Element<VStore> {
+    fn dependencies(&self, event: FDEvent) -> Vec<(usize, FDEvent)> {
+        // Removing any value of `index` might change the elements that can be read.
+        let mut deps: Vec<_> = self
+            .index
+            .dependencies(FDEvent::Inner)
+            .into_iter()
+            .chain(self.array.iter().flat_map(|v| v.dependencies(event)))
+            .collect();
+        deps.sort();
+        deps.dedup();
+        deps
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use gcollections::ops::*;
+    use interval::interval_set::*;
+    use propagators::cmp::*;
+    use search::test::*;
+    use trilean::SKleene::*;
+    use variable::VStoreSet;
+
+    #[test]
+    fn element_read_update_test() {
+        let mut store = VStoreSet::empty();
+        let index = Box::new(store.alloc((-1, 5).to_interval_set())) as Var<VStoreSet>;
+        let mut element = Element::constant(vec![5, 1, 8, 3], index);
+        assert_eq!(element.read(&store), (1, 8).to_interval_set());
+        // The values outside the array are removed from the index.
+        assert!(element.update(&mut store, (0, 10).to_interval_set()));
+        assert_eq!(store[0], (0, 3).to_interval_set());
+        // Only the positions 0 and 2 contain a value greater than 3.
+        assert!(element.update(&mut store, (4, 10).to_interval_set()));
+        assert_eq!(store[0], vec![(0, 0), (2, 2)].to_interval_set());
+        assert_eq!(element.read(&store), (5, 8).to_interval_set());
+        assert!(!element.update(&mut store, (9, 10).to_interval_set()));
+    }
+
+    #[test]
+    fn element_assigned_index_test() {
+        let mut store = VStoreSet::empty();
+        let a: Vec<Var<VStoreSet>> = vec![(0, 5), (3, 9)]
+            .into_iter()
+            .map(|d| Box::new(store.alloc(d.to_interval_set())) as Var<VStoreSet>)
+            .collect();
+        let index = Box::new(store.alloc((1, 1).to_interval_set())) as Var<VStoreSet>;
+        let mut element = Element::new(a, index);
+        assert_eq!(element.read(&store), (3, 9).to_interval_set());
+        assert!(element.update(&mut store, (0, 4).to_interval_set()));
+        assert_eq!(store[0], (0, 5).to_interval_set());
+        assert_eq!(store[1], (3, 4).to_interval_set());
+    }
+
+    #[test]
+    fn x_less_element_test() {
+        let mut space = FDSpace::empty();
+        let x = Box::new(space.vstore.alloc((6, 10).to_interval_set())) as Var<VStore>;
+        let index = Box::new(space.vstore.alloc((0, 2).to_interval_set())) as Var<VStore>;
+        let element = Box::new(Element::constant(vec![5, 1, 8], index));
+        space.cstore.alloc(Box::new(XLessY::new(x, element)));
+        assert_eq!(space.cstore.consistency(&mut space.vstore), True);
+        assert_eq!(space.vstore[0], (6, 7).to_interval_set());
+        assert_eq!(space.vstore[1], (2, 2).to_interval_set());
+    }
+
+    #[test]
+    fn x_less_element_solutions_test() {
+        // x < [2, 0, 3][i] has 2 + 0 + 3 solutions.
+        let mut space = FDSpace::empty();
+        let x = Box::new(space.vstore.alloc((0, 3).to_interval_set())) as Var<VStore>;
+        let index = Box::new(space.vstore.alloc((0, 2).to_interval_set())) as Var<VStore>;
+        let element = Box::new(Element::constant(vec![2, 0, 3], index));
+        space.cstore.alloc(Box::new(XLessY::new(x, element)));
+        assert_eq!(count_assignments(space, 2), 5);
+    }
+}
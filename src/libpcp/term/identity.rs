@@ -67,6 +67,10 @@ impl<Domain> ViewDependencies<FDEvent> for Identity<Domain> {
     fn dependencies(&self, event: FDEvent) -> Vec<(usize, FDEvent)> {
         vec![(self.idx, event)]
     }
+
+    fn variable_index(&self) -> Option<usize> {
+        Some(self.idx)
+    }
 }
 
 #[cfg(test)]
@@ -14,11 +14,15 @@
 
 pub mod addition;
 pub mod constant;
+pub mod element;
 pub mod identity;
 pub mod ops;
 pub mod sum;
+pub mod weighted_sum;
 
 pub use term::addition::Addition;
 pub use term::constant::Constant;
+pub use term::element::Element;
 pub use term::identity::Identity;
 pub use term::sum::Sum;
+pub use term::weighted_sum::WeightedSum;
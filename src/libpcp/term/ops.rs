@@ -25,6 +25,11 @@ pub trait StoreRead<Store: Collection> {
 
 pub trait ViewDependencies<Event> {
     fn dependencies(&self, event: Event) -> Vec<(usize, Event)>;
+
+    /// The index of the variable if the view reads its domain unchanged from the store, the old domain of a `VarDelta` is then the old domain of the view.
+    fn variable_index(&self) -> Option<usize> {
+        None
+    }
 }
 
 impl<Store, R> StoreMonotonicUpdate<Store> for Box<R>
@@ -54,4 +59,8 @@ where
     fn dependencies(&self, event: Event) -> Vec<(usize, Event)> {
         self.deref().dependencies(event)
     }
+
+    fn variable_index(&self) -> Option<usize> {
+        self.deref().variable_index()
+    }
 }
@@ -0,0 +1,245 @@
+// Copyright 2018 Pierre Talbot (IRCAM)
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+
+//     http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! The term `a1*x1 + ... + an*xn` where the coefficients `ai` are integers.
+//! Updating this term prunes the bounds of each variable in a single pass over the terms.
+
+use concept::*;
+use gcollections::kind::*;
+use kernel::*;
+use model::*;
+use propagation::events::*;
+use std::fmt::{Debug, Formatter, Result};
+use term::ops::*;
+
+pub struct WeightedSum<VStore>
+where
+    VStore: VStoreConcept,
+    VStore::Item: Collection,
+{
+    pub(crate) coefs: Vec<<VStore::Item as Collection>::Item>,
+    pub(crate) vars: Vec<Var<VStore>>,
+}
+
+impl<VStore, Domain, Bound> WeightedSum<VStore>
+where
+    VStore: VStoreConcept<Item = Domain>,
+    Domain: Collection<Item = Bound>,
+{
+    pub fn new(coefs: Vec<Bound>, vars: Vec<Var<VStore>>) -> Self {
+        assert_eq!(
+            coefs.len(),
+            vars.len(),
+            "A weighted sum must have one coefficient per variable."
+        );
+        WeightedSum { coefs, vars }
+    }
+}
+
+impl<VStore, Domain, Bound> Debug for WeightedSum<VStore>
+where
+    VStore: VStoreConcept<Item = Domain>,
+    Domain: Collection<Item = Bound>,
+    Bound: Debug,
+{
+    fn fmt(&self, fmt: &mut Formatter) -> Result {
+        fmt.debug_struct("WeightedSum")
+            .field("coefs", &self.coefs)
+            .field("vars", &self.vars)
+            .finish()
+    }
+}
+
+impl<VStore, Domain, Bound> Clone for WeightedSum<VStore>
+where
+    VStore: VStoreConcept<Item = Domain>,
+    Domain: Collection<Item = Bound>,
+    Bound: Clone,
+{
+    fn clone(&self) -> Self {
+        WeightedSum::new(
+            self.coefs.clone(),
+            self.vars.iter().map(|v| v.bclone()).collect(),
+        )
+    }
+}
+
+impl<VStore, Domain, Bound> DisplayStateful<Model> for WeightedSum<VStore>
+where
+    VStore: VStoreConcept<Item = Domain>,
+    Domain: Collection<Item = Bound>,
+    Bound: Debug,
+{
+    fn display(&self, model: &Model) {
+        print!("(");
+        for (i, (a, x)) in self.coefs.iter().zip(self.vars.iter()).enumerate() {
+            if i > 0 {
+                print!(" + ");
+            }
+            print!("{:?}*", a);
+            x.display(model);
+        }
+        print!(")");
+    }
+}
+
+impl<VStore, Domain, Bound> WeightedSum<VStore>
+where
+    VStore: VStoreConcept<Item = Domain>,
+    Domain: IntDomain<Item = Bound>,
+    Bound: IntBound,
+{
+    /// The bounds of `ai*xi` for each term.
+    fn term_bounds(&self, store: &VStore) -> Vec<(Bound, Bound)> {
+        self.coefs
+            .iter()
+            .zip(self.vars.iter())
+            .map(|(a, x)| {
+                let x = x.read(store);
+                let l = a.clone() * x.lower();
+                let u = a.clone() * x.upper();
+                if l <= u {
+                    (l, u)
+                } else {
+                    (u, l)
+                }
+            })
+            .collect()
+    }
+}
+
+impl<VStore, Domain, Bound> StoreMonotonicUpdate<VStore> for WeightedSum<VStore>
+where
+    VStore: VStoreConcept<Item = Domain>,
+    Domain: IntDomain<Item = Bound>,
+    Bound: IntBound,
+{
+    /// For each term, `ai*xi` lies between `lower(value) - max(other terms)` and `upper(value) - min(other terms)`.
+    fn update(&mut self, store: &mut VStore, value: Domain) -> bool {
+        if value.is_empty() {
+            return false;
+        }
+        let bounds = self.term_bounds(store);
+        let (sum_min, sum_max) = bounds
+            .iter()
+            .fold((Bound::zero(), Bound::zero()), |(l, u), (tl, tu)| {
+                (l + tl.clone(), u + tu.clone())
+            });
+        if sum_min > value.upper() || sum_max < value.lower() {
+            return false;
+        }
+        for (i, (min_i, max_i)) in bounds.into_iter().enumerate() {
+            let a = self.coefs[i].clone();
+            if a.is_zero() {
+                continue;
+            }
+            let lb = value.lower() - (sum_max.clone() - max_i);
+            let ub = value.upper() - (sum_min.clone() - min_i);
+            let (lb, ub) = if a.is_positive() {
+                (div_ceil(lb, &a), ub.div_floor(&a))
+            } else {
+                (div_ceil(ub, &a), lb.div_floor(&a))
+            };
+            let x = self.vars[i].read(store).shrink_left(lb).shrink_right(ub);
+            if !self.vars[i].update(store, x) {
+                return false;
+            }
+        }
+        true
+    }
+}
+
+impl<VStore, Domain, Bound> StoreRead<VStore> for WeightedSum<VStore>
+where
+    VStore: VStoreConcept<Item = Domain>,
+    Domain: IntDomain<Item = Bound>,
+    Bound: IntBound,
+{
+    fn read(&self, store: &VStore) -> Domain {
+        let bounds = self.term_bounds(store);
+        let (l, u) = bounds
+            .into_iter()
+            .fold((Bound::zero(), Bound::zero()), |(l, u), (tl, tu)| {
+                (l + tl, u + tu)
+            });
+        Domain::new(l, u)
+    }
+}
+
+impl<VStore, Domain, Bound> ViewDependencies<FDEvent> for WeightedSum<VStore>
+where
+    VStore: VStoreConcept<Item = Domain>,
+    Domain: Collection<Item = Bound>,
+    Bound: IntBound,
+{
+    fn dependencies(&self, event: FDEvent) -> Vec<(usize, FDEvent)> {
+        let mut deps: Vec<_> = self
+            .coefs
+            .iter()
+            .zip(self.vars.iter())
+            .filter(|(a, _)| !a.is_zero())
+            .flat_map(|(_, x)| x.dependencies(event))
+            .collect();
+        deps.sort();
+        deps.dedup();
+        deps
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use gcollections::ops::*;
+    use interval::interval::*;
+    use propagation::*;
+    use propagators::cmp::*;
+    use variable::VStoreFD;
+
+    type VStore = VStoreFD;
+
+    fn vars(store: &mut VStore, doms: Vec<(i32, i32)>) -> Vec<Var<VStore>> {
+        doms.into_iter()
+            .map(|d| Box::new(store.alloc(d.to_interval())) as Var<VStore>)
+            .collect()
+    }
+
+    #[test]
+    fn read_update_test() {
+        let mut store = VStore::empty();
+        let xs = vars(&mut store, vec![(0, 10), (-2, 3), (1, 4)]);
+        let mut sum = WeightedSum::new(vec![2, -3, 1], xs);
+        assert_eq!(sum.read(&store), (-8, 30).to_interval());
+        // 2x - 3y + z = 0 with -3y >= -9 and z >= 1: 2x <= 9 - 1 = 8.
+        assert!(sum.update(&mut store, (0, 0).to_interval()));
+        assert_eq!(store[0], (0, 4).to_interval());
+        // -3y <= -(0 + 1) so y >= 1.
+        assert_eq!(store[1], (1, 3).to_interval());
+        assert_eq!(store[2], (1, 4).to_interval());
+        assert!(!sum.update(&mut store, (20, 30).to_interval()));
+    }
+
+    #[test]
+    fn x_leq_weighted_sum_test() {
+        let mut store = VStore::empty();
+        let xs = vars(&mut store, vec![(0, 10), (0, 10)]);
+        let y = Box::new(store.alloc((0, 5).to_interval())) as Var<VStore>;
+        let sum = Box::new(WeightedSum::new(vec![3, 2], xs));
+        // 3x1 + 2x2 <= y
+        let mut p = x_leq_y(sum, y);
+        assert!(p.propagate(&mut store));
+        assert_eq!(store[0], (0, 1).to_interval());
+        assert_eq!(store[1], (0, 2).to_interval());
+        assert_eq!(store[2], (0, 5).to_interval());
+    }
+}
@@ -13,6 +13,7 @@
 // limitations under the License.
 
 use gcollections::kind::*;
+use propagation::ops::VarDelta;
 use std::slice;
 use vec_map::Drain;
 
@@ -22,6 +23,18 @@ pub trait DrainDelta<Event> {
     fn reset_changed(&mut self);
 }
 
+/// Keeps the old domains of the modified variables for the incremental propagators (see `Propagator::propagate_delta`).
+pub trait AdviseDelta: Collection {
+    /// Starts recording the domains of the variables before their modification, the recording stays enabled after a restoration.
+    fn record_old_domains(&mut self);
+    /// Keeps, for the propagator `prop`, the domain that `var` had before its modifications since the last `forget_old_domains`, unless an older domain is already kept.
+    fn advise(&mut self, prop: usize, var: usize);
+    fn forget_old_domains(&mut self);
+    /// The modifications kept for `prop` since the last call, or `None` if none was kept or an old domain was not recorded.
+    /// The events of the modifications are `FDEvent`, the events of the propagators (see `PropagatorDependencies`), whatever the events of the store.
+    fn take_advice(&mut self, prop: usize) -> Option<Vec<VarDelta<Self::Item>>>;
+}
+
 pub trait Iterable: Collection {
     fn iter(&self) -> slice::Iter<'_, Self::Item>;
 }
@@ -16,7 +16,9 @@ use gcollections::kind::*;
 use gcollections::ops::*;
 use kernel::*;
 use model::*;
-use std::fmt::{Debug, Display};
+use propagation::events::FDEvent;
+use propagation::ops::VarDelta;
+use std::fmt::{Debug, Display, Formatter, Result};
 use std::marker::PhantomData;
 use std::ops::Index;
 use std::slice;
@@ -25,11 +27,70 @@ use variable::concept::*;
 use variable::ops::*;
 use vec_map::{Drain, VecMap};
 
-#[derive(Clone, Debug, PartialEq, Eq)]
-pub struct Store<Memory, Event> {
+pub struct Store<Memory, Event>
+where
+    Memory: MemoryConcept,
+{
     memory: Memory,
     delta: VecMap<Event>,
     has_changed: bool,
+    /// See `AdviseDelta`: the domains before the modifications since the last `forget_old_domains`, they are only recorded if `record_old` is set.
+    old: VecMap<<Memory as Collection>::Item>,
+    /// The old domains kept for each propagator, `None` if one of them was not recorded.
+    advised: VecMap<Option<VecMap<<Memory as Collection>::Item>>>,
+    record_old: bool,
+}
+
+impl<Memory, Event> Clone for Store<Memory, Event>
+where
+    Memory: MemoryConcept + Clone,
+    Memory::Item: Clone,
+    Event: Clone,
+{
+    fn clone(&self) -> Self {
+        Store {
+            memory: self.memory.clone(),
+            delta: self.delta.clone(),
+            has_changed: self.has_changed,
+            old: self.old.clone(),
+            advised: self.advised.clone(),
+            record_old: self.record_old,
+        }
+    }
+}
+
+impl<Memory, Event> Debug for Store<Memory, Event>
+where
+    Memory: MemoryConcept,
+    Event: Debug,
+{
+    fn fmt(&self, fmt: &mut Formatter) -> Result {
+        fmt.debug_struct("Store")
+            .field("memory", &self.memory)
+            .field("delta", &self.delta)
+            .field("has_changed", &self.has_changed)
+            .finish()
+    }
+}
+
+/// The old domains kept for the incremental propagators are not compared.
+impl<Memory, Event> PartialEq for Store<Memory, Event>
+where
+    Memory: MemoryConcept + PartialEq,
+    Event: PartialEq,
+{
+    fn eq(&self, other: &Self) -> bool {
+        self.memory == other.memory
+            && self.delta == other.delta
+            && self.has_changed == other.has_changed
+    }
+}
+
+impl<Memory, Event> Eq for Store<Memory, Event>
+where
+    Memory: MemoryConcept + Eq,
+    Event: Eq,
+{
 }
 
 impl<Memory, Event> Collection for Store<Memory, Event>
@@ -65,11 +126,14 @@ impl<Memory, Event> Store<Memory, Event>
 where
     Memory: MemoryConcept,
 {
-    fn from_memory(memory: Memory) -> Self {
+    fn from_memory(memory: Memory, record_old: bool) -> Self {
         Store {
             memory,
             delta: VecMap::new(),
             has_changed: false,
+            old: VecMap::new(),
+            advised: VecMap::new(),
+            record_old,
         }
     }
 }
@@ -79,7 +143,7 @@ where
     Memory: MemoryConcept,
 {
     fn empty() -> Store<Memory, Event> {
-        Store::from_memory(Memory::empty())
+        Store::from_memory(Memory::empty(), false)
     }
 }
 
@@ -91,9 +155,12 @@ where
     Event: EventConcept<Domain>,
 {
     // FIXME: Need a rustc fix on borrowing rule, `updated` not needed.
-    fn update_delta(&mut self, key: usize, old_dom: &Domain) {
-        if let Some(delta) = Event::new(&self[key], old_dom) {
+    fn update_delta(&mut self, key: usize, old_dom: Domain) {
+        if let Some(delta) = Event::new(&self[key], &old_dom) {
             self.has_changed = true;
+            if self.record_old && !self.old.contains_key(key) {
+                self.old.insert(key, old_dom);
+            }
             let mut updated = false;
             if let Some(old_delta) = self.delta.get_mut(key) {
                 *old_delta = Merge::merge(old_delta.clone(), delta.clone());
@@ -159,7 +226,7 @@ where
         } else {
             if dom.size() < self[idx].size() {
                 let old_dom = self.memory.replace(idx, dom);
-                self.update_delta(idx, &old_dom);
+                self.update_delta(idx, old_dom);
             }
             true
         }
@@ -222,7 +289,10 @@ where
     }
 }
 
-impl<Memory, Event> DrainDelta<Event> for Store<Memory, Event> {
+impl<Memory, Event> DrainDelta<Event> for Store<Memory, Event>
+where
+    Memory: MemoryConcept,
+{
     fn drain_delta(&mut self) -> Drain<'_, Event> {
         self.delta.drain()
     }
@@ -236,6 +306,49 @@ impl<Memory, Event> DrainDelta<Event> for Store<Memory, Event> {
     }
 }
 
+impl<Memory, Domain, Bound, Event> AdviseDelta for Store<Memory, Event>
+where
+    Memory: MemoryConcept<Item = Domain>,
+    Domain: Subset + Cardinality + Bounded<Item = Bound> + Clone,
+    Bound: PartialEq + Eq,
+{
+    fn record_old_domains(&mut self) {
+        self.record_old = true;
+    }
+
+    fn advise(&mut self, prop: usize, var: usize) {
+        let old = self.old.get(var);
+        let advice = self
+            .advised
+            .entry(prop)
+            .or_insert_with(|| Some(VecMap::new()));
+        match (advice.as_mut(), old) {
+            (Some(doms), Some(old)) => {
+                doms.entry(var).or_insert_with(|| old.clone());
+            }
+            _ => *advice = None,
+        }
+    }
+
+    fn forget_old_domains(&mut self) {
+        self.old.clear();
+    }
+
+    /// The events are computed with `FDEvent` since `VarDelta` is given to the propagators, the `Event` of the store only describes its own delta.
+    fn take_advice(&mut self, prop: usize) -> Option<Vec<VarDelta<Domain>>> {
+        match self.advised.remove(prop) {
+            None | Some(None) => None,
+            Some(Some(doms)) => Some(
+                doms.into_iter()
+                    .filter_map(|(var, old)| {
+                        FDEvent::new(&self[var], &old).map(|event| VarDelta { var, event, old })
+                    })
+                    .collect(),
+            ),
+        }
+    }
+}
+
 impl<Memory, Event> Freeze for Store<Memory, Event>
 where
     Memory: MemoryConcept,
@@ -251,6 +364,7 @@ where
     Memory: MemoryConcept,
 {
     frozen_memory: Memory::FrozenState,
+    record_old: bool,
     phantom_event: PhantomData<Event>,
 }
 
@@ -261,6 +375,7 @@ where
     fn new(store: Store<Memory, Event>) -> Self {
         FrozenStore {
             frozen_memory: store.memory.freeze(),
+            record_old: store.record_old,
             phantom_event: PhantomData,
         }
     }
@@ -278,7 +393,7 @@ where
     }
 
     fn restore(self, label: Self::Label) -> Self::State {
-        Store::from_memory(self.frozen_memory.restore(label))
+        Store::from_memory(self.frozen_memory.restore(label), self.record_old)
     }
 }
 
@@ -541,4 +656,30 @@ pub mod test {
             |store, v1, v2| v1.read(store).intersection(&v2.read(store)),
         );
     }
+
+    #[test]
+    fn advice_test() {
+        use propagation::ops::VarDelta;
+        let mut store = VStore::empty();
+        let mut var = store.alloc((0, 10).to_interval());
+        let mut not_recorded = store.alloc((0, 10).to_interval());
+        assert!(not_recorded.update(&mut store, (0, 5).to_interval()));
+        store.record_old_domains();
+        assert!(var.update(&mut store, (0, 5).to_interval()));
+        assert!(var.update(&mut store, (2, 5).to_interval()));
+        store.advise(0, 0);
+        store.advise(1, 0);
+        store.advise(1, 1);
+        store.forget_old_domains();
+        let expected = VarDelta {
+            var: 0,
+            event: Bound,
+            old: (0, 10).to_interval(),
+        };
+        assert_eq!(store.take_advice(0), Some(vec![expected]));
+        // The advice is taken once, and is not available for an unadvised propagator.
+        assert_eq!(store.take_advice(0), None);
+        assert_eq!(store.take_advice(2), None);
+        assert_eq!(store.take_advice(1), None);
+    }
 }